@@ -14,6 +14,66 @@ chartPromise.then(function () {
   destroyMain(chartId); // promise
 });
 
+// destroy all charts at once, e.g. when a whole dashboard unmounts
+destroyAll();
+
+// returns an array of ids of the charts which are currently mounted, e.g. to
+// reconcile them with the state of a framework managing their lifecycles
+let chartIds = getChartIds();
+
 // OPTIONAL: if you want to run all the initialization code before createMain
 // to minimize latency of the first call
 init(); // promise
+
+// returns a PNG data url of the chart as it is currently shown, e.g. to be
+// used as href of a download link; the OPTIONAL scale renders it at a higher
+// resolution (e.g. 3 for print), the chart on the page is left untouched
+let dataUrl = exportImage(chartId);
+let printDataUrl = exportImage(chartId, 3);
+
+// returns an SVG string of the chart as it is currently shown, for crisp
+// vector embedding in reports; text is laid out with fontWidthCoeff
+let svg = exportSvg(chartId);
+
+// returns the visible data point nearest to the given position (css pixels
+// relative to the chart container) as {name, coord, value}, or null when the
+// position is outside of the plot
+let point = nearestPointAt(chartId, event.offsetX, event.offsetY);
+
+// switches between breaking lines at gaps (missing values & maxGap) and
+// connecting across them, e.g. to see the overall trend
+setConnectGaps(chartId, true);
+
+// shows or hides a series by name (animated, the views are rescaled as on a
+// legend click), e.g. to drive visibility from external checkboxes; unlike
+// the legend this may hide every series
+setSeriesVisibility(chartId, "Foo", false);
+
+// returns an array of names of the series which are currently shown
+let visibleSeries = getVisibleSeries(chartId);
+
+// replaces the data of a chart; accepts the same dataSets, contentName & axis
+// titles as createMain (coordType & valueType cannot change), the zoomed in
+// window is kept according to the updateZoomPolicy config option
+updateData(chartId, { dataSets: [...] });
+
+// recomputes the data bounds & redraws the chart, keeps it consistent after
+// programmatic changes
+refresh(chartId);
+
+// only available when built with the "debug" cargo feature: returns a JSON
+// string describing the current render state (viewport, ticks, visible series,
+// tooltip & legend state), handy for golden-file tests
+let snapshot = debugSnapshot(chartId);
+// lines like "viewport.coordMax: 10 -> 5" for what changed since the last
+// debugSnapshot call
+let changes = debugSnapshotDiff(chartId);
+
+// all of the above throw (or reject) with an error object, e.g. to highlight
+// the offending field of a form:
+//   { code: "params", path: "dataSets[2].coords.5", message: "failed to parse as f64" }
+// code is one of "config", "params" or "runtime"; path is null when the error
+// is not bound to an input field
+chartPromise.catch(function (error) {
+  console.error(error.code, error.path, error.message);
+});
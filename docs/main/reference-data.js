@@ -1,22 +1,71 @@
 const params = {
-  // css selector of where to put the chart
+  // css selector of where to put the chart; creating a chart in a container
+  // which already has one destroys the old chart first
   selector: "#chart-1",
 
-  // optional content name (not used at the moment)
+  // OPTIONAL: title drawn above the chart
   contentName: "New chart",
 
+  // OPTIONAL: called once with the chart id after the chart is drawn for the
+  // first time, e.g. to remove a loading spinner
+  // onReady: (chartId) => spinner.remove(),
+
+  // OPTIONAL: called with an array of the tooltips shown whenever it changes
+  // (an empty array once they are gone), e.g. to render rich HTML tooltips
+  // together with tooltipRenderer: "custom"; each tooltip looks like:
+  // {
+  //     "coord": the matched coord
+  //     "formattedCoord": the coord as the tooltip heading shows it
+  //     "rows": list of {name, value, formattedValue, color} in tooltip order
+  //     "x", "y": the pointer position in css pixels relative to the chart
+  //     "pointX", "pointY": the position of the highlighted point, likewise
+  // }
+  // onTooltip: (tooltips) => renderHtmlTooltips(tooltips),
+
   // one of 3 supported data types:
-  //  * "date"
-  //  * "datetime"
+  //  * "date" (timestamps in ms)
+  //  * "datetime" (timestamps in seconds)
   //  * "number"
   coordType: "date",
   valueType: "number",
 
+  // OPTIONAL: titles drawn below the x axis & along the y axis
+  // coordAxisTitle: "Date",
+  // valueAxisTitle: "Requests",
+
+  // OPTIONAL: timezone of "datetime" coords & values in minutes east of UTC,
+  // e.g. -300 for New York (EST); defaults to the browser's one
+  // tzOffsetMinutes: -300,
+
   // list of objects like:
   // {
   //     "name": name of a series
   //     "coords"; list of coordinates of coordType type
-  //     "values": list of values of valueType type
+  //     "values": list of values of valueType type; null or NaN mean there is
+  //               no data and break the line
+  //     "coordOffset": OPTIONAL constant added to every coordinate (in coord
+  //                    units, i.e. ms for dates), e.g. to align series
+  //                    recorded with clock skew
+  //     "color": OPTIONAL rgb color like [255, 0, 0], overrides the palette
+  //     "maxGap": OPTIONAL max distance between adjacent coordinates (in coord
+  //               units) to connect; larger gaps break the line
+  //     "area": OPTIONAL true to fill the area between the line and the
+  //             baseline (see the baseline config option)
+  //     "interpolation": OPTIONAL how adjacent points are connected: "linear"
+  //                      (default), "step-after" (a value holds until the
+  //                      next point, e.g. for settings & digital signals),
+  //                      "step-before" (a value holds since the previous point)
+  //                      or "monotone" (a smooth curve which doesn't overshoot
+  //                      the points)
+  //     "bucket": OPTIONAL width of coord intervals (in coord units, i.e. ms
+  //               for dates) to aggregate the data points into, one point per
+  //               interval, e.g. 3600000 for hourly points
+  //     "agg": OPTIONAL aggregation of values within a bucket: "mean"
+  //            (default), "min", "max" or "sum"
+  //     "valuesLow", "valuesHigh": OPTIONAL lower & upper bounds of the value
+  //                                at each coordinate, e.g. a confidence
+  //                                interval; drawn as a translucent band
+  //                                around the line & shown in the tooltip
   // }
   dataSets: [
     {
@@ -26,4 +75,43 @@ const params = {
       values: [10, "20", 30.0],
     },
   ],
+
+  // OPTIONAL: data sets computed from the ones above, drawn & toggled like
+  // any other; recomputed by updateData; list of objects like:
+  // {
+  //     "name": name of the derived series
+  //     "from": name of the source data set
+  //     "window": number of data points to average over
+  //     "type": OPTIONAL "sma" (simple moving average, default) or "ema"
+  //             (exponential moving average)
+  //     "color": OPTIONAL rgb color like [255, 0, 0], overrides the palette
+  // }
+  // derivedDataSets: [{ name: "Foo (7d avg)", from: "Foo", window: 7 }],
+
+  // OPTIONAL: reference lines & shaded bands across the plot, list of
+  // objects like:
+  // {
+  //     "type": OPTIONAL "line" (default) or "band"
+  //     "axis": "value" for a horizontal line / band at values, "coord" for a
+  //             vertical one at coordinates
+  //     "at": value of valueType or coordinate of coordType type, lines only
+  //     "from" & "to": bounds of a band, of the same types as "at"
+  //     "color": OPTIONAL rgb color like [255, 0, 0], defaults to colorTick;
+  //              bands are translucent
+  //     "label": OPTIONAL text drawn near the end of a line or in the top
+  //              left corner of a band
+  // }
+  // annotations: [
+  //   { axis: "value", at: 25, color: [255, 0, 0], label: "SLA" },
+  //   { type: "band", axis: "coord", from: "2020-01-02", to: "2020-01-03" },
+  // ],
+
+  // OPTIONAL: labeled points, list of objects like:
+  // {
+  //     "coord": coordinate of coordType type
+  //     "value": value of valueType type
+  //     "color": OPTIONAL rgb color like [255, 0, 0], defaults to colorTick
+  //     "label": OPTIONAL text drawn in a callout next to the point
+  // }
+  // markers: [{ coord: "2020-01-02", value: 20, label: "deploy" }],
 };
@@ -50,9 +50,14 @@ const CONFIG = {
     [150, 130, 29],
   ],
 
-  // long press duration in ms
+  // long press duration in ms; on touch devices holding a finger still on
+  // the plot this long shows the tooltip until the finger is lifted
   msLongPress: 500,
 
+  // double-click window in ms; a second click on a legend item within it
+  // shows only that series (or all of them again)
+  msDoubleClick: 300,
+
   // automatically switch to pseudo-log scale when charts take N-times more
   // vertical space.
   // pseudo-log scale means: log10(value - globalMinValue + 1000.0) - 3.0;
@@ -62,4 +67,250 @@ const CONFIG = {
   // number of significant digits when fallen back to scientific notation:
   //  1.234e6
   expFmtSignificantDigits: 5,
+
+  // OPTIONAL: formats of coordinates & values (tooltip and ticks)
+  //  * for "date" / "datetime" types: chrono format string, e.g. "%Y-%m-%d"
+  //    or "%Y-%m-%dT%H:%M:%S%:z" for ISO-8601
+  //  * for "number" type: "<precision>.<scale>", either part can be omitted,
+  //    e.g. ".2" for 2 decimal places
+  //  without coordFormat, date ticks adapt to the zoom level: "2023",
+  //  "Q3 2023", "Aug 2023", "Aug 17", "Thu 17", "14:05"
+  // coordFormat: "%Y-%m-%d",
+  // valueFormat: ".2",
+
+  // OPTIONAL: force animations on (true) or off (false); when omitted,
+  // animations follow the OS-level "prefers-reduced-motion" setting
+  // animate: false,
+
+  // OPTIONAL: duration of animations (zooming, toggling data sets, fading
+  // ticks) in milliseconds, 0 disables them
+  // animationDurationMs: 200,
+
+  // OPTIONAL: easing of animations, one of: "smooth" (default, accelerates
+  // then cruises), "linear", "easeOut"
+  // animationEasing: "smooth",
+
+  // OPTIONAL: number formatting separators, e.g. "." & "," for German locale
+  groupSeparator: ",",
+  decimalSeparator: ".",
+
+  // OPTIONAL: outline the preview when zoomed out to hint it is draggable
+  previewGripOutline: false,
+
+  // OPTIONAL: abbreviations of large numbers in ticks, one of:
+  //  * "short" - K, M, B, T, Q (default)
+  //  * "si" - k, M, G, T, P
+  numberAbbreviations: "short",
+
+  // OPTIONAL: alpha to draw hidden series with (0 - don't draw them at all);
+  // hidden series never affect autoscaling
+  hiddenGhostAlpha: 0,
+
+  // OPTIONAL: measure coordinate tick labels and skip the ones overlapping
+  // their neighbors, keeping labels of the current tick generation first
+  avoidTickLabelCollisions: true,
+
+  // OPTIONAL: while hovering a legend item, show its series and dim the others
+  // without toggling anything
+  legendHoverPreview: false,
+
+  // OPTIONAL: value area fills originate from (bottom of the plot when
+  // omitted); fills below it are drawn fainter than the ones above
+  // baseline: 0,
+
+  // OPTIONAL: format dates via the browser's Intl.DateTimeFormat to get
+  // localized month names, 12/24h clock, etc; takes precedence over
+  // coordFormat/valueFormat for dates. intlLocale defaults to the browser one
+  // intlLocale: "de-DE",
+  // intlDateFormat: { year: "numeric", month: "short", day: "numeric" },
+
+  // OPTIONAL: distance in css px the pointer has to move while pressed to be
+  // treated as a drag rather than a click; shorter drags neither select nor
+  // zoom, so imprecise taps don't zoom into a sliver
+  dragThreshold: 3,
+
+  // OPTIONAL: large series are decimated to ~2 points per pixel of the plot
+  // width, which isn't known before the first layout. When set, series are
+  // decimated to this number of points in advance, so the first frame is
+  // drawn with it and the exact resolution is computed on the next one
+  // decimationTargetPoints: 2000,
+
+  // OPTIONAL: how large series are decimated:
+  //  - "lttb" (largest triangle three buckets) preserves the shape
+  //  - "minmax" preserves the extremes
+  //  - "nth" takes every n-th point, the fastest one
+  //  - "none" always draws every point
+  decimationMode: "lttb",
+
+  // OPTIONAL: show "© Plotica" in the bottom right corner of the preview
+  showWatermark: true,
+
+  // OPTIONAL: texts to translate; "{}" in hintHidden is replaced with the
+  // number of tooltip rows which didn't fit
+  hintZoomOut: "Click to zoom out",
+  hintZoomIn: "Drag here or above to zoom in",
+  hintHidden: "{} hidden",
+  hintShowAll: "All",
+  hintHideAll: "None",
+  hintAllHidden: "All series are hidden",
+  hintNoData: "No data",
+
+  // OPTIONAL: connect lines across gaps (missing values & maxGap) initially,
+  // can be toggled at runtime with setConnectGaps
+  connectGaps: false,
+
+  // OPTIONAL: right-to-left layout of the tooltip & legend, e.g. for Arabic
+  // or Hebrew
+  rtl: false,
+
+  // OPTIONAL: what happens to a zoomed in window on updateData:
+  //  - "reset" zooms out to the full range
+  //  - "preserve" keeps the exact coord window, even if it's partially empty
+  //  - "clampToData" keeps the window size, shifting it into the new data
+  //    range, e.g. to stay pinned to "now" while streaming
+  updateZoomPolicy: "reset",
+
+  // OPTIONAL: the axis drags & pinches are interpreted along: "x", "y" or
+  // "both"; on touch devices the page keeps scrolling along the other one,
+  // e.g. "x" lets users scroll past the chart vertically
+  panAxis: "both",
+
+  // OPTIONAL: when zoomed in, draws the whole series faintly behind the
+  // zoomed in lines (squeezed into the plot area) to keep the overall trend in
+  // sight
+  contextGhost: false,
+
+  // OPTIONAL: always labels value 0, in bold, when zero is in the visible
+  // value range, even if the grid would not place a tick there; see zeroLine
+  // for the line itself
+  zeroTick: false,
+
+  // OPTIONAL: draws a bolder line in colorTick at value 0 (over the grid &
+  // under the series) when zero is in the visible value range
+  zeroLine: false,
+
+  // OPTIONAL: draws a line from the highlighted tooltip point down to the
+  // coord axis, where its coord is labeled in a box over the ticks
+  spikeLine: false,
+
+  // OPTIONAL: the value axis always reaches 0 (from below for positive data,
+  // from above for negative data) so small changes are not exaggerated;
+  // ignored with the log value scale
+  beginAtZero: false,
+
+  // OPTIONAL: headroom added above & below the value range as a fraction of
+  // it, e.g. 0.05 keeps the extreme points off the frame; not added beyond
+  // zero with beginAtZero, ignored with the log value scale
+  valuePadding: 0,
+
+  // OPTIONAL: the tooltip shows up only after the pointer rests (moves no
+  // further than dragThreshold) for this long, makes dense charts less noisy
+  tooltipDelayMs: 0,
+
+  // OPTIONAL: "full" shows a row per data set, "compact" a single small
+  // "coord: value" line, which occludes less of small charts
+  tooltipMode: "full",
+
+  // OPTIONAL: "follow" keeps the tooltip next to the pointer, "topRight" &
+  // "topLeft" pin it to a corner of the plot so it never covers the hovered
+  // data, "anchored" puts it above the highlighted point
+  tooltipPosition: "follow",
+
+  // OPTIONAL: "custom" skips drawing the tooltip box (the hovered points are
+  // still marked), leaving it to the onTooltip callback of the params, which
+  // is then required
+  tooltipRenderer: "canvas",
+
+  // OPTIONAL: shows the span of a drag-selection as a duration (e.g. "2h 15m")
+  // when coords are dates
+  selectionDuration: true,
+
+  // OPTIONAL: order of tooltip rows: "none" (data set order), "valueDesc" or
+  // "valueAsc" by the value at the hovered coord
+  tooltipSort: "none",
+
+  // OPTIONAL: which data sets the tooltip lists:
+  //  - "coord" only those having a point at the coord closest to the pointer
+  //  - "nearest" every visible one, with its point closest to the pointer,
+  //    so sparse series are always represented
+  tooltipMatch: "coord",
+
+  // OPTIONAL: "linear" or "log" forces the value scale, skipping the
+  // autoLogScaleThreshold heuristic; "auto" keeps it; data with zero or
+  // negative values is always drawn on the linear scale
+  valueScale: "auto",
+
+  // OPTIONAL: the log value scale puts its ticks at powers of it, e.g. 2 or
+  // Math.E; should be greater than 1
+  logBase: 10,
+
+  // OPTIONAL: caps the tooltip width (css px), too long data set names get
+  // truncated with an ellipsis
+  // tooltipMaxWidth: 300,
+
+  // OPTIONAL: show values linearly interpolated at the exact pointer coord
+  // instead of snapping to the closest data points (tooltipMatch is ignored)
+  interpolateTooltip: false,
+
+  // OPTIONAL: the finest distance between coord ticks, in coord units (ms for
+  // dates, seconds for datetimes); "date" coords default to a day, so pass
+  // e.g. 3600000 for hourly ticks on sub-day data or 0 to lift the limit;
+  // "datetime" coords default to a second
+  // minTickPeriod: 3600000,
+
+  // OPTIONAL: show a compact single-line tooltip when hovering the preview
+  previewTooltip: false,
+
+  // OPTIONAL: trace lines coarser while panning, zooming or dragging the
+  // preview, and at full detail once released; helps low-end devices
+  adaptiveQuality: false,
+
+  // OPTIONAL: cap on points per data set; onExceed decides whether larger
+  // ones are rejected ("error") or sampled down to the cap keeping roughly
+  // even coord spacing ("sample")
+  // maxPointsPerSeries: 1000000,
+  onExceed: "error",
+
+  // OPTIONAL: "flow" wraps legend items into rows by their text width,
+  // "columns" aligns them into equal-width columns (as many as fit) with
+  // color swatches, names are truncated to the column
+  legendLayout: "flow",
+
+  // OPTIONAL: add hintShowAll / hintHideAll buttons to the left of the legend
+  // which show or hide every series at once
+  legendBulkToggles: false,
+
+  // OPTIONAL: font size & color of the title (contentName) drawn above the
+  // chart, default to fontSizeLarge & colorTick
+  // titleFontSize: 14,
+  // titleColor: [142, 142, 142],
+
+  // OPTIONAL: where the legend goes: "bottom", "top", "left" or "right" (a
+  // strip of 20% of the width), "hidden" gives its space to the content
+  legendPosition: "bottom",
+
+  // OPTIONAL: false hides the preview below the chart & gives its space to
+  // the content; zooming is then done by dragging over the chart or pinching
+  showPreview: true,
+
+  // OPTIONAL: "sparkline" draws just the lines over the whole canvas, with no
+  // ticks, grid, title, legend, preview nor tooltips, e.g. for tables of tiny
+  // trend charts
+  mode: "normal",
+
+  // OPTIONAL: "percent" rescales each data set to a percentage of its first
+  // value (indexed to 100), e.g. to compare series of different magnitudes
+  valueTransform: "none",
+
+  // OPTIONAL: spacing of coord & value ticks (and their grid lines) in tick
+  // label sizes; higher values give sparser grids, lower ones denser grids
+  coordTicksDutyFactor: 1.5,
+  valueTicksDutyFactor: 5.0,
+
+  // OPTIONAL: false hides the grid lines (X: vertical ones at coord ticks, Y:
+  // horizontal ones at value ticks) or the tick labels of an axis
+  showGridX: true,
+  showGridY: true,
+  showTicksX: true,
+  showTicksY: true,
 };
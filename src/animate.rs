@@ -5,9 +5,56 @@
  *
  * Copyright (C) 2023, Nikita Almakov
  */
+use std::str::FromStr;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
 pub static ANIMATED_NUMBERS_COUNT: AtomicUsize = AtomicUsize::new(1);
+pub const DEFAULT_ANIMATION_DURATION_US: f64 = 200000.0;
+
+// how a chart animates its numbers, picked up by animations as they start
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Animation {
+    // when disabled, set_value snaps to the new value as if no time was passed
+    pub enabled: bool,
+    // applied to durations of animations, 1.0 keeps AnimatedNumber::new at
+    // DEFAULT_ANIMATION_DURATION_US
+    pub duration_scale: f64,
+    pub easing: Easing,
+}
+impl Animation {
+    pub fn new(enabled: bool, duration_us: f64, easing: Easing) -> Self {
+        Self {
+            enabled: enabled && duration_us > 0.0,
+            duration_scale: duration_us / DEFAULT_ANIMATION_DURATION_US,
+            easing,
+        }
+    }
+}
+impl Default for Animation {
+    fn default() -> Self {
+        Self::new(true, DEFAULT_ANIMATION_DURATION_US, Easing::Smooth)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Easing {
+    // accelerates for dt1, then cruises for dt2
+    Smooth = 0,
+    Linear = 1,
+    // cubic, decelerating towards the end
+    EaseOut = 2,
+}
+impl FromStr for Easing {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "smooth" => Ok(Self::Smooth),
+            "linear" => Ok(Self::Linear),
+            "easeOut" => Ok(Self::EaseOut),
+            v => Err(format!("unsupported Easing: {}", v)),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct AnimatedNumber {
@@ -19,6 +66,9 @@ pub struct AnimatedNumber {
     t0: Option<f64>,
     dt1: f64,
     dt2: f64,
+    base_dt1: f64,
+    base_dt2: f64,
+    easing: Easing,
 }
 impl AnimatedNumber {
     pub fn new(initial_value: f64) -> Self {
@@ -36,6 +86,9 @@ impl AnimatedNumber {
             // dt2: 200000.0,
             dt1: dt1_us,
             dt2: dt2_us,
+            base_dt1: dt1_us,
+            base_dt2: dt2_us,
+            easing: Easing::Smooth,
         }
     }
     pub fn get_value(&mut self, time_us: f64) -> f64 {
@@ -44,6 +97,18 @@ impl AnimatedNumber {
             Some(t0) => {
                 ANIMATED_NUMBERS_COUNT.fetch_add(1, Ordering::Relaxed);
                 let us = time_us - t0;
+                if self.easing != Easing::Smooth {
+                    let progress = us / (self.dt1 + self.dt2);
+                    if progress >= 1.0 {
+                        self.t0 = None;
+                        return self.x1;
+                    }
+                    let eased = match self.easing {
+                        Easing::EaseOut => 1.0 - (1.0 - progress).powi(3),
+                        _ => progress,
+                    };
+                    return eased * (self.x1 - self.x0) + self.x0;
+                }
                 if us <= self.dt1 {
                     (self.k * us * us / 2.0 + self.v0 * us) * (self.x1 - self.x0) + self.x0
                 } else if us >= self.dt2 + self.dt1 {
@@ -62,34 +127,48 @@ impl AnimatedNumber {
     pub fn get_end_value(&self) -> f64 {
         self.x1
     }
-    pub fn set_value(&mut self, new_value: f64, time_us: Option<f64>) {
-        match time_us {
+    pub fn set_value(&mut self, new_value: f64, time_us: Option<f64>, animation: Animation) {
+        match time_us.filter(|_| animation.enabled) {
             None => {
                 self.t0 = None;
                 self.x1 = new_value;
             }
-            Some(time_us) => {
-                ANIMATED_NUMBERS_COUNT.fetch_add(1, Ordering::Relaxed);
-                self.x0 = self.get_value(time_us);
-                self.x1 = new_value;
-                self.v0 = match self.t0 {
-                    Some(t0) => self.v0 + self.k * (self.dt1.min(time_us - t0)),
-                    None => 0.0,
-                };
+            Some(time_us) => self.animate_to(
+                new_value,
+                time_us,
+                animation.duration_scale,
+                animation.easing,
+            ),
+        }
+    }
+    fn animate_to(&mut self, new_value: f64, time_us: f64, scale: f64, easing: Easing) {
+        ANIMATED_NUMBERS_COUNT.fetch_add(1, Ordering::Relaxed);
+        self.x0 = self.get_value(time_us);
+        // an animation in progress keeps its pace to stay smooth
+        if self.t0.is_none() {
+            self.dt1 = self.base_dt1 * scale;
+            self.dt2 = self.base_dt2 * scale;
+            self.easing = easing;
+        }
+        self.x1 = new_value;
+        self.v0 = match self.t0 {
+            Some(t0) if self.easing == Easing::Smooth => {
+                self.v0 + self.k * (self.dt1.min(time_us - t0))
+            }
+            _ => 0.0,
+        };
 
-                self.k = (1.0 - self.v0 * (self.dt1 + self.dt2))
-                    / (self.dt1 * (self.dt1 * 0.5 + self.dt2));
+        self.k = (1.0 - self.v0 * (self.dt1 + self.dt2)) / (self.dt1 * (self.dt1 * 0.5 + self.dt2));
 
-                self.c = self.k * self.dt1 + self.v0;
-                self.t0 = Some(time_us);
-            }
-        }
+        self.c = self.k * self.dt1 + self.v0;
+        self.t0 = Some(time_us);
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::animate::AnimatedNumber;
+    use crate::animate::{AnimatedNumber, Animation, Easing};
+    use std::str::FromStr;
 
     #[test]
     fn test_animated_number() {
@@ -98,11 +177,11 @@ mod tests {
 
         let mut n = AnimatedNumber::new(0.0);
         assert_eq!(n.get_value(150.0), 0.0);
-        n.set_value(1.0, Some(150.0));
+        n.set_value(1.0, Some(150.0), Animation::default());
         assert_eq!(n.get_value(150.0), 0.0);
 
         let mut n = AnimatedNumber::new(1.0);
-        n.set_value(0.0, Some(1000000.0));
+        n.set_value(0.0, Some(1000000.0), Animation::default());
         assert_eq!(n.get_end_value(), 0.0);
         assert_eq!(n.get_value(1000000.0), 1.0);
         assert_eq!(n.get_value(1200000.0), 0.9215686274509804);
@@ -112,4 +191,58 @@ mod tests {
         assert_eq!(n.get_value(1900000.0), 0.11764705882352944);
         assert_eq!(n.get_value(2000000.0), 0.0);
     }
+
+    #[test]
+    fn test_animation_duration_scale() {
+        // 100ms + 100ms stretched to 400ms in total
+        let mut n = AnimatedNumber::new(1.0);
+        n.animate_to(0.0, 0.0, 2.0, Easing::Linear);
+        assert_eq!(n.get_value(200000.0), 0.5);
+
+        // retargeting midway keeps the pace of the animation in progress
+        n.animate_to(1.0, 200000.0, 0.5, Easing::EaseOut);
+        assert_eq!(n.easing, Easing::Linear);
+        assert_eq!(n.get_value(400000.0), 0.75);
+        assert_eq!(n.get_value(600000.0), 1.0);
+        assert_eq!(n.t0, None);
+
+        // the next animation picks up the new scale
+        n.animate_to(0.0, 600000.0, 0.5, Easing::Linear);
+        assert_eq!(n.get_value(650000.0), 0.5);
+        assert_eq!(n.get_value(700000.0), 0.0);
+
+        // a chart with animations disabled snaps right away
+        n.set_value(
+            1.0,
+            Some(700000.0),
+            Animation::new(false, 200000.0, Easing::Linear),
+        );
+        assert_eq!(n.get_value(700000.0), 1.0);
+        assert_eq!(n.t0, None);
+        // as does a zero duration
+        n.set_value(
+            0.0,
+            Some(700000.0),
+            Animation::new(true, 0.0, Easing::Linear),
+        );
+        assert_eq!(n.get_value(700000.0), 0.0);
+    }
+
+    #[test]
+    fn test_easing() {
+        assert_eq!(Easing::from_str("easeOut"), Ok(Easing::EaseOut));
+        assert!(Easing::from_str("bounce").is_err());
+
+        let mut n = AnimatedNumber::custom(0.0, 100000.0, 100000.0);
+        n.easing = Easing::Linear;
+        n.t0 = Some(0.0);
+        n.x1 = 1.0;
+        assert_eq!(n.get_value(50000.0), 0.25);
+        assert_eq!(n.get_value(100000.0), 0.5);
+
+        n.easing = Easing::EaseOut;
+        assert_eq!(n.get_value(100000.0), 0.875);
+        assert_eq!(n.get_value(200000.0), 1.0);
+        assert_eq!(n.t0, None);
+    }
 }
@@ -5,23 +5,38 @@
  *
  * Copyright (C) 2023, Nikita Almakov
  */
-use crate::grid::{Grid, Tick};
+use crate::data_set::{DataPoint, DataSet};
+use crate::decimate::{DecimationConsumer, DECIMATION_POINTS_PER_PX};
+use crate::grid::{ensure_zero_tick, pick_non_overlapping, Grid, Tick};
+use crate::legend::apply_hover_preview;
 use crate::params::Content;
-use crate::params::{ChartConfig, ClientCaps, VerboseFormat};
+use crate::params::{
+    AnnotationAxis, AnnotationKind, ChartConfig, ChartMode, ClientCaps, VerboseFormat,
+};
 use crate::scale::Scale;
-use crate::screen::{CoordSpace, Padding, ScreenArea, ScreenPos, Size};
+use crate::screen::{CoordSpace, CoordSpaceHandle, Padding, ScreenArea, ScreenPos, Size};
 use crate::tooltip::Tooltip;
+use crate::utils::{is_drag, place_rect_inside};
 use std::cell::RefCell;
 use std::rc::Rc;
-use wasm_bindgen::JsValue;
 
 pub enum Axis {
     X,
     Y,
 }
 
-const COORD_TICKS_DUTY_FACTOR: f64 = 1.5;
-const VALUE_TICKS_DUTY_FACTOR: f64 = 5.0;
+const AREA_ALPHA_ABOVE_BASELINE: f64 = 0.3;
+const AREA_ALPHA_BELOW_BASELINE: f64 = 0.15;
+const CONTEXT_GHOST_ALPHA: f64 = 0.12;
+const BAND_ALPHA: f64 = 0.15;
+pub const ERROR_BAND_ALPHA: f64 = 0.2;
+const ZERO_LINE_WIDTH: Size = Size::Px(2.0);
+// lines are traced coarser while the view is being dragged or pinched
+const COARSE_MIN_STEP: Size = Size::Px(3.0);
+const MAX_PINNED_TOOLTIPS: usize = 5;
+// css px, clicking this close to a pinned tooltip unpins it
+const PIN_TOGGLE_DISTANCE: f64 = 10.0;
+const MARKER_PADDING: Size = Size::Px(4.0);
 
 pub struct Camera<T>
 where
@@ -45,10 +60,15 @@ where
     pub pointer_down: Option<ScreenPos>,
     pub pointer_down_time_us: Option<f64>,
     pub pointer: Option<ScreenPos>,
-    pub pointer_clicked: Option<ScreenPos>,
+    pub pointer_clicked: Vec<ScreenPos>,
     pub pointer_clicked_time_us: Option<f64>,
     pub pinch_coords: Option<(f64, f64)>,
+    pub pointer_rest: Option<ScreenPos>,
+    pub pointer_rest_time_us: Option<f64>,
+    // a finger held still on a touch device shows the tooltip until released
+    pub touch_hold: bool,
     pub zoomed_in: bool,
+    pub coarse: bool,
 
     pub dirty: bool,
 }
@@ -69,11 +89,13 @@ where
             content.coord_type,
             content.global_coord_min,
             content.global_coord_max,
+            chart_config.borrow().min_tick_period,
         );
         let value_grid = Grid::new(
             content.value_type,
             content.global_value_min,
             content.global_value_max,
+            None,
         );
         let content_padding =
             Padding::new([Size::Px(0.0), Size::Px(0.0), Size::Px(0.0), Size::Px(0.0)]);
@@ -98,11 +120,15 @@ where
             pointer_down: None,
             pointer_down_time_us: None,
             pointer: None,
-            pointer_clicked: None,
+            pointer_clicked: Vec::new(),
             pointer_clicked_time_us: None,
             pinch_coords: None,
+            pointer_rest: None,
+            pointer_rest_time_us: None,
+            touch_hold: false,
             zoomed_in: false,
 
+            coarse: false,
             dirty: false,
         };
         camera.update_by_content(content, None);
@@ -110,19 +136,127 @@ where
     }
     pub fn update_by_content(&mut self, content: &mut Content, time_us: Option<f64>) {
         let conf = self.chart_config.borrow();
+        let sparkline = conf.mode == ChartMode::Sparkline;
+        let coord_ticks_padding = conf
+            .font_size_small
+            .mul(content.coord_short_verbose_len as f64);
+        let value_ticks_padding = conf
+            .font_size_small
+            .mul(content.value_short_verbose_len as f64);
         *self.content_padding.get_mut() = [
             Size::Px(0.0),
             Size::Px(0.0),
-            conf.font_size_small
-                .mul(content.coord_short_verbose_len as f64),
-            conf.font_size_small
-                .mul(content.value_short_verbose_len as f64),
+            match content.coord_axis_title {
+                _ if sparkline => Size::Px(0.0),
+                Some(_) => Size::Sum(vec![coord_ticks_padding, conf.font_size_normal.clone()]),
+                None => coord_ticks_padding,
+            },
+            // the value title is rotated, so it takes a line height of width
+            match content.value_axis_title {
+                _ if sparkline => Size::Px(0.0),
+                Some(_) => Size::Sum(vec![
+                    value_ticks_padding,
+                    conf.font_size_normal.mul(conf.font_width_coeff.recip()),
+                ]),
+                None => value_ticks_padding,
+            },
         ];
         let [coord_min, coord_max, value_min, value_max] = content.get_min_max();
-        self.coord_space
-            .content_updated(coord_min, coord_max, value_min, value_max, time_us);
+        self.coord_space.content_updated(
+            coord_min,
+            coord_max,
+            value_min,
+            value_max,
+            time_us,
+            conf.animation,
+        );
+        self.control_coord_space.content_updated(
+            coord_min,
+            coord_max,
+            value_min,
+            value_max,
+            time_us,
+            conf.animation,
+        );
+    }
+    // the tooltip delay restarts whenever the pointer moves further than the
+    // drag threshold from where it came to rest
+    pub fn track_pointer_rest(&mut self, pos: &ScreenPos, time_us: f64) {
+        let drag_threshold = self.chart_config.borrow().drag_threshold;
+        let moved = match self.pointer_rest.as_ref() {
+            Some(rest_pos) => is_drag(rest_pos, pos, drag_threshold),
+            None => true,
+        };
+        if moved {
+            self.pointer_rest = Some(pos.to_owned());
+            self.pointer_rest_time_us = Some(time_us);
+        }
+    }
+    // clicks toggle pinned tooltips, a click where there's no data unpins all
+    pub fn toggle_pin(&mut self, content: &Content, time_us: f64) {
+        let pos = match self.pointer.clone() {
+            Some(pos) => pos,
+            None => return,
+        };
+        let coord = self.control_coord_space.get_handle(time_us).get_coord(&pos);
+        let over_data = coord.is_some_and(|coord| {
+            content.data_sets.iter().any(|data_set| {
+                data_set.alpha.get_end_value() > 0.0
+                    && data_set.data_points[0].coord <= coord
+                    && data_set.data_points[data_set.data_points.len() - 1].coord >= coord
+            })
+        });
+        if !over_data {
+            self.pointer_clicked.clear();
+        } else if let Some(index) = self
+            .pointer_clicked
+            .iter()
+            .position(|pinned| (pinned.0 - pos.0).abs() < PIN_TOGGLE_DISTANCE)
+        {
+            self.pointer_clicked.remove(index);
+        } else {
+            if self.pointer_clicked.len() == MAX_PINNED_TOOLTIPS {
+                self.pointer_clicked.remove(0);
+            }
+            self.pointer_clicked.push(pos);
+        }
+        self.pointer_clicked_time_us = Some(time_us);
+    }
+    pub fn is_tooltip_pending(&self, time_us: f64) -> bool {
+        match (self.pointer.as_ref(), self.pointer_rest_time_us) {
+            (Some(_), Some(rest_time_us)) => {
+                time_us - rest_time_us < self.chart_config.borrow().tooltip_delay_ms * 1000.0
+            }
+            _ => false,
+        }
+    }
+    pub fn content_replaced(
+        &mut self,
+        content: &mut Content,
+        window: Option<(f64, f64)>,
+        time_us: f64,
+    ) {
+        self.global_scale = T::new(content);
+        self.coord_space.set_scale(self.global_scale.clone());
         self.control_coord_space
-            .content_updated(coord_min, coord_max, value_min, value_max, time_us);
+            .set_scale(self.global_scale.clone());
+        self.coord_grid = Grid::new(
+            content.coord_type,
+            content.global_coord_min,
+            content.global_coord_max,
+            self.chart_config.borrow().min_tick_period,
+        );
+        self.value_grid = Grid::new(
+            content.value_type,
+            content.global_value_min,
+            content.global_value_max,
+            None,
+        );
+        self.zoomed_in = false;
+        self.update_by_content(content, Some(time_us));
+        if let Some((coord_start, coord_end)) = window {
+            self.zoom_by_coords(content, coord_start, coord_end, time_us);
+        }
     }
     pub fn zoom_by_coords(
         &mut self,
@@ -140,24 +274,27 @@ where
         let mut number_of_points: usize = 0;
         for data_set in content.data_sets.iter_mut() {
             if data_set.alpha.get_end_value() > 0.0 {
-                if let Some(data_points) = data_set.slice_by_coord(coord_start, coord_end) {
-                    number_of_points = number_of_points.max(data_points.len());
-                    for data_point in data_points.iter() {
-                        value_min = value_min.min(data_point.value);
-                        value_max = value_max.max(data_point.value);
-                    }
+                let (data_set_points, value_extents) =
+                    data_set.get_value_extents(coord_start, coord_end);
+                number_of_points = number_of_points.max(data_set_points);
+                if let Some((data_set_min, data_set_max)) = value_extents {
+                    value_min = value_min.min(data_set_min);
+                    value_max = value_max.max(data_set_max);
                 }
             }
         }
         if number_of_points > 1 {
+            let (value_min, value_max) = content.frame_value_range(value_min, value_max);
             self.zoomed_in = !(self.global_scale.get_coord_min() == coord_start
                 && self.global_scale.get_coord_max() == coord_end);
+            let animation = self.chart_config.borrow().animation;
             self.coord_space.content_updated(
                 coord_start,
                 coord_end,
                 value_min,
                 value_max,
                 Some(time_us),
+                animation,
             );
             self.control_coord_space.content_updated(
                 coord_start,
@@ -165,6 +302,7 @@ where
                 value_min,
                 value_max,
                 Some(time_us),
+                animation,
             );
         }
     }
@@ -192,70 +330,291 @@ where
         }
         self.zoom_by_coords(content, coord_start, coord_end, time_us);
     }
+    pub fn nearest_point_at<'a>(
+        &mut self,
+        content: &'a Content,
+        pos: &ScreenPos,
+        time_us: f64,
+    ) -> Option<(&'a DataSet, &'a DataPoint)> {
+        let coord_space_handle = self.control_coord_space.get_handle(time_us);
+        let screen_area_handle = coord_space_handle.screen_area_handle.as_ref();
+        if !screen_area_handle.contains_pos(pos) {
+            return None;
+        }
+        let cx = screen_area_handle.get_cx(pos);
+        let cy = screen_area_handle.get_cy(pos);
+
+        let mut nearest: Option<(&DataSet, &DataPoint)> = None;
+        let mut min_distance = f64::MAX;
+        for data_set in content.data_sets.iter() {
+            if data_set.alpha.get_end_value() == 0.0 {
+                continue;
+            }
+            let data_points = DataSet::slice_data_points_by_coord(
+                data_set.data_points.as_slice(),
+                coord_space_handle.scale.get_coord_min(),
+                coord_space_handle.scale.get_coord_max(),
+            );
+            for data_point in data_points.iter() {
+                if data_point.value.is_nan() {
+                    continue;
+                }
+                let dx = coord_space_handle.get_cx(data_point.coord) - cx;
+                let dy = coord_space_handle.get_cy(data_point.value) - cy;
+                let distance = dx * dx + dy * dy;
+                if distance < min_distance {
+                    min_distance = distance;
+                    nearest = Some((data_set, data_point));
+                }
+            }
+        }
+        nearest
+    }
+    // the whole series squeezed into the view, the global scale maps it onto
+    // the plot area & anything past it (e.g. overshooting tick padding) is clipped
+    fn draw_context_ghost(&mut self, content: &mut Content, time_us: f64) {
+        let screen_area_handle = self.coord_space.screen_area.get_handle();
+        let crc = screen_area_handle.crc.as_ref();
+        let coord_space_handle = CoordSpaceHandle {
+            screen_area_handle: Rc::clone(&screen_area_handle),
+            scale: Rc::new(self.global_scale.clone()),
+        };
+        let config = self.chart_config.borrow();
+
+        crc.save();
+        crc.begin_path();
+        crc.rect(
+            screen_area_handle.left_cx(),
+            screen_area_handle.top_cy(),
+            screen_area_handle.right_cx() - screen_area_handle.left_cx(),
+            screen_area_handle.bottom_cy() - screen_area_handle.top_cy(),
+        );
+        crc.clip();
+        crc.set_line_width(config.line_width.to_cpx_height(screen_area_handle.as_ref()));
+        for data_set in content.data_sets.iter_mut() {
+            let alpha = data_set.alpha.get_value(time_us) * CONTEXT_GHOST_ALPHA;
+            if alpha == 0.0 {
+                continue;
+            }
+            let picked = data_set.pick_decimated(
+                (screen_area_handle.canvas_content_width * DECIMATION_POINTS_PER_PX) as usize,
+                DecimationConsumer::ContextGhost,
+            );
+            crc.begin_path();
+            crc.set_stroke_style(data_set.to_css_color(alpha).as_str());
+            coord_space_handle.trace_line(
+                data_set.get_decimated(picked),
+                data_set.max_gap,
+                content.connect_gaps,
+                data_set.interpolation,
+                1.0,
+            );
+            crc.stroke();
+        }
+        crc.restore();
+    }
     pub fn draw(&mut self, content: &mut Content, time_us: f64) {
         let coord_space_handle = self.coord_space.get_handle(time_us);
         let screen_area_handle = coord_space_handle.screen_area_handle.as_ref();
         let crc = screen_area_handle.crc.as_ref();
 
-        let ticks = self.get_coord_ticks(content.coord_short_verbose_len as f64, time_us);
-        self.draw_grid(ticks.as_slice(), Axis::X, time_us);
-        self.draw_ticks(content, ticks.as_slice(), Axis::X, time_us);
+        if self.chart_config.borrow().mode != ChartMode::Sparkline {
+            let ticks = self.get_coord_ticks(content.coord_short_verbose_len as f64, time_us);
+            if self.chart_config.borrow().show_grid_x {
+                self.draw_grid(ticks.as_slice(), Axis::X, time_us);
+            }
+            if self.chart_config.borrow().show_ticks_x {
+                self.draw_ticks(content, ticks.as_slice(), Axis::X, time_us);
+            }
+
+            let ticks = self.get_value_ticks(time_us);
+            if self.chart_config.borrow().show_grid_y {
+                self.draw_grid(ticks.as_slice(), Axis::Y, time_us);
+            }
+            if self.chart_config.borrow().show_ticks_y {
+                self.draw_ticks(content, ticks.as_slice(), Axis::Y, time_us);
+            }
+            self.draw_axis_titles(content, time_us);
+            if self.chart_config.borrow().zero_line {
+                self.draw_zero_line(time_us);
+            }
+        }
 
-        let ticks = self.get_value_ticks(time_us);
-        self.draw_grid(ticks.as_slice(), Axis::Y, time_us);
-        self.draw_ticks(content, ticks.as_slice(), Axis::Y, time_us);
+        if self.zoomed_in && self.chart_config.borrow().context_ghost {
+            self.draw_context_ghost(content, time_us);
+        }
 
         let config = self.chart_config.borrow();
+        // bands go under the series, lines over them
+        Self::draw_annotations(&config, &coord_space_handle, content, true);
+        let min_step = if self.coarse {
+            COARSE_MIN_STEP.to_cpx_width(screen_area_handle)
+        } else {
+            1.0
+        };
+        let hover_preview = content.get_hover_preview(time_us);
         let mut alpha: f64;
-        for data_set in content.data_sets.iter_mut() {
-            alpha = data_set.alpha.get_value(time_us);
+        for (index, data_set) in content.data_sets.iter_mut().enumerate() {
+            alpha = apply_hover_preview(data_set.alpha.get_value(time_us), index, hover_preview)
+                .max(config.hidden_ghost_alpha);
             if alpha == 0.0 {
                 continue;
             }
-            if let Some(data_points) = data_set.slice_by_coord(
+            let target_points =
+                (screen_area_handle.canvas_content_width * DECIMATION_POINTS_PER_PX) as usize;
+            let zoom = if self.zoomed_in {
+                (self.global_scale.get_coord_max() - self.global_scale.get_coord_min())
+                    / (coord_space_handle.scale.get_coord_max()
+                        - coord_space_handle.scale.get_coord_min())
+            } else {
+                1.0
+            };
+            let picked = data_set.pick_decimated(
+                DataSet::get_zoom_target_points(target_points, zoom),
+                DecimationConsumer::Camera,
+            );
+            let all_data_points = data_set.get_decimated(picked);
+            let data_points = DataSet::slice_data_points_by_coord(
+                all_data_points,
                 coord_space_handle.scale.get_coord_min(),
                 coord_space_handle.scale.get_coord_max(),
-            ) {
-                let mut it = data_points.iter();
-                let data_point = it.next().unwrap();
+            );
+            if !data_set.band_low.is_empty() {
+                let (coord_min, coord_max) = (
+                    coord_space_handle.scale.get_coord_min(),
+                    coord_space_handle.scale.get_coord_max(),
+                );
                 crc.begin_path();
-                crc.set_stroke_style(&JsValue::from_str(data_set.to_css_color(alpha).as_str()));
+                crc.set_fill_style(data_set.to_css_color(alpha * ERROR_BAND_ALPHA).as_str());
+                coord_space_handle.trace_band(
+                    DataSet::slice_data_points_by_coord(&data_set.band_low, coord_min, coord_max),
+                    DataSet::slice_data_points_by_coord(&data_set.band_high, coord_min, coord_max),
+                    min_step,
+                );
+                crc.fill();
+            }
+            if !data_points.is_empty() {
+                crc.begin_path();
+                crc.set_stroke_style(data_set.to_css_color(alpha).as_str());
                 crc.set_line_width(config.line_width.to_cpx_height(screen_area_handle));
+                coord_space_handle.trace_line(
+                    data_points,
+                    data_set.max_gap,
+                    content.connect_gaps,
+                    data_set.interpolation,
+                    min_step,
+                );
+                crc.stroke();
 
-                let mut prev_x = coord_space_handle.get_cx(data_point.coord);
-                let mut prev_y = coord_space_handle.get_cy(data_point.value);
-                crc.move_to(prev_x, prev_y);
-
-                let mut min_y = f64::MAX;
-                let mut max_y = f64::MIN;
-
-                let mut x: f64;
-                let mut y: f64;
-                for data_point in it {
-                    x = coord_space_handle.get_cx(data_point.coord);
-                    y = coord_space_handle.get_cy(data_point.value);
-                    if x - prev_x >= 1.0 || (y - prev_y).abs() >= 1.0 {
-                        crc.line_to(x, y);
-                        prev_x = x;
-                        prev_y = y;
+                if data_set.area {
+                    let top_cy = screen_area_handle.top_cy();
+                    let bottom_cy = screen_area_handle.bottom_cy();
+                    let base_cy = match config.baseline {
+                        Some(baseline) => coord_space_handle.get_cy(baseline),
+                        None => bottom_cy,
+                    };
+                    let base_cy = if base_cy.is_nan() {
+                        bottom_cy
+                    } else {
+                        base_cy.clamp(top_cy, bottom_cy)
+                    };
+                    let left_cx = screen_area_handle.left_cx();
+                    let width = screen_area_handle.right_cx() - left_cx;
+                    // fills above & below the baseline are told apart by alpha
+                    for (clip_cy, clip_height, area_alpha) in [
+                        (top_cy, base_cy - top_cy, AREA_ALPHA_ABOVE_BASELINE),
+                        (base_cy, bottom_cy - base_cy, AREA_ALPHA_BELOW_BASELINE),
+                    ] {
+                        if clip_height <= 0.0 {
+                            continue;
+                        }
+                        crc.save();
+                        crc.begin_path();
+                        crc.rect(left_cx, clip_cy, width, clip_height);
+                        crc.clip();
+                        crc.begin_path();
+                        crc.set_fill_style(data_set.to_css_color(alpha * area_alpha).as_str());
+                        coord_space_handle.trace_area(
+                            data_points,
+                            data_set.max_gap,
+                            content.connect_gaps,
+                            data_set.interpolation,
+                            base_cy,
+                        );
+                        crc.fill();
+                        crc.restore();
                     }
-                    min_y = min_y.min(y);
-                    max_y = max_y.max(y);
                 }
-                crc.stroke();
             }
         }
 
-        if self.pointer_down.is_none() {
-            self.tooltip.draw(
-                content,
-                self.control_coord_space.get_handle(time_us),
-                self.pointer_clicked.as_ref().or(self.pointer.as_ref()),
-                &self.global_scale,
-                time_us,
+        Self::draw_annotations(&config, &coord_space_handle, content, false);
+        Self::draw_markers(&config, &coord_space_handle, content);
+
+        // the scale keeps the last frame, so say why there's nothing in it
+        if !content.data_sets.is_empty()
+            && content
+                .data_sets
+                .iter()
+                .all(|data_set| data_set.alpha.get_end_value() == 0.0)
+        {
+            crc.set_text_align("center");
+            crc.set_text_baseline("middle");
+            crc.set_font(
+                format!(
+                    "{}px {}",
+                    config.font_size_large.to_cpx_height(screen_area_handle),
+                    config.font_standard.as_str()
+                )
+                .as_str(),
+            );
+            let v = config.color_tooltip_font;
+            crc.set_fill_style(format!("rgba({}, {}, {}, {})", v.0, v.1, v.2, v.3).as_str());
+            crc.fill_text(
+                config.hint_all_hidden.as_str(),
+                (screen_area_handle.left_cx() + screen_area_handle.right_cx()) * 0.5,
+                (screen_area_handle.top_cy() + screen_area_handle.bottom_cy()) * 0.5,
             );
         }
     }
+    pub fn draw_tooltips(&mut self, content: &mut Content, time_us: f64) {
+        self.tooltip.reported.clear();
+        if self.pointer.is_none() {
+            self.pointer_rest = None;
+            self.pointer_rest_time_us = None;
+        }
+        if self.pointer_down.is_none() {
+            self.tooltip.placed.clear();
+            if self.pointer_clicked.is_empty() {
+                let pointer = if self.is_tooltip_pending(time_us) {
+                    None
+                } else {
+                    self.pointer.as_ref()
+                };
+                self.tooltip.draw(
+                    content,
+                    self.control_coord_space.get_handle(time_us),
+                    pointer,
+                    &self.global_scale,
+                    time_us,
+                );
+            } else {
+                let mut visible = false;
+                for pointer in self.pointer_clicked.iter() {
+                    self.tooltip.draw(
+                        content,
+                        self.control_coord_space.get_handle(time_us),
+                        Some(pointer),
+                        &self.global_scale,
+                        time_us,
+                    );
+                    visible = visible || self.tooltip.visible;
+                }
+                self.tooltip.visible = visible;
+            }
+        }
+        self.tooltip.notify();
+    }
     fn draw_grid(&mut self, ticks: &[Tick], axis: Axis, time_us: f64) {
         let config = self.chart_config.borrow();
         let coord_space_handle = self.coord_space.get_handle(time_us);
@@ -270,9 +629,9 @@ where
                 for tick in ticks.iter() {
                     crc.begin_path();
                     if tick.alpha != alpha {
-                        crc.set_stroke_style(&JsValue::from_str(
+                        crc.set_stroke_style(
                             format!("rgb({}, {}, {}, {:.3})", v.0, v.1, v.2, tick.alpha).as_str(),
-                        ));
+                        );
                         alpha = tick.alpha;
                     }
                     crc.move_to(
@@ -290,9 +649,9 @@ where
                 for tick in ticks.iter() {
                     crc.begin_path();
                     if tick.alpha != alpha {
-                        crc.set_stroke_style(&JsValue::from_str(
+                        crc.set_stroke_style(
                             format!("rgb({}, {}, {}, {:.3})", v.0, v.1, v.2, tick.alpha).as_str(),
-                        ));
+                        );
                         alpha = tick.alpha;
                     }
                     crc.move_to(
@@ -308,6 +667,219 @@ where
             }
         }
     }
+    // over the grid & under the series, in the tick color to stand out of the grid
+    fn draw_zero_line(&mut self, time_us: f64) {
+        let config = self.chart_config.borrow();
+        let coord_space_handle = self.coord_space.get_handle(time_us);
+        let (value_min, value_max) = (
+            coord_space_handle.scale.get_value_min(),
+            coord_space_handle.scale.get_value_max(),
+        );
+        if !(value_min <= 0.0 && value_max >= 0.0) {
+            return;
+        }
+        let screen_area_handle = coord_space_handle.screen_area_handle.as_ref();
+        let crc = screen_area_handle.crc.as_ref();
+        let cy = coord_space_handle.get_cy(0.0);
+        let v = config.color_tick;
+        crc.begin_path();
+        crc.set_line_width(ZERO_LINE_WIDTH.to_cpx_height(screen_area_handle));
+        crc.set_stroke_style(format!("rgb({}, {}, {})", v.0, v.1, v.2).as_str());
+        crc.move_to(screen_area_handle.left_cx(), cy);
+        crc.line_to(screen_area_handle.right_cx(), cy);
+        crc.stroke();
+    }
+    fn draw_annotations(
+        config: &ChartConfig,
+        coord_space_handle: &CoordSpaceHandle<T>,
+        content: &Content,
+        bands: bool,
+    ) {
+        let screen_area_handle = coord_space_handle.screen_area_handle.as_ref();
+        let crc = screen_area_handle.crc.as_ref();
+        let (left_cx, right_cx) = (screen_area_handle.left_cx(), screen_area_handle.right_cx());
+        let (top_cy, bottom_cy) = (screen_area_handle.top_cy(), screen_area_handle.bottom_cy());
+        let label_padding = config.font_size_small.to_cpx_width(screen_area_handle);
+        crc.set_line_width(config.line_width.to_cpx_height(screen_area_handle));
+        crc.set_font(
+            format!(
+                "{}px {}",
+                config.font_size_small.to_cpx_height(screen_area_handle),
+                config.font_standard.as_str()
+            )
+            .as_str(),
+        );
+        for annotation in content.annotations.iter() {
+            let v = annotation.rgb;
+            // labels go to the right end of horizontal lines & to the top
+            // end of vertical ones, bands are labeled in their top left corner
+            let label_pos = match (&annotation.kind, &annotation.axis) {
+                (AnnotationKind::Line(at), AnnotationAxis::Value) if !bands => {
+                    let cy = coord_space_handle.get_cy(*at);
+                    if !(top_cy..=bottom_cy).contains(&cy) {
+                        continue;
+                    }
+                    crc.begin_path();
+                    crc.move_to(left_cx, cy);
+                    crc.line_to(right_cx, cy);
+                    crc.set_text_align("right");
+                    crc.set_text_baseline("bottom");
+                    (right_cx - label_padding, cy)
+                }
+                (AnnotationKind::Line(at), AnnotationAxis::Coord) if !bands => {
+                    let cx = coord_space_handle.get_cx(*at);
+                    if !(left_cx..=right_cx).contains(&cx) {
+                        continue;
+                    }
+                    crc.begin_path();
+                    crc.move_to(cx, top_cy);
+                    crc.line_to(cx, bottom_cy);
+                    crc.set_text_align("left");
+                    crc.set_text_baseline("top");
+                    (cx + label_padding, top_cy)
+                }
+                (AnnotationKind::Band(from, to), axis) if bands => {
+                    let (band_left_cx, band_right_cx, band_top_cy, band_bottom_cy) = match axis {
+                        AnnotationAxis::Value => {
+                            let (from_cy, to_cy) = (
+                                coord_space_handle.get_cy(*from),
+                                coord_space_handle.get_cy(*to),
+                            );
+                            (left_cx, right_cx, from_cy.min(to_cy), from_cy.max(to_cy))
+                        }
+                        AnnotationAxis::Coord => {
+                            let (from_cx, to_cx) = (
+                                coord_space_handle.get_cx(*from),
+                                coord_space_handle.get_cx(*to),
+                            );
+                            (from_cx.min(to_cx), from_cx.max(to_cx), top_cy, bottom_cy)
+                        }
+                    };
+                    let band_left_cx = band_left_cx.max(left_cx);
+                    let band_right_cx = band_right_cx.min(right_cx);
+                    let band_top_cy = band_top_cy.max(top_cy);
+                    let band_bottom_cy = band_bottom_cy.min(bottom_cy);
+                    if band_left_cx >= band_right_cx || band_top_cy >= band_bottom_cy {
+                        continue;
+                    }
+                    crc.set_fill_style(
+                        format!("rgba({}, {}, {}, {})", v.0, v.1, v.2, BAND_ALPHA).as_str(),
+                    );
+                    crc.fill_rect(
+                        band_left_cx,
+                        band_top_cy,
+                        band_right_cx - band_left_cx,
+                        band_bottom_cy - band_top_cy,
+                    );
+                    crc.set_text_align("left");
+                    crc.set_text_baseline("top");
+                    (band_left_cx + label_padding, band_top_cy)
+                }
+                _ => continue,
+            };
+            let color = format!("rgb({}, {}, {})", v.0, v.1, v.2);
+            if !bands {
+                crc.set_stroke_style(color.as_str());
+                crc.stroke();
+            }
+            if let Some(label) = annotation.label.as_ref() {
+                crc.set_fill_style(color.as_str());
+                crc.fill_text(label.as_str(), label_pos.0, label_pos.1);
+            }
+        }
+    }
+    fn draw_markers(
+        config: &ChartConfig,
+        coord_space_handle: &CoordSpaceHandle<T>,
+        content: &Content,
+    ) {
+        let screen_area_handle = coord_space_handle.screen_area_handle.as_ref();
+        let crc = screen_area_handle.crc.as_ref();
+        let radius = config.circle_diameter.to_cpx_height(screen_area_handle);
+        let padding = MARKER_PADDING.to_cpx_height(screen_area_handle);
+        let font_height = config.font_size_small.to_cpx_height(screen_area_handle);
+        crc.set_line_width(config.line_width.to_cpx_height(screen_area_handle));
+        crc.set_font(format!("{}px {}", font_height, config.font_standard.as_str()).as_str());
+        crc.set_text_align("left");
+        crc.set_text_baseline("top");
+        let coord_min = coord_space_handle.scale.get_coord_min();
+        let coord_max = coord_space_handle.scale.get_coord_max();
+        for marker in content.markers.iter() {
+            if !(coord_min..=coord_max).contains(&marker.coord) {
+                continue;
+            }
+            let v = marker.rgb;
+            let color = format!("rgb({}, {}, {})", v.0, v.1, v.2);
+            let cx = coord_space_handle.get_cx(marker.coord);
+            let cy = coord_space_handle.get_cy(marker.value);
+            crc.begin_path();
+            crc.set_fill_style(color.as_str());
+            crc.arc(cx, cy, radius, 0.0, std::f64::consts::PI * 2.0);
+            crc.fill();
+
+            let label = match marker.label.as_ref() {
+                Some(label) => label,
+                None => continue,
+            };
+            let width = crc.measure_text_width(label.as_str()) + 2.0 * padding;
+            let height = font_height + 2.0 * padding;
+            // the callout goes above the point, kept within the plot
+            let (x, y) = place_rect_inside(
+                cx,
+                cy - radius - height,
+                width,
+                height,
+                screen_area_handle.left_cx(),
+                screen_area_handle.right_cx(),
+                screen_area_handle.bottom_cy(),
+                radius,
+            );
+            let y = y.max(screen_area_handle.top_cy());
+            let v = config.color_tooltip;
+            crc.set_fill_style(format!("rgba({}, {}, {}, {})", v.0, v.1, v.2, v.3).as_str());
+            crc.fill_rect(x, y, width, height);
+            crc.set_stroke_style(color.as_str());
+            crc.stroke_rect(x, y, width, height);
+            let v = config.color_tooltip_font;
+            crc.set_fill_style(format!("rgba({}, {}, {}, {})", v.0, v.1, v.2, v.3).as_str());
+            crc.fill_text(label.as_str(), x + padding, y + padding);
+        }
+    }
+    fn draw_axis_titles(&mut self, content: &Content, time_us: f64) {
+        if content.coord_axis_title.is_none() && content.value_axis_title.is_none() {
+            return;
+        }
+        let config = self.chart_config.borrow();
+        let coord_space_handle = self.coord_space.get_handle(time_us);
+        let screen_area_handle = coord_space_handle.screen_area_handle.as_ref();
+        let crc = screen_area_handle.crc.as_ref();
+        let font_height = config.font_size_normal.to_cpx_height(screen_area_handle);
+        crc.set_font(format!("{}px {}", font_height, config.font_standard.as_str()).as_str());
+        let v = config.color_tick;
+        crc.set_fill_style(format!("rgb({}, {}, {})", v.0, v.1, v.2).as_str());
+        crc.set_text_align("center");
+        crc.set_text_baseline("middle");
+
+        if let Some(title) = content.coord_axis_title.as_ref() {
+            crc.fill_text(
+                title.as_str(),
+                (screen_area_handle.left_cx() + screen_area_handle.right_cx()) * 0.5,
+                screen_area_handle.bottom_cy()
+                    + config.font_size_small.to_cpx_height(screen_area_handle)
+                    + font_height * 0.5,
+            );
+        }
+        if let Some(title) = content.value_axis_title.as_ref() {
+            crc.save();
+            crc.translate(
+                screen_area_handle.outer_left_cx() + font_height * 0.5,
+                (screen_area_handle.top_cy() + screen_area_handle.bottom_cy()) * 0.5,
+            );
+            crc.rotate(-std::f64::consts::FRAC_PI_2);
+            crc.fill_text(title.as_str(), 0.0, 0.0);
+            crc.restore();
+        }
+    }
     fn draw_ticks(&mut self, content: &Content, ticks: &[Tick], axis: Axis, time_us: f64) {
         let config = self.chart_config.borrow();
         let coord_space_handle = self.coord_space.get_handle(time_us);
@@ -328,12 +900,25 @@ where
                 max_value = coord_space_handle.scale.get_value_max();
             }
         }
-        let formatted_ticks = verbose_format.format_values(
-            ticks.iter(),
-            |tick: &Tick| tick.value,
-            min_value,
-            max_value,
-        );
+        // explicit coord formats are kept as is
+        let adaptive_ticks = match axis {
+            Axis::X if config.coord_format.is_none() => verbose_format.format_date_ticks(
+                ticks.iter(),
+                |tick: &Tick| tick.value,
+                self.global_scale
+                    .denormalize_coord(self.coord_grid.current_period)
+                    - self.global_scale.denormalize_coord(0.0),
+            ),
+            _ => None,
+        };
+        let formatted_ticks = adaptive_ticks.unwrap_or_else(|| {
+            verbose_format.format_values(
+                ticks.iter(),
+                |tick: &Tick| tick.value,
+                min_value,
+                max_value,
+            )
+        });
 
         let crc = screen_area_handle.crc.as_ref();
         crc.set_font(
@@ -354,23 +939,44 @@ where
                 let y = screen_area_handle.bottom_cy() + font_height * 0.5;
                 crc.set_text_align("center");
                 crc.set_text_baseline("middle");
-                for (tick, formatted_tick) in ticks.iter().zip(formatted_ticks.iter()) {
+                let picked = if config.avoid_tick_label_collisions {
+                    let half_padding =
+                        config.font_size_small.to_cpx_width(screen_area_handle) * 0.5;
+                    let spans: Vec<(f64, f64)> = ticks
+                        .iter()
+                        .zip(formatted_ticks.iter())
+                        .map(|(tick, formatted_tick)| {
+                            let half_width = crc.measure_text_width(formatted_tick.as_str()) * 0.5
+                                + half_padding;
+                            let x = coord_space_handle.get_cx(tick.value);
+                            (x - half_width, x + half_width)
+                        })
+                        .collect();
+                    pick_non_overlapping(ticks, &spans)
+                } else {
+                    vec![true; ticks.len()]
+                };
+                for ((tick, formatted_tick), _) in ticks
+                    .iter()
+                    .zip(formatted_ticks.iter())
+                    .zip(picked.iter())
+                    .filter(|(_, &picked)| picked)
+                {
                     if tick.alpha != alpha {
-                        crc.set_fill_style(&JsValue::from_str(
+                        crc.set_fill_style(
                             format!(
                                 "rgba({}, {}, {}, {:.3})",
                                 tick_color.0, tick_color.1, tick_color.2, tick.alpha
                             )
                             .as_str(),
-                        ));
+                        );
                         alpha = tick.alpha;
                     }
                     crc.fill_text(
                         formatted_tick.as_str(),
                         coord_space_handle.get_cx(tick.value),
                         y,
-                    )
-                    .unwrap();
+                    );
                 }
             }
             Axis::Y => {
@@ -382,36 +988,49 @@ where
                 let x = left_cx - ticks_width * 0.5;
                 crc.set_text_align("center");
                 crc.set_text_baseline("middle");
+                let c_font_size = config.font_size_small.to_cpx_height(screen_area_handle);
                 for (tick, formatted_tick) in ticks.iter().zip(formatted_ticks.iter()) {
+                    // the zero label stands out of the other ones
+                    let zero_tick = config.zero_tick && tick.value == 0.0;
+                    if zero_tick {
+                        crc.set_font(
+                            format!("bold {}px {}", c_font_size, config.font_standard.as_str())
+                                .as_str(),
+                        );
+                    }
                     if tick.alpha != alpha {
-                        crc.set_fill_style(&JsValue::from_str(
+                        crc.set_fill_style(
                             format!(
                                 "rgba({}, {}, {}, {:.3})",
                                 tick_color.0, tick_color.1, tick_color.2, tick.alpha
                             )
                             .as_str(),
-                        ));
+                        );
                         alpha = tick.alpha;
                     }
                     crc.fill_text(
                         formatted_tick.as_str(),
                         x,
                         coord_space_handle.get_cy(tick.value),
-                    )
-                    .unwrap();
+                    );
+                    if zero_tick {
+                        crc.set_font(
+                            format!("{}px {}", c_font_size, config.font_standard.as_str()).as_str(),
+                        );
+                    }
                 }
             }
         }
     }
 
-    fn get_coord_ticks(&mut self, coord_short_verbose_len: f64, time_us: f64) -> Vec<Tick> {
+    pub fn get_coord_ticks(&mut self, coord_short_verbose_len: f64, time_us: f64) -> Vec<Tick> {
         let config = self.chart_config.borrow();
         let coord_space_handle = self.coord_space.get_handle(time_us);
         let screen_area_handle = coord_space_handle.screen_area_handle.as_ref();
         let max_ticks = screen_area_handle.canvas_content_width
             / (config.font_size_small.to_cpx_width(screen_area_handle)
                 * coord_short_verbose_len
-                * COORD_TICKS_DUTY_FACTOR);
+                * config.coord_ticks_duty_factor);
 
         let min_as_normalized_global = self
             .global_scale
@@ -423,6 +1042,7 @@ where
 
         let mut ticks = self.coord_grid.get_ticks(
             time_us,
+            config.animation,
             min_as_normalized_global,
             max_as_normalized_global,
             max_ticks,
@@ -433,12 +1053,13 @@ where
         ticks
     }
 
-    fn get_value_ticks(&mut self, time_us: f64) -> Vec<Tick> {
+    pub fn get_value_ticks(&mut self, time_us: f64) -> Vec<Tick> {
         let config = self.chart_config.borrow();
         let coord_space_handle = self.coord_space.get_handle(time_us);
         let screen_area_handle = coord_space_handle.screen_area_handle.as_ref();
         let max_ticks = screen_area_handle.canvas_content_height
-            / (config.font_size_small.to_cpx_height(screen_area_handle) * VALUE_TICKS_DUTY_FACTOR);
+            / (config.font_size_small.to_cpx_height(screen_area_handle)
+                * config.value_ticks_duty_factor);
 
         let min_as_normalized_global = self
             .global_scale
@@ -446,15 +1067,165 @@ where
         let max_as_normalized_global = self
             .global_scale
             .normalize_value(coord_space_handle.scale.get_value_max());
-        let mut ticks = self.value_grid.get_ticks(
-            time_us,
-            min_as_normalized_global,
-            max_as_normalized_global,
+        let mut ticks = match self.global_scale.get_value_ticks(
+            coord_space_handle.scale.get_value_min(),
+            coord_space_handle.scale.get_value_max(),
             max_ticks,
-        );
-        for tick in ticks.iter_mut() {
-            tick.value = self.global_scale.denormalize_value(tick.normalized_value);
+        ) {
+            Some(values) => values
+                .into_iter()
+                .map(|value| Tick {
+                    normalized_value: self.global_scale.normalize_value(value),
+                    alpha: 1.0,
+                    end_alpha: 1.0,
+                    value,
+                })
+                .collect(),
+            None => {
+                let mut ticks = self.value_grid.get_ticks(
+                    time_us,
+                    config.animation,
+                    min_as_normalized_global,
+                    max_as_normalized_global,
+                    max_ticks,
+                );
+                for tick in ticks.iter_mut() {
+                    tick.value = self.global_scale.denormalize_value(tick.normalized_value);
+                }
+                ticks
+            }
+        };
+        if config.zero_tick {
+            ensure_zero_tick(
+                &mut ticks,
+                coord_space_handle.scale.get_value_min(),
+                coord_space_handle.scale.get_value_max(),
+                self.global_scale.normalize_value(0.0),
+            );
         }
         ticks
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params::{Annotation, AnnotationAxis, AnnotationKind, Marker};
+    use crate::render::RecordingRenderer;
+    use crate::scale::LinearScale;
+    use crate::screen::ScreenAreaHandle;
+
+    fn coord_space_handle(
+        renderer: &Rc<RecordingRenderer>,
+        content: &Content,
+    ) -> CoordSpaceHandle<LinearScale> {
+        CoordSpaceHandle {
+            screen_area_handle: Rc::new(ScreenAreaHandle {
+                crc: Rc::clone(renderer) as Rc<dyn crate::render::Renderer>,
+                screen_width: 200.0,
+                screen_height: 100.0,
+                css_to_physical_scale: 1.0,
+                font_height_to_physical_scale: 1.0,
+                font_width_to_physical_scale: 1.0,
+                outer_padding: [0.0; 4],
+                canvas_content_width: 200.0,
+                canvas_content_height: 100.0,
+                canvas_padding: [0.0; 4],
+            }),
+            scale: Rc::new(LinearScale::new(content)),
+        }
+    }
+
+    #[test]
+    fn test_draw_band_annotations() {
+        let conf = ChartConfig::default();
+        let mut content = Content {
+            value_padding: 0.0,
+            begin_at_zero: false,
+            ..Content::default()
+        };
+        content
+            .parse_and_add_data_set("a", vec![0.0, 10.0], vec![0.0, 10.0], (0, 0, 0), 0.0)
+            .unwrap();
+        for (axis, kind) in [
+            (AnnotationAxis::Value, AnnotationKind::Band(5.0, 100.0)),
+            (AnnotationAxis::Coord, AnnotationKind::Band(4.0, 2.0)),
+            (AnnotationAxis::Coord, AnnotationKind::Band(20.0, 30.0)),
+            (AnnotationAxis::Value, AnnotationKind::Line(5.0)),
+        ] {
+            content.annotations.push(Annotation {
+                axis,
+                kind,
+                rgb: (255, 0, 0),
+                label: None,
+            });
+        }
+        let renderer = Rc::new(RecordingRenderer::new(1.0));
+        let coord_space_handle = coord_space_handle(&renderer, &content);
+
+        // bands go first, clipped to the plot, the ones out of it are skipped
+        Camera::draw_annotations(&conf, &coord_space_handle, &content, true);
+        let calls = renderer.take_calls();
+        let fill_rects: Vec<&String> = calls
+            .iter()
+            .filter(|call| call.starts_with("fill_rect"))
+            .collect();
+        assert_eq!(
+            fill_rects,
+            vec!["fill_rect(0, 0, 200, 50)", "fill_rect(40, 0, 40, 100)"]
+        );
+        assert!(!calls.iter().any(|call| call.starts_with("line_to")));
+
+        // lines are drawn on top of the series in the second pass
+        Camera::draw_annotations(&conf, &coord_space_handle, &content, false);
+        let calls = renderer.take_calls();
+        assert!(!calls.iter().any(|call| call.starts_with("fill_rect")));
+        assert!(calls.contains(&"line_to(200, 50)".to_string()));
+    }
+
+    #[test]
+    fn test_draw_markers() {
+        let conf = ChartConfig::default();
+        let mut content = Content {
+            value_padding: 0.0,
+            begin_at_zero: false,
+            ..Content::default()
+        };
+        content
+            .parse_and_add_data_set("a", vec![0.0, 10.0], vec![0.0, 10.0], (0, 0, 0), 0.0)
+            .unwrap();
+        for (coord, label) in [(5.0, Some("deploy")), (20.0, Some("later")), (10.0, None)] {
+            content.markers.push(Marker {
+                coord,
+                value: 10.0,
+                rgb: (0, 0, 255),
+                label: label.map(String::from),
+            });
+        }
+        let renderer = Rc::new(RecordingRenderer::new(1.0));
+        let coord_space_handle = coord_space_handle(&renderer, &content);
+        Camera::draw_markers(&conf, &coord_space_handle, &content);
+        let calls = renderer.take_calls();
+
+        // the one out of the visible range isn't drawn
+        let arcs: Vec<&String> = calls
+            .iter()
+            .filter(|call| call.starts_with("arc"))
+            .collect();
+        assert_eq!(arcs.len(), 2);
+        assert!(arcs[0].starts_with("arc(100, 0,"));
+        assert!(arcs[1].starts_with("arc(200, 0,"));
+        let labels: Vec<&String> = calls
+            .iter()
+            .filter(|call| call.starts_with("fill_text"))
+            .collect();
+        assert_eq!(labels.len(), 1);
+        assert!(labels[0].starts_with("fill_text(\"deploy\""));
+        // the callout above the top edge is pushed back into the plot
+        let callout = calls
+            .iter()
+            .find(|call| call.starts_with("fill_rect"))
+            .unwrap();
+        assert_eq!(callout, "fill_rect(104, 0, 14, 18)");
+    }
+}
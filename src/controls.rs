@@ -6,6 +6,7 @@
  * Copyright (C) 2023, Nikita Almakov
  */
 use crate::screen::ScreenPos;
+use crate::utils::is_drag;
 use js_sys::Reflect;
 use wasm_bindgen::prelude::*;
 
@@ -27,15 +28,17 @@ pub trait WatchControls {
 }
 
 pub struct MouseControls {
+    drag_threshold: f64,
     primary_down: Option<ScreenPos>,
-    primary_moved: Option<ScreenPos>,
+    primary_dragged: bool,
 }
 
 impl MouseControls {
-    pub fn new() -> Self {
+    pub fn new(drag_threshold: f64) -> Self {
         Self {
+            drag_threshold,
             primary_down: None,
-            primary_moved: None,
+            primary_dragged: false,
         }
     }
     fn get_event_coordinates(event: &JsValue) -> ScreenPos {
@@ -54,27 +57,32 @@ impl WatchControls for MouseControls {
     fn down(&mut self, event: &JsValue) -> Option<ControlEvent> {
         let pos = Self::get_event_coordinates(event);
         self.primary_down = Some(pos.clone());
-        self.primary_moved = None;
+        self.primary_dragged = false;
         Some(ControlEvent::PointerDown { pos })
     }
     fn moved(&mut self, event: &JsValue) -> Option<ControlEvent> {
         let pos = Self::get_event_coordinates(event);
-        self.primary_moved = Some(pos.clone());
+        if !self.primary_dragged {
+            self.primary_dragged = match self.primary_down.as_ref() {
+                Some(down) => is_drag(down, &pos, self.drag_threshold),
+                None => true,
+            };
+        }
         Some(ControlEvent::PointerMoved { pos })
     }
     fn up(&mut self, _event: &JsValue) -> Option<ControlEvent> {
-        let result = if self.primary_moved.is_some() {
+        let result = if self.primary_dragged {
             Some(ControlEvent::PointerUp)
         } else {
             Some(ControlEvent::PointerClicked)
         };
         self.primary_down = None;
-        self.primary_moved = None;
+        self.primary_dragged = false;
         result
     }
     fn left(&mut self, _event: &JsValue) -> Option<ControlEvent> {
         self.primary_down = None;
-        self.primary_moved = None;
+        self.primary_dragged = false;
         Some(ControlEvent::PointerLeft)
     }
 }
@@ -88,15 +96,18 @@ struct TouchState {
     id: f64,
     down: ScreenPos,
     moved: ScreenPos,
+    dragged: bool,
 }
 
 pub struct TouchControls {
+    drag_threshold: f64,
     primary: Option<TouchState>,
     secondary: Option<TouchState>,
 }
 impl TouchControls {
-    pub fn new() -> Self {
+    pub fn new(drag_threshold: f64) -> Self {
         Self {
+            drag_threshold,
             primary: None,
             secondary: None,
         }
@@ -158,6 +169,7 @@ impl WatchControls for TouchControls {
                     id: touch.id,
                     down: touch.pos.clone(),
                     moved: touch.pos.clone(),
+                    dragged: false,
                 });
             }
         }
@@ -167,6 +179,7 @@ impl WatchControls for TouchControls {
                     id: touch.id,
                     down: touch.pos.clone(),
                     moved: touch.pos.clone(),
+                    dragged: false,
                 });
                 secondary_set = true;
             }
@@ -191,6 +204,8 @@ impl WatchControls for TouchControls {
             active_touches += 1;
             if let Some(touch) = touches.iter().filter(|touch| touch.id == primary.id).next() {
                 primary.moved = touch.pos.clone();
+                primary.dragged =
+                    primary.dragged || is_drag(&primary.down, &primary.moved, self.drag_threshold);
                 primary_updated = true;
                 updated_touches += 1;
             }
@@ -240,7 +255,7 @@ impl WatchControls for TouchControls {
                 result = if secondary_just_left {
                     Some(ControlEvent::PinchFinished)
                 } else if self.secondary.is_none() {
-                    if primary.moved == primary.down {
+                    if !primary.dragged {
                         Some(ControlEvent::PointerClicked)
                     } else {
                         Some(ControlEvent::PointerUp)
@@ -6,6 +6,15 @@
  * Copyright (C) 2023, Nikita Almakov
  */
 use crate::animate::AnimatedNumber;
+use crate::decimate::{
+    decimate, DecimationConsumer, DecimationMode, DECIMATION_CONSUMERS,
+    DECIMATION_REFINEMENTS_PENDING,
+};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::atomic::Ordering;
+
+const MAX_DECIMATED_SETS: usize = 8;
 
 #[derive(Debug, PartialEq)]
 pub struct DataPoint {
@@ -22,14 +31,20 @@ pub struct DataSetMeta {
 }
 impl DataSetMeta {
     pub fn from_data_points(data_points: &[DataPoint]) -> Self {
-        let mut total: f64 = 0.0;
         let mut values: Vec<f64> = data_points
             .iter()
-            .map(|p| {
-                total += p.value;
-                p.value
-            })
+            .map(|p| p.value)
+            .filter(|value| !value.is_nan())
             .collect();
+        if values.is_empty() {
+            return Self {
+                min: f64::NAN,
+                p25: f64::NAN,
+                p50: f64::NAN,
+                p75: f64::NAN,
+                max: f64::NAN,
+            };
+        }
         values.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
         let max_index = values.len() - 1;
         Self {
@@ -53,25 +68,280 @@ impl DataSetMeta {
     }
 }
 
+// bottom-up segment tree of value min & max by data point index, NaN values
+// are left out; resolves the value range of any coord window in O(log n)
+pub struct ValueExtentsIndex {
+    size: usize,
+    min: Vec<f64>,
+    max: Vec<f64>,
+}
+impl ValueExtentsIndex {
+    pub fn new(data_points: &[DataPoint]) -> Self {
+        Self::from_extents(
+            data_points.iter().map(|p| (p.value, p.value)),
+            data_points.len(),
+        )
+    }
+    // e.g. a value with the bounds of its error band
+    pub fn from_extents<I: Iterator<Item = (f64, f64)>>(extents: I, size: usize) -> Self {
+        let mut min = vec![f64::INFINITY; size * 2];
+        let mut max = vec![f64::NEG_INFINITY; size * 2];
+        for (index, (value_min, value_max)) in extents.enumerate() {
+            if !value_min.is_nan() {
+                min[size + index] = value_min;
+            }
+            if !value_max.is_nan() {
+                max[size + index] = value_max;
+            }
+        }
+        for index in (1..size).rev() {
+            min[index] = min[index * 2].min(min[index * 2 + 1]);
+            max[index] = max[index * 2].max(max[index * 2 + 1]);
+        }
+        Self { size, min, max }
+    }
+    // over data point indices from..to (exclusive)
+    pub fn query(&self, from: usize, to: usize) -> Option<(f64, f64)> {
+        let (mut left, mut right) = (from + self.size, to.min(self.size) + self.size);
+        let (mut value_min, mut value_max) = (f64::INFINITY, f64::NEG_INFINITY);
+        while left < right {
+            if left & 1 == 1 {
+                value_min = value_min.min(self.min[left]);
+                value_max = value_max.max(self.max[left]);
+                left += 1;
+            }
+            if right & 1 == 1 {
+                right -= 1;
+                value_min = value_min.min(self.min[right]);
+                value_max = value_max.max(self.max[right]);
+            }
+            left >>= 1;
+            right >>= 1;
+        }
+        if value_min > value_max {
+            None
+        } else {
+            Some((value_min, value_max))
+        }
+    }
+}
+
+pub struct DecimatedDataPoints {
+    pub target_points: usize,
+    pub data_points: Vec<DataPoint>,
+}
+
 pub struct DataSet {
     pub name: String,
     pub data_points: Vec<DataPoint>,
     pub meta: DataSetMeta,
+    pub value_extents: ValueExtentsIndex,
     pub rgb: (u8, u8, u8),
     pub alpha: AnimatedNumber,
+    pub max_gap: Option<f64>,
+    pub area: bool,
+    pub interpolation: Interpolation,
+    pub decimated: Vec<DecimatedDataPoints>,
+    pub decimation_refinement_deferred: [bool; DECIMATION_CONSUMERS],
+    pub decimation_mode: DecimationMode,
+    // lower & upper bounds (e.g. a confidence interval) aligned with
+    // data_points, empty when there is no band
+    pub band_low: Vec<DataPoint>,
+    pub band_high: Vec<DataPoint>,
+    // values were multiplied by it, e.g. by a percent value transform
+    pub value_factor: f64,
 }
 
 impl DataSet {
     pub fn new(name: &str, rgb: (u8, u8, u8), data_points: Vec<DataPoint>) -> Self {
         let meta = DataSetMeta::from_data_points(data_points.as_slice());
+        let value_extents = ValueExtentsIndex::new(data_points.as_slice());
         Self {
             name: name.to_string(),
             data_points,
             meta,
+            value_extents,
             rgb,
             alpha: AnimatedNumber::new(1.0),
+            max_gap: None,
+            area: false,
+            interpolation: Interpolation::Linear,
+            decimated: Vec::new(),
+            decimation_refinement_deferred: [false; DECIMATION_CONSUMERS],
+            decimation_mode: DecimationMode::Lttb,
+            band_low: Vec::new(),
+            band_high: Vec::new(),
+            value_factor: 1.0,
+        }
+    }
+    // bounds are matched to data points by coord, so it holds after data
+    // points were sampled; the band counts towards value extents
+    pub fn set_band(&mut self, coords: &[f64], lows: &[f64], highs: &[f64]) {
+        let mut bounds: Vec<(f64, f64, f64)> = coords
+            .iter()
+            .zip(lows.iter().zip(highs.iter()))
+            .map(|(coord, (low, high))| (*coord, low * self.value_factor, high * self.value_factor))
+            .collect();
+        bounds.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        let mut bounds_iter = bounds.into_iter().peekable();
+        self.band_low = Vec::with_capacity(self.data_points.len());
+        self.band_high = Vec::with_capacity(self.data_points.len());
+        for data_point in self.data_points.iter() {
+            while bounds_iter
+                .peek()
+                .is_some_and(|bound| bound.0 < data_point.coord)
+            {
+                bounds_iter.next();
+            }
+            let (low, high) = match bounds_iter.peek() {
+                Some(bound) if bound.0 == data_point.coord => (bound.1, bound.2),
+                _ => (f64::NAN, f64::NAN),
+            };
+            self.band_low.push(DataPoint {
+                coord: data_point.coord,
+                value: low,
+            });
+            self.band_high.push(DataPoint {
+                coord: data_point.coord,
+                value: high,
+            });
+        }
+        self.index_value_extents();
+    }
+    // after data points were changed in place, e.g. before a refresh
+    pub fn reindex(&mut self) {
+        self.meta = DataSetMeta::from_data_points(self.data_points.as_slice());
+        self.index_value_extents();
+        self.decimated.clear();
+        self.decimation_refinement_deferred = [false; DECIMATION_CONSUMERS];
+    }
+    fn index_value_extents(&mut self) {
+        if self.band_low.is_empty() {
+            self.value_extents = ValueExtentsIndex::new(self.data_points.as_slice());
+            return;
+        }
+        let extents: Vec<(f64, f64)> = self
+            .data_points
+            .iter()
+            .zip(self.band_low.iter().zip(self.band_high.iter()))
+            .map(|(point, (low, high))| {
+                (
+                    f64::INFINITY
+                        .min(point.value)
+                        .min(low.value)
+                        .min(high.value),
+                    f64::NEG_INFINITY
+                        .max(point.value)
+                        .max(low.value)
+                        .max(high.value),
+                )
+            })
+            .collect();
+        for (value_min, value_max) in extents.iter() {
+            if value_min.is_finite() {
+                self.meta.min = self.meta.min.min(*value_min);
+            }
+            if value_max.is_finite() {
+                self.meta.max = self.meta.max.max(*value_max);
+            }
+        }
+        self.value_extents =
+            ValueExtentsIndex::from_extents(extents.into_iter(), self.data_points.len());
+    }
+    pub fn precompute_decimation(&mut self, target_points: usize) {
+        if let Some(decimator) = self.decimation_mode.get_decimator() {
+            if self.data_points.len() > target_points {
+                self.decimated.push(DecimatedDataPoints {
+                    target_points,
+                    data_points: decimate(self.data_points.as_slice(), target_points, decimator),
+                });
+            }
+        }
+    }
+    // when only a precomputed set of another resolution is available, it is
+    // drawn once and the exact one is computed on the next frame (refinement)
+    pub fn pick_decimated(
+        &mut self,
+        target_points: usize,
+        consumer: DecimationConsumer,
+    ) -> Option<usize> {
+        if self.data_points.len() <= target_points || self.decimation_mode == DecimationMode::None {
+            return None;
+        }
+        let deferred = &mut self.decimation_refinement_deferred[consumer as usize];
+        Some(
+            match self
+                .decimated
+                .iter()
+                .position(|item| item.target_points == target_points)
+            {
+                Some(index) => index,
+                None if !self.decimated.is_empty() && !*deferred => {
+                    *deferred = true;
+                    DECIMATION_REFINEMENTS_PENDING.fetch_add(1, Ordering::Relaxed);
+                    let ratio = |item: &DecimatedDataPoints| {
+                        (item.target_points as f64 / target_points as f64)
+                            .ln()
+                            .abs()
+                    };
+                    (0..self.decimated.len())
+                        .min_by(|a, b| {
+                            ratio(&self.decimated[*a]).total_cmp(&ratio(&self.decimated[*b]))
+                        })
+                        .unwrap()
+                }
+                None => {
+                    *deferred = false;
+                    if self.decimated.len() == MAX_DECIMATED_SETS {
+                        self.decimated.remove(0);
+                    }
+                    self.precompute_decimation(target_points);
+                    self.decimated.len() - 1
+                }
+            },
+        )
+    }
+    // zoomed in views are drawn from sets of 2x, 4x, 8x, ... the screen
+    // target, so they still hold about the target number of points; zoom is
+    // the global coord range divided by the visible one
+    pub fn get_zoom_target_points(target_points: usize, zoom: f64) -> usize {
+        if zoom.is_nan() || zoom <= 1.0 {
+            return target_points;
+        }
+        (target_points as f64 * 2.0_f64.powf(zoom.log2().ceil())) as usize
+    }
+    pub fn get_decimated(&self, picked: Option<usize>) -> &[DataPoint] {
+        match picked {
+            Some(index) => self.decimated[index].data_points.as_slice(),
+            None => self.data_points.as_slice(),
         }
     }
+    pub fn slice_data_points_by_coord(
+        data_points: &[DataPoint],
+        coord_start: f64,
+        coord_end: f64,
+    ) -> &[DataPoint] {
+        let left_idx = data_points.partition_point(|p| p.coord < coord_start);
+        let right_idx = data_points.partition_point(|p| p.coord <= coord_end);
+        &data_points[left_idx..right_idx.max(left_idx)]
+    }
+    // number of points within the coord range & their value min and max
+    pub fn get_value_extents(
+        &self,
+        coord_start: f64,
+        coord_end: f64,
+    ) -> (usize, Option<(f64, f64)>) {
+        let left_idx = self.data_points.partition_point(|p| p.coord < coord_start);
+        let right_idx = self
+            .data_points
+            .partition_point(|p| p.coord <= coord_end)
+            .max(left_idx);
+        (
+            right_idx - left_idx,
+            self.value_extents.query(left_idx, right_idx),
+        )
+    }
+    #[cfg(test)]
     pub fn slice_by_coord(&self, coord_start: f64, coord_end: f64) -> Option<&[DataPoint]> {
         if let Some(left_idx) = self.bin_search_left_bound(coord_start) {
             if let Some(right_idx) = self.bin_search_right_bound(coord_end) {
@@ -80,6 +350,26 @@ impl DataSet {
         }
         None
     }
+    // interpolation between the points bracketing the coord, gaps stay gaps
+    pub fn interpolate_value(&self, coord: f64) -> Option<f64> {
+        let left = &self.data_points[self.bin_search_right_bound(coord)?];
+        let right = &self.data_points[self.bin_search_left_bound(coord)?];
+        if left.value.is_nan() || right.value.is_nan() {
+            return None;
+        }
+        if right.coord == left.coord {
+            return Some(left.value);
+        }
+        match self.interpolation {
+            Interpolation::Linear | Interpolation::Monotone => {}
+            Interpolation::StepBefore => return Some(right.value),
+            Interpolation::StepAfter => return Some(left.value),
+        }
+        Some(
+            left.value
+                + (right.value - left.value) * (coord - left.coord) / (right.coord - left.coord),
+        )
+    }
     pub fn to_css_color(&self, alpha: f64) -> String {
         format!(
             "rgba({}, {}, {}, {})",
@@ -152,7 +442,18 @@ impl DataSet {
         }
         None
     }
-    #[allow(dead_code)]
+    // same as the bounds above, but stepping over missing (NaN) values
+    pub fn bin_search_defined_left_bound(&self, left_bound: f64) -> Option<usize> {
+        let index = self.bin_search_left_bound(left_bound)?;
+        (index..self.data_points.len()).find(|&i| !self.data_points[i].value.is_nan())
+    }
+    pub fn bin_search_defined_right_bound(&self, right_bound: f64) -> Option<usize> {
+        let index = self.bin_search_right_bound(right_bound)?;
+        (0..=index)
+            .rev()
+            .find(|&i| !self.data_points[i].value.is_nan())
+    }
+    #[cfg(test)]
     pub fn bin_search(&self, x: f64) -> Option<usize> {
         let data = self.data_points.as_slice();
         if data.is_empty() {
@@ -184,9 +485,220 @@ impl DataSet {
     }
 }
 
+// how consecutive points are connected: "step-after" holds a value until the
+// next point, "step-before" jumps to it right after the previous one,
+// "monotone" is a smooth curve which never overshoots the points
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Interpolation {
+    Linear,
+    StepBefore,
+    StepAfter,
+    Monotone,
+}
+impl FromStr for Interpolation {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "linear" => Ok(Self::Linear),
+            "step-before" => Ok(Self::StepBefore),
+            "step-after" => Ok(Self::StepAfter),
+            "monotone" => Ok(Self::Monotone),
+            v => Err(format!("unsupported Interpolation: {}", v)),
+        }
+    }
+}
+impl Interpolation {
+    // the corner inserted between two points, if any
+    pub fn get_corner(&self, prev: (f64, f64), next: (f64, f64)) -> Option<(f64, f64)> {
+        match self {
+            Self::Linear | Self::Monotone => None,
+            Self::StepBefore => Some((prev.0, next.1)),
+            Self::StepAfter => Some((next.0, prev.1)),
+        }
+    }
+    // bezier control points of each pair of adjacent points of a monotone
+    // cubic (Fritsch-Carlson) through them; x must not decrease
+    pub fn get_monotone_control_points(points: &[(f64, f64)]) -> Vec<((f64, f64), (f64, f64))> {
+        if points.len() < 2 {
+            return Vec::new();
+        }
+        let secants: Vec<f64> = points
+            .windows(2)
+            .map(|pair| {
+                let dx = pair[1].0 - pair[0].0;
+                if dx > 0.0 {
+                    (pair[1].1 - pair[0].1) / dx
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        let mut tangents = vec![0.0; points.len()];
+        for index in 1..points.len() - 1 {
+            let (s0, s1) = (secants[index - 1], secants[index]);
+            // a local extremum stays flat, so the curve doesn't overshoot it
+            if s0 * s1 <= 0.0 {
+                continue;
+            }
+            let h0 = points[index].0 - points[index - 1].0;
+            let h1 = points[index + 1].0 - points[index].0;
+            let p = (s0 * h1 + s1 * h0) / (h0 + h1);
+            tangents[index] = 2.0 * s0.signum() * s0.abs().min(s1.abs()).min(0.5 * p.abs());
+        }
+        let last = points.len() - 1;
+        tangents[0] = Self::get_end_tangent(secants[0], tangents[1]);
+        tangents[last] = Self::get_end_tangent(secants[last - 1], tangents[last - 1]);
+        points
+            .windows(2)
+            .zip(tangents.windows(2))
+            .map(|(pair, tangents)| {
+                let third = (pair[1].0 - pair[0].0) / 3.0;
+                (
+                    (pair[0].0 + third, pair[0].1 + third * tangents[0]),
+                    (pair[1].0 - third, pair[1].1 - third * tangents[1]),
+                )
+            })
+            .collect()
+    }
+    fn get_end_tangent(secant: f64, neighbour_tangent: f64) -> f64 {
+        let tangent = (3.0 * secant - neighbour_tangent) * 0.5;
+        if tangent * secant <= 0.0 {
+            0.0
+        } else if tangent.abs() > 3.0 * secant.abs() {
+            3.0 * secant
+        } else {
+            tangent
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MovingAverage {
+    Sma,
+    Ema,
+}
+impl FromStr for MovingAverage {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sma" => Ok(Self::Sma),
+            "ema" => Ok(Self::Ema),
+            v => Err(format!("unsupported MovingAverage: {}", v)),
+        }
+    }
+}
+impl MovingAverage {
+    // missing values stay missing & are skipped by the average; the sma is
+    // missing until the window is filled
+    pub fn compute(&self, data_points: &[DataPoint], window: usize) -> Vec<f64> {
+        let window = window.max(1);
+        let mut result = Vec::with_capacity(data_points.len());
+        match self {
+            Self::Sma => {
+                let mut sum = 0.0;
+                let mut count: usize = 0;
+                for (index, data_point) in data_points.iter().enumerate() {
+                    if !data_point.value.is_nan() {
+                        sum += data_point.value;
+                        count += 1;
+                    }
+                    if index >= window {
+                        let dropped = data_points[index - window].value;
+                        if !dropped.is_nan() {
+                            sum -= dropped;
+                            count -= 1;
+                        }
+                    }
+                    result.push(
+                        if index + 1 < window || count == 0 || data_point.value.is_nan() {
+                            f64::NAN
+                        } else {
+                            sum / count as f64
+                        },
+                    );
+                }
+            }
+            Self::Ema => {
+                let alpha = 2.0 / (window as f64 + 1.0);
+                let mut ema = f64::NAN;
+                for data_point in data_points.iter() {
+                    if data_point.value.is_nan() {
+                        result.push(f64::NAN);
+                        continue;
+                    }
+                    ema = if ema.is_nan() {
+                        data_point.value
+                    } else {
+                        alpha * data_point.value + (1.0 - alpha) * ema
+                    };
+                    result.push(ema);
+                }
+            }
+        }
+        result
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Aggregation {
+    Mean,
+    Min,
+    Max,
+    Sum,
+}
+impl FromStr for Aggregation {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mean" => Ok(Self::Mean),
+            "min" => Ok(Self::Min),
+            "max" => Ok(Self::Max),
+            "sum" => Ok(Self::Sum),
+            v => Err(format!("unsupported Aggregation: {}", v)),
+        }
+    }
+}
+impl Aggregation {
+    // one point per bucket of [k * bucket, (k + 1) * bucket) coords, placed at
+    // its start; buckets with missing values only stay missing
+    pub fn apply(&self, coords: &[f64], values: &[f64], bucket: f64) -> (Vec<f64>, Vec<f64>) {
+        // (sum, min, max, count) per bucket index
+        let mut buckets: BTreeMap<i64, (f64, f64, f64, usize)> = BTreeMap::new();
+        for (coord, value) in coords.iter().zip(values.iter()) {
+            let acc = buckets.entry((coord / bucket).floor() as i64).or_insert((
+                0.0,
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+                0,
+            ));
+            if !value.is_nan() {
+                acc.0 += value;
+                acc.1 = acc.1.min(*value);
+                acc.2 = acc.2.max(*value);
+                acc.3 += 1;
+            }
+        }
+        buckets
+            .into_iter()
+            .map(|(index, (sum, min, max, count))| {
+                let value = match (count, self) {
+                    (0, _) => f64::NAN,
+                    (_, Self::Mean) => sum / count as f64,
+                    (_, Self::Min) => min,
+                    (_, Self::Max) => max,
+                    (_, Self::Sum) => sum,
+                };
+                (index as f64 * bucket, value)
+            })
+            .unzip()
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::data_set::{DataPoint, DataSet};
+    use crate::data_set::{Aggregation, DataPoint, DataSet, Interpolation, MovingAverage};
+    use crate::decimate::DecimationConsumer;
+    use std::str::FromStr;
     #[test]
     fn test_bin_search_empty() {
         let empty_data = DataSet::new("test", (255, 255, 255), vec![]);
@@ -199,6 +711,217 @@ mod tests {
         }
     }
     #[test]
+    fn test_get_value_extents() {
+        let data = DataSet::new(
+            "test",
+            (255, 255, 255),
+            (0..1000)
+                .map(|i| DataPoint {
+                    coord: i as f64,
+                    value: if i % 7 == 3 {
+                        f64::NAN
+                    } else {
+                        ((i * 7919) % 1013) as f64 - 500.0
+                    },
+                })
+                .collect(),
+        );
+        for (coord_start, coord_end) in [
+            (0.0, 999.0),
+            (-10.0, 2000.0),
+            (3.0, 3.0),
+            (2.5, 17.5),
+            (100.0, 101.0),
+            (333.0, 777.0),
+            (998.5, 2000.0),
+        ] {
+            let mut expected: Option<(f64, f64)> = None;
+            let slice = DataSet::slice_data_points_by_coord(
+                data.data_points.as_slice(),
+                coord_start,
+                coord_end,
+            );
+            for data_point in slice.iter().filter(|p| !p.value.is_nan()) {
+                expected = Some(match expected {
+                    Some((min, max)) => (min.min(data_point.value), max.max(data_point.value)),
+                    None => (data_point.value, data_point.value),
+                });
+            }
+            assert_eq!(
+                data.get_value_extents(coord_start, coord_end),
+                (slice.len(), expected)
+            );
+        }
+        assert_eq!(data.get_value_extents(2000.0, 3000.0), (0, None));
+    }
+    #[test]
+    fn test_zoom_levels() {
+        let mut data = DataSet::new(
+            "test",
+            (255, 255, 255),
+            (0..10000)
+                .map(|i| DataPoint {
+                    coord: i as f64,
+                    value: (i as f64 * 0.1).sin(),
+                })
+                .collect(),
+        );
+        assert_eq!(DataSet::get_zoom_target_points(1000, 1.0), 1000);
+        // a third of the range needs 4x the points
+        assert_eq!(DataSet::get_zoom_target_points(1000, 3.0), 4000);
+        assert_eq!(DataSet::get_zoom_target_points(1000, 4.0), 4000);
+
+        // nothing to draw instead yet, so the first level is computed at once
+        let picked = data.pick_decimated(4000, DecimationConsumer::Camera);
+        assert_eq!(data.get_decimated(picked).len(), 4000);
+
+        // a deeper one is drawn from the closest level & refined next frame
+        assert_eq!(
+            data.pick_decimated(8000, DecimationConsumer::Camera),
+            picked
+        );
+        let picked = data.pick_decimated(8000, DecimationConsumer::Camera);
+        assert_eq!(data.get_decimated(picked).len(), 8000);
+        assert_eq!(data.decimated.len(), 2);
+
+        // fully zoomed in views need no decimation
+        let target_points = DataSet::get_zoom_target_points(1000, 20.0);
+        assert_eq!(
+            data.pick_decimated(target_points, DecimationConsumer::Camera),
+            None
+        );
+    }
+    #[test]
+    fn test_decimation_refinement_per_consumer() {
+        let mut data = DataSet::new(
+            "test",
+            (255, 255, 255),
+            (0..1000)
+                .map(|i| DataPoint {
+                    coord: i as f64,
+                    value: (i as f64 * 0.1).sin(),
+                })
+                .collect(),
+        );
+        data.precompute_decimation(100);
+
+        // the precomputed set is drawn once by each of them
+        assert_eq!(
+            data.pick_decimated(200, DecimationConsumer::Camera),
+            Some(0)
+        );
+        assert_eq!(
+            data.pick_decimated(300, DecimationConsumer::Preview),
+            Some(0)
+        );
+        assert_eq!(data.decimated.len(), 1);
+
+        // & refined on the next frame
+        let picked = data.pick_decimated(200, DecimationConsumer::Camera);
+        assert_eq!(data.get_decimated(picked).len(), 200);
+        let picked = data.pick_decimated(300, DecimationConsumer::Preview);
+        assert_eq!(data.get_decimated(picked).len(), 300);
+    }
+    #[test]
+    fn test_interpolate_value() {
+        let mut data = DataSet::new(
+            "test",
+            (255, 255, 255),
+            vec![
+                DataPoint {
+                    coord: 1.0,
+                    value: 10.0,
+                },
+                DataPoint {
+                    coord: 3.0,
+                    value: 20.0,
+                },
+                DataPoint {
+                    coord: 4.0,
+                    value: f64::NAN,
+                },
+            ],
+        );
+        assert_eq!(data.interpolate_value(0.5), None);
+        assert_eq!(data.interpolate_value(1.0), Some(10.0));
+        assert_eq!(data.interpolate_value(1.5), Some(12.5));
+        assert_eq!(data.interpolate_value(3.0), Some(20.0));
+        assert_eq!(data.interpolate_value(3.5), None);
+        assert_eq!(data.interpolate_value(5.0), None);
+
+        data.interpolation = Interpolation::StepAfter;
+        assert_eq!(data.interpolate_value(1.5), Some(10.0));
+        assert_eq!(data.interpolate_value(3.0), Some(20.0));
+        data.interpolation = Interpolation::StepBefore;
+        assert_eq!(data.interpolate_value(1.5), Some(20.0));
+        assert_eq!(data.interpolate_value(1.0), Some(10.0));
+        assert_eq!(
+            Interpolation::StepAfter.get_corner((0.0, 1.0), (2.0, 3.0)),
+            Some((2.0, 1.0))
+        );
+        assert_eq!(
+            Interpolation::from_str("step-before"),
+            Ok(Interpolation::StepBefore)
+        );
+        assert!(Interpolation::from_str("step").is_err());
+    }
+    #[test]
+    fn test_monotone_control_points() {
+        assert!(Interpolation::get_monotone_control_points(&[(0.0, 1.0)]).is_empty());
+
+        let points = [(0.0, 0.0), (3.0, 3.0), (6.0, 3.0), (9.0, 0.0)];
+        let control_points = Interpolation::get_monotone_control_points(&points);
+        assert_eq!(control_points.len(), 3);
+        // the plateau between the equal points is flat, not overshooting
+        assert_eq!(control_points[1], ((4.0, 3.0), (5.0, 3.0)));
+        for (index, (cp1, cp2)) in control_points.iter().enumerate() {
+            let (low, high) = (
+                points[index].1.min(points[index + 1].1),
+                points[index].1.max(points[index + 1].1),
+            );
+            for cp in [cp1, cp2] {
+                assert!(cp.1 >= low && cp.1 <= high, "{:?}", control_points);
+            }
+        }
+
+        // a straight line stays straight
+        let control_points =
+            Interpolation::get_monotone_control_points(&[(0.0, 0.0), (3.0, 6.0), (6.0, 12.0)]);
+        assert_eq!(
+            control_points,
+            vec![((1.0, 2.0), (2.0, 4.0)), ((4.0, 8.0), (5.0, 10.0))]
+        );
+    }
+    #[test]
+    fn test_meta_skips_nan() {
+        let data = DataSet::new(
+            "test",
+            (255, 255, 255),
+            vec![
+                DataPoint {
+                    coord: 1.0,
+                    value: 3.0,
+                },
+                DataPoint {
+                    coord: 2.0,
+                    value: f64::NAN,
+                },
+                DataPoint {
+                    coord: 3.0,
+                    value: 1.0,
+                },
+            ],
+        );
+        assert_eq!(data.meta.min, 1.0);
+        assert_eq!(data.meta.p50, 2.0);
+        assert_eq!(data.meta.max, 3.0);
+
+        assert_eq!(data.bin_search_defined_right_bound(2.0), Some(0));
+        assert_eq!(data.bin_search_defined_left_bound(2.0), Some(2));
+        assert_eq!(data.bin_search_defined_left_bound(1.5), Some(2));
+        assert_eq!(data.bin_search_defined_right_bound(3.5), Some(2));
+    }
+    #[test]
     fn test_bin_search_odd_number() {
         let data = DataSet::new(
             "test",
@@ -389,4 +1112,66 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    fn test_moving_average() {
+        let data_points: Vec<DataPoint> = [1.0, 2.0, f64::NAN, 4.0, 6.0]
+            .into_iter()
+            .enumerate()
+            .map(|(index, value)| DataPoint {
+                coord: index as f64,
+                value,
+            })
+            .collect();
+        let sma = MovingAverage::Sma.compute(&data_points, 2);
+        assert!(sma[0].is_nan());
+        assert_eq!(sma[1], 1.5);
+        assert!(sma[2].is_nan());
+        assert_eq!(sma[3..], [4.0, 5.0]);
+
+        let ema = MovingAverage::Ema.compute(&data_points, 3);
+        assert_eq!(ema[0..2], [1.0, 1.5]);
+        assert!(ema[2].is_nan());
+        assert_eq!(ema[3..], [2.75, 4.375]);
+    }
+
+    #[test]
+    fn test_aggregation() {
+        let coords = [0.0, 5.0, 10.0, 12.0, 31.0, 25.0];
+        let values = [1.0, 3.0, f64::NAN, f64::NAN, 4.0, 2.0];
+        let (bucket_coords, mean) = Aggregation::Mean.apply(&coords, &values, 10.0);
+        assert_eq!(bucket_coords, [0.0, 10.0, 20.0, 30.0]);
+        assert_eq!(mean[0], 2.0);
+        assert!(mean[1].is_nan());
+        assert_eq!(mean[2..], [2.0, 4.0]);
+        let (_, sum) = Aggregation::Sum.apply(&coords, &values, 10.0);
+        assert_eq!(sum[0], 4.0);
+        let (_, max) = Aggregation::Max.apply(&coords, &values, 10.0);
+        assert_eq!(max[0], 3.0);
+    }
+
+    #[test]
+    fn test_set_band() {
+        let mut data = DataSet::new(
+            "test",
+            (255, 255, 255),
+            vec![
+                DataPoint {
+                    coord: 1.0,
+                    value: 2.0,
+                },
+                DataPoint {
+                    coord: 3.0,
+                    value: 3.0,
+                },
+            ],
+        );
+        // the point at coord 2 was sampled out
+        data.set_band(&[3.0, 2.0, 1.0], &[2.5, 0.0, 1.0], &[5.0, 9.0, 2.5]);
+        assert_eq!(data.band_low.len(), 2);
+        assert_eq!(data.band_low[0].value, 1.0);
+        assert_eq!(data.band_high[1].value, 5.0);
+        assert_eq!((data.meta.min, data.meta.max), (1.0, 5.0));
+        assert_eq!(data.get_value_extents(0.0, 2.0), (1, Some((1.0, 2.5))));
+    }
 }
@@ -15,6 +15,9 @@ extern "C" {
 
     #[wasm_bindgen(js_namespace = console, js_name = log)]
     pub fn console_log_js_value(v: JsValue);
+
+    #[wasm_bindgen(js_namespace = console, js_name = warn)]
+    pub fn console_warn(s: &str);
 }
 
 #[allow(unused_macros)]
@@ -0,0 +1,331 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ *
+ * Copyright (C) 2023, Nikita Almakov
+ */
+use crate::data_set::DataPoint;
+use std::str::FromStr;
+use std::sync::atomic::AtomicUsize;
+
+// number of data sets drawn with an approximate decimation, which are to be
+// refined on the next frame
+pub static DECIMATION_REFINEMENTS_PENDING: AtomicUsize = AtomicUsize::new(0);
+
+pub const DECIMATION_POINTS_PER_PX: f64 = 2.0;
+
+// each of them defers refinements of its own resolution
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DecimationConsumer {
+    Camera = 0,
+    ContextGhost = 1,
+    Preview = 2,
+}
+pub const DECIMATION_CONSUMERS: usize = 3;
+
+type Decimator = fn(&[DataPoint], usize) -> Vec<DataPoint>;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DecimationMode {
+    Lttb,
+    MinMax,
+    Nth,
+    None,
+}
+impl FromStr for DecimationMode {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lttb" => Ok(Self::Lttb),
+            "minmax" => Ok(Self::MinMax),
+            "nth" => Ok(Self::Nth),
+            "none" => Ok(Self::None),
+            v => Err(format!("unsupported DecimationMode: {}", v)),
+        }
+    }
+}
+impl DecimationMode {
+    pub fn get_decimator(&self) -> Option<Decimator> {
+        match self {
+            Self::Lttb => Some(lttb),
+            Self::MinMax => Some(min_max),
+            Self::Nth => Some(nth),
+            Self::None => None,
+        }
+    }
+}
+
+fn copy_data_points<'a>(data_points: impl Iterator<Item = &'a DataPoint>) -> Vec<DataPoint> {
+    data_points
+        .map(|p| DataPoint {
+            coord: p.coord,
+            value: p.value,
+        })
+        .collect()
+}
+
+// NaN values break lines, so finite runs are decimated separately and stay
+// separated by a single NaN point
+pub fn decimate(
+    data_points: &[DataPoint],
+    target_points: usize,
+    decimator: Decimator,
+) -> Vec<DataPoint> {
+    let mut result: Vec<DataPoint> = Vec::with_capacity(target_points + 1);
+    let total = data_points.len();
+    let mut run_start: usize = 0;
+    for index in 0..=total {
+        if index < total && !data_points[index].value.is_nan() {
+            continue;
+        }
+        if run_start < index {
+            let run = &data_points[run_start..index];
+            let run_target = (target_points * run.len()).div_ceil(total);
+            result.extend(decimator(run, run_target));
+        }
+        if index < total {
+            result.push(DataPoint {
+                coord: data_points[index].coord,
+                value: f64::NAN,
+            });
+        }
+        run_start = index + 1;
+    }
+    result
+}
+
+// largest triangle three buckets
+pub fn lttb(data_points: &[DataPoint], target_points: usize) -> Vec<DataPoint> {
+    let length = data_points.len();
+    if target_points >= length || target_points < 3 {
+        return copy_data_points(data_points.iter());
+    }
+    let mut result: Vec<DataPoint> = Vec::with_capacity(target_points);
+    let bucket_size = (length - 2) as f64 / (target_points - 2) as f64;
+
+    let mut a = &data_points[0];
+    result.push(DataPoint {
+        coord: a.coord,
+        value: a.value,
+    });
+    for bucket in 0..target_points - 2 {
+        let next_start = ((bucket + 1) as f64 * bucket_size) as usize + 1;
+        let next_end = (((bucket + 2) as f64 * bucket_size) as usize + 1).min(length);
+        let next_length = (next_end - next_start) as f64;
+        let (mut avg_coord, mut avg_value) = (0.0, 0.0);
+        for p in data_points[next_start..next_end].iter() {
+            avg_coord += p.coord;
+            avg_value += p.value;
+        }
+        avg_coord /= next_length;
+        avg_value /= next_length;
+
+        let start = (bucket as f64 * bucket_size) as usize + 1;
+        let end = next_start;
+        let mut max_area: f64 = -1.0;
+        let mut picked = &data_points[start];
+        for p in data_points[start..end].iter() {
+            let area = ((a.coord - avg_coord) * (p.value - a.value)
+                - (a.coord - p.coord) * (avg_value - a.value))
+                .abs();
+            if area > max_area {
+                max_area = area;
+                picked = p;
+            }
+        }
+        result.push(DataPoint {
+            coord: picked.coord,
+            value: picked.value,
+        });
+        a = picked;
+    }
+    let last = &data_points[length - 1];
+    result.push(DataPoint {
+        coord: last.coord,
+        value: last.value,
+    });
+    result
+}
+
+// the lowest & the highest points of each bucket in coord order, the first &
+// the last points are always kept
+pub fn min_max(data_points: &[DataPoint], target_points: usize) -> Vec<DataPoint> {
+    let length = data_points.len();
+    if target_points >= length || target_points < 4 {
+        return copy_data_points(data_points.iter());
+    }
+    let inner_points = &data_points[1..length - 1];
+    let inner_length = inner_points.len();
+    let buckets = (target_points - 2) / 2;
+    let mut result: Vec<DataPoint> = Vec::with_capacity(buckets * 2 + 2);
+    result.extend(copy_data_points(data_points.first().into_iter()));
+    for bucket in 0..buckets {
+        let start = bucket * inner_length / buckets;
+        let end = (bucket + 1) * inner_length / buckets;
+        let bucket_points = &inner_points[start..end];
+        let (mut min_index, mut max_index) = (0, 0);
+        for (index, p) in bucket_points.iter().enumerate() {
+            if p.value < bucket_points[min_index].value {
+                min_index = index;
+            }
+            if p.value > bucket_points[max_index].value {
+                max_index = index;
+            }
+        }
+        let (first, second) = if min_index <= max_index {
+            (min_index, max_index)
+        } else {
+            (max_index, min_index)
+        };
+        result.extend(copy_data_points(
+            [&bucket_points[first], &bucket_points[second]]
+                .into_iter()
+                .take(if first == second { 1 } else { 2 }),
+        ));
+    }
+    result.extend(copy_data_points(data_points.last().into_iter()));
+    result
+}
+
+// every n-th point, the last one is always kept
+pub fn nth(data_points: &[DataPoint], target_points: usize) -> Vec<DataPoint> {
+    let length = data_points.len();
+    if target_points >= length || target_points < 2 {
+        return copy_data_points(data_points.iter());
+    }
+    let step = (length - 1).div_ceil(target_points - 1);
+    let mut result = copy_data_points(data_points.iter().step_by(step));
+    if !(length - 1).is_multiple_of(step) {
+        result.extend(copy_data_points(data_points.last().into_iter()));
+    }
+    result
+}
+
+// the points closest to evenly spaced coords, the first & last ones included
+pub fn sample_evenly(data_points: &[DataPoint], target_points: usize) -> Vec<DataPoint> {
+    let length = data_points.len();
+    if target_points >= length || target_points < 2 {
+        return copy_data_points(data_points.iter());
+    }
+    let first_coord = data_points[0].coord;
+    let step = (data_points[length - 1].coord - first_coord) / (target_points - 1) as f64;
+    let mut result: Vec<DataPoint> = Vec::with_capacity(target_points);
+    let mut last_index: Option<usize> = None;
+    for k in 0..target_points {
+        let coord = first_coord + step * k as f64;
+        let right = data_points
+            .partition_point(|p| p.coord < coord)
+            .min(length - 1);
+        let index = if k == target_points - 1 {
+            length - 1
+        } else if right > 0
+            && coord - data_points[right - 1].coord < data_points[right].coord - coord
+        {
+            right - 1
+        } else {
+            right
+        };
+        if last_index.is_none_or(|last_index| index > last_index) {
+            result.extend(copy_data_points(data_points[index..=index].iter()));
+            last_index = Some(index);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_data_points(values: &[f64]) -> Vec<DataPoint> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| DataPoint {
+                coord: index as f64,
+                value: *value,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_lttb() {
+        let values: Vec<f64> = (0..1000).map(|i| ((i % 100) as f64).sin()).collect();
+        let data_points = make_data_points(values.as_slice());
+        let result = lttb(data_points.as_slice(), 100);
+        assert_eq!(result.len(), 100);
+        assert_eq!(result.first(), data_points.first());
+        assert_eq!(result.last(), data_points.last());
+        assert!(result.windows(2).all(|w| w[0].coord < w[1].coord));
+
+        let data_points = make_data_points(&[0.0, 0.0, 0.0, 10.0, 0.0, 0.0, 0.0]);
+        let result = lttb(data_points.as_slice(), 3);
+        assert_eq!(result[1].value, 10.0);
+    }
+
+    #[test]
+    fn test_sample_evenly() {
+        // dense start, sparse end
+        let data_points: Vec<DataPoint> = (0..1000)
+            .map(|i| DataPoint {
+                coord: (i * i) as f64,
+                value: i as f64,
+            })
+            .collect();
+        let result = sample_evenly(data_points.as_slice(), 10);
+        assert!(result.len() <= 10);
+        assert_eq!(result.first(), data_points.first());
+        assert_eq!(result.last(), data_points.last());
+        assert!(result.windows(2).all(|w| w[0].coord < w[1].coord));
+        let step = data_points.last().unwrap().coord / 9.0;
+        for (k, data_point) in result.iter().enumerate() {
+            assert!((data_point.coord - step * k as f64).abs() < 2000.0);
+        }
+        assert_eq!(sample_evenly(data_points.as_slice(), 2000).len(), 1000);
+    }
+
+    #[test]
+    fn test_decimate_keeps_breaks() {
+        let mut values: Vec<f64> = (0..200).map(|i| i as f64).collect();
+        values[100] = f64::NAN;
+        let data_points = make_data_points(values.as_slice());
+        let result = decimate(data_points.as_slice(), 20, lttb);
+        assert_eq!(result.iter().filter(|p| p.value.is_nan()).count(), 1);
+        assert!(result.len() <= 22);
+        assert_eq!(result.first(), data_points.first());
+        assert_eq!(result.last(), data_points.last());
+    }
+
+    #[test]
+    fn test_decimation_modes() {
+        let mut values: Vec<f64> = (0..1000).map(|i| ((i % 50) as f64).sin()).collect();
+        values[333] = 100.0;
+        values[666] = -100.0;
+        let data_points = make_data_points(values.as_slice());
+        for mode in ["lttb", "minmax", "nth"] {
+            let decimator = mode
+                .parse::<DecimationMode>()
+                .unwrap()
+                .get_decimator()
+                .unwrap();
+            let result = decimator(data_points.as_slice(), 100);
+            assert!(result.len() <= 100, "{}: {}", mode, result.len());
+            assert!(result.len() >= 50, "{}: {}", mode, result.len());
+            assert_eq!(result.first(), data_points.first(), "{}", mode);
+            assert_eq!(result.last(), data_points.last(), "{}", mode);
+            assert!(
+                result.windows(2).all(|w| w[0].coord < w[1].coord),
+                "{}",
+                mode
+            );
+
+            let extremes_kept =
+                result.iter().any(|p| p.value == 100.0) && result.iter().any(|p| p.value == -100.0);
+            // nth-point is the only one which doesn't look at values
+            assert_eq!(extremes_kept, mode != "nth", "{}", mode);
+        }
+        assert!(DecimationMode::None.get_decimator().is_none());
+        assert!("average".parse::<DecimationMode>().is_err());
+    }
+}
@@ -0,0 +1,107 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ *
+ * Copyright (C) 2023, Nikita Almakov
+ */
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorCode {
+    // a config option is missing or invalid
+    Config,
+    // a param, e.g. a data point, is missing or invalid
+    Params,
+    // e.g. an unknown chart id or an invalid export scale
+    Runtime,
+}
+impl ErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Config => "config",
+            Self::Params => "params",
+            Self::Runtime => "runtime",
+        }
+    }
+}
+
+// crosses to js as { code, path, message }, so apps can point at the
+// offending field
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartError {
+    pub code: ErrorCode,
+    pub path: Option<String>,
+    pub message: String,
+}
+impl ChartError {
+    pub fn at(path: String, message: &str) -> Self {
+        Self {
+            code: ErrorCode::Params,
+            path: Some(path),
+            message: message.to_string(),
+        }
+    }
+    pub fn with_code(mut self, code: ErrorCode) -> Self {
+        self.code = code;
+        self
+    }
+    #[cfg(feature = "wasm")]
+    pub fn to_js_value(&self) -> wasm_bindgen::JsValue {
+        use wasm_bindgen::JsValue;
+        let obj = js_sys::Object::new();
+        for (key, value) in [
+            ("code", JsValue::from_str(self.code.as_str())),
+            (
+                "path",
+                self.path
+                    .as_deref()
+                    .map(JsValue::from_str)
+                    .unwrap_or(JsValue::NULL),
+            ),
+            ("message", JsValue::from_str(self.message.as_str())),
+        ] {
+            js_sys::Reflect::set(&obj, &JsValue::from_str(key), &value).unwrap();
+        }
+        obj.into()
+    }
+}
+impl From<String> for ChartError {
+    fn from(message: String) -> Self {
+        Self {
+            code: ErrorCode::Runtime,
+            path: None,
+            message,
+        }
+    }
+}
+impl fmt::Display for ChartError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.path.as_ref() {
+            Some(path) => write!(f, "{}: {}: {}", self.code.as_str(), self.message, path),
+            None => write!(f, "{}: {}", self.code.as_str(), self.message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::{ChartError, ErrorCode};
+
+    #[test]
+    fn test_chart_error() {
+        let error = ChartError::at("dataSets[2].coords.5".to_string(), "failed to parse as f64")
+            .with_code(ErrorCode::Params);
+        assert_eq!(error.path.as_deref(), Some("dataSets[2].coords.5"));
+        assert_eq!(error.message, "failed to parse as f64");
+        assert_eq!(
+            error.to_string(),
+            "params: failed to parse as f64: dataSets[2].coords.5"
+        );
+
+        let error = ChartError::from("chart not found by id".to_string());
+        assert_eq!(error.code, ErrorCode::Runtime);
+        assert_eq!(error.path, None);
+        assert_eq!(error.to_string(), "runtime: chart not found by id");
+    }
+}
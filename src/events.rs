@@ -41,3 +41,27 @@ impl Drop for JsEventListener {
             .unwrap();
     }
 }
+
+// the window resize event misses containers resized by the layout, e.g. on a
+// sidebar toggle
+pub struct JsResizeObserver {
+    observer: web_sys::ResizeObserver,
+    _closure: Closure<dyn Fn(JsValue)>,
+}
+impl JsResizeObserver {
+    // None when ResizeObserver is not supported
+    pub fn new(target: &web_sys::Element, listener: Box<dyn Fn(JsValue)>) -> Option<Self> {
+        let closure = Closure::new(listener);
+        let observer = web_sys::ResizeObserver::new(closure.as_ref().unchecked_ref()).ok()?;
+        observer.observe(target);
+        Some(Self {
+            observer,
+            _closure: closure,
+        })
+    }
+}
+impl Drop for JsResizeObserver {
+    fn drop(&mut self) {
+        self.observer.disconnect();
+    }
+}
@@ -5,10 +5,43 @@
  *
  * Copyright (C) 2023, Nikita Almakov
  */
-use crate::animate::AnimatedNumber;
+use crate::animate::{AnimatedNumber, Animation};
 use crate::params::DataType;
+use crate::scale::expand_zero_range;
+use chrono::prelude::*;
+use chrono::{Days, Months};
 use std::cmp::Ordering;
 
+pub const SECOND_MS: f64 = 1000.0;
+pub const MINUTE_MS: f64 = 60.0 * SECOND_MS;
+pub const HOUR_MS: f64 = 60.0 * MINUTE_MS;
+pub const DAY_MS: f64 = 24.0 * HOUR_MS;
+
+// the calendar unit date ticks this far from each other are snapped to & labeled by
+#[derive(Debug, PartialEq)]
+pub enum CalendarUnit {
+    Year,
+    Quarter,
+    Month,
+    Week,
+    Day,
+}
+pub fn calendar_unit(period_ms: f64) -> Option<CalendarUnit> {
+    if period_ms >= 366.0 * DAY_MS {
+        Some(CalendarUnit::Year)
+    } else if period_ms >= 92.0 * DAY_MS {
+        Some(CalendarUnit::Quarter)
+    } else if period_ms >= 31.0 * DAY_MS {
+        Some(CalendarUnit::Month)
+    } else if period_ms >= 7.0 * DAY_MS {
+        Some(CalendarUnit::Week)
+    } else if period_ms >= DAY_MS {
+        Some(CalendarUnit::Day)
+    } else {
+        None
+    }
+}
+
 #[derive(Debug)]
 pub struct Tick {
     pub normalized_value: f64,
@@ -22,6 +55,24 @@ pub struct TickGeneration {
     period: f64,
     alpha: AnimatedNumber,
 }
+// maps normalized ticks of a date axis to timestamps & back
+#[derive(Clone)]
+pub struct Calendar {
+    global_min: f64,
+    global_range: f64,
+    ms_per_unit: f64,
+    tz_offset: FixedOffset,
+}
+impl Calendar {
+    fn snap(&self, normalized_value: f64, normalized_period: f64) -> f64 {
+        let to_ms = self.global_range * self.ms_per_unit;
+        let timestamp_ms =
+            (normalized_value * self.global_range + self.global_min) * self.ms_per_unit;
+        let snapped_ms = snap_to_calendar(timestamp_ms, normalized_period * to_ms, &self.tz_offset);
+        (snapped_ms / self.ms_per_unit - self.global_min) / self.global_range
+    }
+}
+
 #[derive(Clone)]
 pub struct Grid {
     pub grid_base: f64,
@@ -29,12 +80,20 @@ pub struct Grid {
     pub min_period: Option<f64>,
     pub current_period: f64,
     pub generations: Vec<TickGeneration>,
+    pub calendar: Option<Calendar>,
 }
 impl Grid {
-    pub fn new(data_type: DataType, global_min: f64, global_max: f64) -> Self {
-        let min_period = match data_type.get_min_period() {
-            Some(min_period) => Some(min_period / (global_max - global_min)),
-            None => None,
+    // min_period_override replaces the data type's one, zero lifts it
+    pub fn new(
+        data_type: DataType,
+        global_min: f64,
+        global_max: f64,
+        min_period_override: Option<f64>,
+    ) -> Self {
+        let (global_min, global_max) = expand_zero_range(global_min, global_max);
+        let min_period = match min_period_override.or_else(|| data_type.get_min_period()) {
+            Some(min_period) if min_period > 0.0 => Some(min_period / (global_max - global_min)),
+            _ => None,
         };
         let mut grid_period: f64 = 1.0;
         let mut grid_base: f64;
@@ -54,10 +113,27 @@ impl Grid {
             }
         }
 
+        let calendar = match data_type {
+            DataType::Number => None,
+            DataType::Date => Some(Calendar {
+                global_min,
+                global_range: global_max - global_min,
+                ms_per_unit: 1.0,
+                tz_offset: FixedOffset::east_opt(0).unwrap(),
+            }),
+            DataType::DateTime { tz_offset } => Some(Calendar {
+                global_min,
+                global_range: global_max - global_min,
+                ms_per_unit: SECOND_MS,
+                tz_offset,
+            }),
+        };
+
         Self {
             grid_base,
             grid_period,
             min_period,
+            calendar,
             current_period: grid_period,
             generations: vec![TickGeneration {
                 period: grid_period,
@@ -69,6 +145,7 @@ impl Grid {
     pub fn get_ticks(
         &mut self,
         time_us: f64,
+        animation: Animation,
         normalized_min_value: f64,
         normalized_max_value: f64,
         max_ticks: f64,
@@ -91,21 +168,21 @@ impl Grid {
             for generation in self.generations.iter_mut() {
                 if generation.period == period {
                     generation_to_be_created = false;
-                    generation.alpha.set_value(1.0, Some(time_us));
+                    generation.alpha.set_value(1.0, Some(time_us), animation);
                 } else {
                     let this_ticks_number = range / generation.period;
                     if this_ticks_number > current_ticks_number * 4.0
                         || this_ticks_number < current_ticks_number as f64 * 0.25
                     {
-                        generation.alpha.set_value(0.0, None);
+                        generation.alpha.set_value(0.0, None, animation);
                     } else {
-                        generation.alpha.set_value(0.0, Some(time_us));
+                        generation.alpha.set_value(0.0, Some(time_us), animation);
                     }
                 }
             }
             if generation_to_be_created {
                 let mut alpha = AnimatedNumber::new(0.4);
-                alpha.set_value(1.0, Some(time_us));
+                alpha.set_value(1.0, Some(time_us), animation);
                 self.generations.push(TickGeneration { period, alpha });
             }
             self.current_period = period;
@@ -126,20 +203,27 @@ impl Grid {
             let left_bound = normalized_min_value + period * 0.25;
 
             while normalized_value < normalized_max_value {
+                let tick_value = match self.calendar.as_ref() {
+                    Some(calendar) => calendar.snap(normalized_value, period),
+                    None => normalized_value,
+                };
+                normalized_value += period;
+                if tick_value < normalized_min_value || tick_value >= normalized_max_value {
+                    continue;
+                }
                 ticks.push(Tick {
-                    normalized_value,
+                    normalized_value: tick_value,
                     value: 0.0,
-                    alpha: if normalized_value < left_bound || normalized_value > right_bound {
+                    alpha: if tick_value < left_bound || tick_value > right_bound {
                         alpha * 0.5
                     } else {
                         alpha
                     },
                     end_alpha,
                 });
-                normalized_value += period;
             }
         }
-        if self.generations.len() > 1 {
+        if self.generations.len() > 1 || self.calendar.is_some() {
             ticks.sort_unstable_by(|a, b| {
                 match a.normalized_value.partial_cmp(&b.normalized_value).unwrap() {
                     Ordering::Equal => b.alpha.partial_cmp(&a.alpha).unwrap(),
@@ -160,3 +244,269 @@ impl Grid {
         ticks
     }
 }
+
+// moves a timestamp to the closest calendar boundary which is not finer than
+// the period: years, quarters, months, weeks (Mondays), days, hours, ...
+pub fn snap_to_calendar(timestamp_ms: f64, period_ms: f64, tz_offset: &FixedOffset) -> f64 {
+    let local_ms = |date: NaiveDate| {
+        tz_offset
+            .from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap())
+            .unwrap()
+            .timestamp_millis() as f64
+    };
+    let datetime = match DateTime::from_timestamp_millis(timestamp_ms as i64) {
+        Some(datetime) => datetime.with_timezone(tz_offset),
+        None => return timestamp_ms,
+    };
+    let date = datetime.date_naive();
+    let (floor, ceil) = match calendar_unit(period_ms) {
+        Some(CalendarUnit::Year) => {
+            let floor = NaiveDate::from_ymd_opt(date.year(), 1, 1).unwrap();
+            (floor, floor + Months::new(12))
+        }
+        Some(CalendarUnit::Quarter) => {
+            let floor = NaiveDate::from_ymd_opt(date.year(), date.month0() / 3 * 3 + 1, 1).unwrap();
+            (floor, floor + Months::new(3))
+        }
+        Some(CalendarUnit::Month) => {
+            let floor = NaiveDate::from_ymd_opt(date.year(), date.month(), 1).unwrap();
+            (floor, floor + Months::new(1))
+        }
+        Some(CalendarUnit::Week) => {
+            let floor = date - Days::new(date.weekday().num_days_from_monday() as u64);
+            (floor, floor + Days::new(7))
+        }
+        Some(CalendarUnit::Day) => (date, date + Days::new(1)),
+        None => {
+            let unit_ms = match period_ms {
+                p if p >= HOUR_MS => HOUR_MS,
+                p if p >= MINUTE_MS => MINUTE_MS,
+                p if p >= SECOND_MS => SECOND_MS,
+                _ => return timestamp_ms,
+            };
+            let offset_ms = tz_offset.local_minus_utc() as f64 * SECOND_MS;
+            return ((timestamp_ms + offset_ms) / unit_ms).round() * unit_ms - offset_ms;
+        }
+    };
+    let (floor_ms, ceil_ms) = (local_ms(floor), local_ms(ceil));
+    if timestamp_ms - floor_ms <= ceil_ms - timestamp_ms {
+        floor_ms
+    } else {
+        ceil_ms
+    }
+}
+
+// makes sure a fully opaque tick sits exactly at zero when zero is in range
+pub fn ensure_zero_tick(
+    ticks: &mut Vec<Tick>,
+    value_min: f64,
+    value_max: f64,
+    normalized_zero: f64,
+) {
+    if !(value_min <= 0.0 && value_max >= 0.0) {
+        return;
+    }
+    let tolerance = (value_max - value_min) * 1e-9;
+    match ticks.iter_mut().find(|tick| tick.value.abs() <= tolerance) {
+        Some(tick) => {
+            tick.value = 0.0;
+            tick.alpha = 1.0;
+            tick.end_alpha = 1.0;
+        }
+        None => {
+            let index = ticks.partition_point(|tick| tick.value < 0.0);
+            ticks.insert(
+                index,
+                Tick {
+                    normalized_value: normalized_zero,
+                    alpha: 1.0,
+                    end_alpha: 1.0,
+                    value: 0.0,
+                },
+            );
+        }
+    }
+}
+
+pub fn pick_non_overlapping(ticks: &[Tick], spans: &[(f64, f64)]) -> Vec<bool> {
+    let mut order: Vec<usize> = (0..ticks.len()).collect();
+    order.sort_by(
+        |&a, &b| match ticks[b].end_alpha.partial_cmp(&ticks[a].end_alpha).unwrap() {
+            Ordering::Equal => ticks[b].alpha.partial_cmp(&ticks[a].alpha).unwrap(),
+            value => value,
+        },
+    );
+    let mut picked = vec![false; ticks.len()];
+    let mut taken: Vec<(f64, f64)> = Vec::with_capacity(ticks.len());
+    for index in order {
+        let (start, end) = spans[index];
+        if taken
+            .iter()
+            .all(|&(taken_start, taken_end)| end <= taken_start || start >= taken_end)
+        {
+            taken.push((start, end));
+            picked[index] = true;
+        }
+    }
+    picked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick(value: f64, end_alpha: f64) -> Tick {
+        Tick {
+            normalized_value: value,
+            alpha: end_alpha,
+            end_alpha,
+            value,
+        }
+    }
+
+    #[test]
+    fn test_pick_non_overlapping() {
+        let ticks = vec![tick(0.0, 1.0), tick(10.0, 0.0), tick(20.0, 1.0)];
+        // mixed short & long labels: the fading middle one collides with both
+        let spans = vec![(-2.0, 2.0), (4.0, 16.0), (14.0, 26.0)];
+        assert_eq!(
+            pick_non_overlapping(&ticks, &spans),
+            vec![true, false, true]
+        );
+
+        let ticks = vec![tick(0.0, 1.0), tick(10.0, 1.0), tick(20.0, 1.0)];
+        let spans = vec![(-6.0, 6.0), (4.0, 16.0), (14.0, 26.0)];
+        assert_eq!(
+            pick_non_overlapping(&ticks, &spans),
+            vec![true, false, true]
+        );
+
+        let spans = vec![(-1.0, 1.0), (9.0, 11.0), (19.0, 21.0)];
+        assert_eq!(pick_non_overlapping(&ticks, &spans), vec![true, true, true]);
+    }
+
+    #[test]
+    fn test_snap_to_calendar() {
+        let utc = FixedOffset::east_opt(0).unwrap();
+        let ms = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().timestamp_millis() as f64;
+        let snap = |s: &str, period_ms: f64, tz_offset: &FixedOffset| {
+            DateTime::from_timestamp_millis(snap_to_calendar(ms(s), period_ms, tz_offset) as i64)
+                .unwrap()
+                .with_timezone(tz_offset)
+                .to_rfc3339()
+        };
+        for (period_ms, expected) in [
+            (400.0 * DAY_MS, "2024-01-01T00:00:00+00:00"),
+            (100.0 * DAY_MS, "2023-10-01T00:00:00+00:00"),
+            (40.0 * DAY_MS, "2023-09-01T00:00:00+00:00"),
+            // Monday
+            (10.0 * DAY_MS, "2023-08-21T00:00:00+00:00"),
+            (2.0 * DAY_MS, "2023-08-18T00:00:00+00:00"),
+            (3.0 * HOUR_MS, "2023-08-17T14:00:00+00:00"),
+            (5.0 * MINUTE_MS, "2023-08-17T14:07:00+00:00"),
+        ] {
+            assert_eq!(snap("2023-08-17T14:07:09+00:00", period_ms, &utc), expected);
+        }
+        assert_eq!(
+            ms("2023-08-17T14:07:09.5+00:00"),
+            snap_to_calendar(ms("2023-08-17T14:07:09.5+00:00"), 500.0, &utc)
+        );
+
+        // boundaries are local ones
+        let tz_offset = FixedOffset::east_opt(3 * 3600).unwrap();
+        assert_eq!(
+            snap("2023-08-17T22:30:00+03:00", 2.0 * DAY_MS, &tz_offset),
+            "2023-08-18T00:00:00+03:00"
+        );
+        assert_eq!(
+            snap("2023-08-17T10:30:00+03:00", 2.0 * DAY_MS, &tz_offset),
+            "2023-08-17T00:00:00+03:00"
+        );
+    }
+
+    #[test]
+    fn test_date_ticks_land_on_midnights() {
+        let start = DateTime::parse_from_rfc3339("2023-01-03T14:07:00+00:00")
+            .unwrap()
+            .timestamp_millis() as f64;
+        let mut grid = Grid::new(DataType::Date, start, start + 30.0 * DAY_MS, None);
+        let ticks = grid.get_ticks(0.0, Animation::default(), 0.0, 1.0, 10.0);
+        assert!(ticks.len() > 1);
+        for tick in ticks.iter() {
+            let tick_ms = start + tick.normalized_value * 30.0 * DAY_MS;
+            assert!(((tick_ms / DAY_MS).round() * DAY_MS - tick_ms).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_datetime_ticks_stop_at_seconds() {
+        let offset = FixedOffset::east_opt(3600).unwrap();
+        // datetime coords are in seconds
+        let start = DateTime::parse_from_rfc3339("2023-01-03T09:30:00+01:00")
+            .unwrap()
+            .timestamp() as f64;
+        let range = 6.5 * 3600.0;
+        let data_type = DataType::DateTime { tz_offset: offset };
+        let mut grid = Grid::new(data_type, start, start + range, None);
+        // 3 seconds of a trading day
+        grid.get_ticks(0.0, Animation::default(), 0.5, 0.5 + 3.0 / range, 10.0);
+        assert_eq!(grid.current_period, 1.0 / range);
+
+        // ~1.6 hours apart
+        let mut grid = Grid::new(data_type, start, start + range, None);
+        let ticks = grid.get_ticks(0.0, Animation::default(), 0.0, 1.0, 4.0);
+        assert!(ticks.len() > 1);
+        for tick in ticks.iter() {
+            let tick_s = start + tick.normalized_value * range;
+            assert!(((tick_s / 3600.0).round() * 3600.0 - tick_s).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_min_period_on_deep_zoom() {
+        let day = 86400000.0;
+        // a quarter of an hour out of 10 days
+        let (zoomed_min, zoomed_max) = (0.5, 0.5 + 0.25 / 24.0 / 10.0);
+
+        let mut grid = Grid::new(DataType::Date, 0.0, 10.0 * day, None);
+        let ticks = grid.get_ticks(0.0, Animation::default(), zoomed_min, zoomed_max, 5.0);
+        assert_eq!(grid.current_period, 0.1);
+        assert!(ticks.len() <= 1);
+
+        let mut grid = Grid::new(DataType::Date, 0.0, 10.0 * day, Some(0.0));
+        let ticks = grid.get_ticks(0.0, Animation::default(), zoomed_min, zoomed_max, 5.0);
+        assert!(grid.current_period < 0.1 / 24.0);
+        assert!(ticks.len() >= 3);
+
+        let minute = 60000.0;
+        let mut grid = Grid::new(DataType::Date, 0.0, 10.0 * day, Some(minute));
+        grid.get_ticks(
+            0.0,
+            Animation::default(),
+            zoomed_min,
+            zoomed_min + minute / day / 100.0,
+            5.0,
+        );
+        assert_eq!(grid.current_period, minute / (10.0 * day));
+    }
+
+    #[test]
+    fn test_ensure_zero_tick() {
+        let mut ticks = vec![tick(-7.0, 1.0), tick(3.0, 1.0), tick(13.0, 1.0)];
+        ensure_zero_tick(&mut ticks, -10.0, 20.0, 0.0);
+        let values: Vec<f64> = ticks.iter().map(|tick| tick.value).collect();
+        assert_eq!(values, vec![-7.0, 0.0, 3.0, 13.0]);
+        assert_eq!(ticks[1].alpha, 1.0);
+
+        // an existing fading tick at zero is emphasized instead of duplicated
+        let mut ticks = vec![tick(-5.0, 1.0), tick(1e-12, 0.0), tick(5.0, 1.0)];
+        ensure_zero_tick(&mut ticks, -10.0, 10.0, 0.0);
+        assert_eq!(ticks.len(), 3);
+        assert_eq!((ticks[1].value, ticks[1].end_alpha), (0.0, 1.0));
+
+        // zero is not visible
+        let mut ticks = vec![tick(5.0, 1.0), tick(15.0, 1.0)];
+        ensure_zero_tick(&mut ticks, 1.0, 20.0, 0.0);
+        assert_eq!(ticks.len(), 2);
+    }
+}
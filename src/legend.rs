@@ -5,18 +5,41 @@
  *
  * Copyright (C) 2023, Nikita Almakov
  */
-use crate::params::{ChartConfig, Content};
+use crate::animate::Animation;
+use crate::params::{ChartConfig, Content, LegendLayout, LegendPosition};
 use crate::screen::ScreenRect;
 use crate::screen::{ScreenArea, ScreenPos, Size};
+use crate::tooltip::Tooltip;
 use crate::utils::is_click;
 use std::cell::RefCell;
 use std::rc::Rc;
-use wasm_bindgen::prelude::*;
 
 const SCREEN_PADDING: Size = Size::Px(5.0);
 const MARGIN_HORIZONTAL: Size = Size::Px(15.0);
 const MARGIN_VERTICAL: Size = Size::Px(5.0);
 const LINE_WIDTH: Size = Size::Px(2.0);
+const HOVER_PREVIEW_DIM_ALPHA: f64 = 0.15;
+
+// blends toggled alpha towards the hover preview without touching its end value
+pub fn apply_hover_preview(alpha: f64, index: usize, hover_preview: Option<(usize, f64)>) -> f64 {
+    match hover_preview {
+        Some((hovered_index, mix)) => {
+            let target = if hovered_index == index {
+                1.0
+            } else {
+                alpha * HOVER_PREVIEW_DIM_ALPHA
+            };
+            alpha + (target - alpha) * mix
+        }
+        None => alpha,
+    }
+}
+
+pub struct LegendClick {
+    pub time_us: f64,
+    pub index: usize,
+    pub was_isolated: bool,
+}
 
 pub struct LegendItem {
     pub width: f64,
@@ -41,6 +64,9 @@ pub struct Legend {
     pub mandatory_right_index: Option<usize>,
     pub approx_per_page: Option<usize>,
     pub has_next: bool,
+    pub last_click: Option<LegendClick>,
+    pub toggle_all: Option<ScreenRect>,
+    pub toggle_none: Option<ScreenRect>,
 }
 
 impl Legend {
@@ -61,6 +87,9 @@ impl Legend {
             mandatory_right_index: None,
             approx_per_page: None,
             has_next: false,
+            last_click: None,
+            toggle_all: None,
+            toggle_none: None,
         }
     }
     fn get_items(&mut self, content: &Content) -> Rc<Vec<LegendItem>> {
@@ -128,11 +157,52 @@ impl Legend {
         let c_margin_horizontal = MARGIN_HORIZONTAL.to_cpx_height(screen_area_handle);
         let c_margin_vertical = MARGIN_VERTICAL.to_cpx_height(screen_area_handle);
         let c_arrow_width = conf.font_size_large.to_cpx_width(screen_area_handle) * 3.0;
-        drop(conf);
+        let rtl = conf.rtl;
+        let columns_layout = matches!(conf.legend_layout, LegendLayout::Columns);
+        // a legend on a side lists the series one per row, paged with arrows
+        // above & below them
+        let vertical = matches!(
+            conf.legend_position,
+            LegendPosition::Left | LegendPosition::Right
+        );
+        let c_arrow_height = conf.font_size_large.to_cpx_height(screen_area_handle) * 2.0;
 
         let cy_start = screen_area_handle.top_cy() + c_screen_padding;
         let cy_end = screen_area_handle.bottom_cy() - c_screen_padding;
 
+        // show all / hide all take a fixed slot to the left of the arrows
+        let mut c_toggles_width = 0.0;
+        if conf.legend_bulk_toggles {
+            let c_font_height = conf.font_size_large.to_cpx_height(screen_area_handle);
+            let c_font_width = conf.font_size_large.to_cpx_width(screen_area_handle);
+            let [toggle_all, toggle_none] =
+                [conf.hint_show_all.as_str(), conf.hint_hide_all.as_str()].map(|hint| {
+                    let c_width = c_font_width * hint.chars().count() as f64 + c_font_height;
+                    let rect = ScreenRect::from_width(
+                        screen_area_handle.left_cx() + c_screen_padding + c_toggles_width,
+                        cy_start,
+                        c_width,
+                        c_font_height * 2.0,
+                    );
+                    c_toggles_width += c_width + c_margin_horizontal;
+                    rect
+                });
+            c_toggles_width += c_screen_padding;
+            self.toggle_all = Some(toggle_all);
+            self.toggle_none = Some(toggle_none);
+        } else {
+            self.toggle_all = None;
+            self.toggle_none = None;
+        }
+        // on a side they take a row above the series instead
+        let mut c_toggles_height = 0.0;
+        if vertical && conf.legend_bulk_toggles {
+            c_toggles_width = 0.0;
+            c_toggles_height =
+                conf.font_size_large.to_cpx_height(screen_area_handle) * 2.0 + c_margin_vertical;
+        }
+        drop(conf);
+
         let mut with_buttons = self.offset > 0;
         let mut cx_start: f64;
         let mut cx_end: f64;
@@ -141,38 +211,55 @@ impl Legend {
         let mut positions: Vec<ScreenRect>;
         let mut offset = self.offset;
         let mandatory_right_index = self.mandatory_right_index.clone();
+        let mut cy_items_start = cy_start;
+        let mut cy_items_end = cy_end;
 
         loop {
-            if with_buttons {
-                cx_start = screen_area_handle.left_cx() + c_arrow_width + c_margin_horizontal;
-                cx_end = screen_area_handle.right_cx() - c_arrow_width - c_margin_horizontal;
-            } else {
+            if vertical {
+                let c_arrows_height = if with_buttons {
+                    c_arrow_height + c_margin_vertical
+                } else {
+                    0.0
+                };
                 cx_start = screen_area_handle.left_cx() + c_screen_padding;
                 cx_end = screen_area_handle.right_cx() - c_screen_padding;
-            }
-
-            let mut cx = cx_start;
-            let mut cy = cy_start;
-            has_next = false;
-
-            positions = Vec::new();
-            let items = self.get_items(content);
-
-            for item in items.iter().skip(offset) {
-                if cx + item.width > cx_end {
-                    cx = cx_start;
-                    cy += item.height + c_margin_vertical;
+                cy_items_start = cy_start + c_toggles_height + c_arrows_height;
+                cy_items_end = cy_end - c_arrows_height;
+                (positions, has_next) = Self::layout_column(
+                    self.get_items(content).as_slice(),
+                    offset,
+                    (cx_start, cx_end),
+                    (cy_items_start, cy_items_end),
+                    c_margin_vertical,
+                    columns_layout,
+                    rtl,
+                );
+                if has_next {
+                    approx_per_page = Some(positions.len());
                 }
-                if cy + item.height > cy_end {
-                    if positions.len() == 0 {
-                        break;
-                    }
-                    has_next = true;
+            } else {
+                if with_buttons {
+                    cx_start = screen_area_handle.left_cx()
+                        + c_toggles_width
+                        + c_arrow_width
+                        + c_margin_horizontal;
+                    cx_end = screen_area_handle.right_cx() - c_arrow_width - c_margin_horizontal;
+                } else {
+                    cx_start = screen_area_handle.left_cx() + c_screen_padding + c_toggles_width;
+                    cx_end = screen_area_handle.right_cx() - c_screen_padding;
+                }
+
+                (positions, has_next) = Self::layout_rows(
+                    self.get_items(content).as_slice(),
+                    offset,
+                    (cx_start, cx_end),
+                    (cy_start, cy_end),
+                    (c_margin_horizontal, c_margin_vertical),
+                    columns_layout,
+                );
+                if has_next {
                     approx_per_page = Some(positions.len());
-                    break;
                 }
-                positions.push(ScreenRect::from_width(cx, cy, item.width, item.height));
-                cx += item.width + c_margin_horizontal;
             }
             if let Some(mandatory_right_index) = mandatory_right_index {
                 let right_index = offset + positions.len() - 1;
@@ -193,6 +280,21 @@ impl Legend {
         self.mandatory_right_index = None;
         self.has_next = has_next;
 
+        if vertical && (self.offset > 0 || has_next) {
+            self.arrow_left = Some(ScreenRect::from_width(
+                cx_start,
+                cy_items_start - c_arrow_height - c_margin_vertical,
+                cx_end - cx_start,
+                c_arrow_height,
+            ));
+            self.arrow_right = Some(ScreenRect::from_width(
+                cx_start,
+                cy_items_end + c_margin_vertical,
+                cx_end - cx_start,
+                c_arrow_height,
+            ));
+            return;
+        }
         if self.offset > 0 || has_next {
             let arrow_height = self.positions[self.positions.len() - 1].cy2 - self.positions[0].cy1;
             self.arrow_left = Some(ScreenRect::from_width(
@@ -211,16 +313,114 @@ impl Legend {
             self.arrow_left = None;
             self.arrow_right = None;
         }
+        if rtl {
+            // the previous page arrow goes to the right
+            std::mem::swap(&mut self.arrow_left, &mut self.arrow_right);
+        }
+    }
+
+    // flows the items from `offset` into rows, or into equal-width columns
+    // sized by the widest one; returns their positions and whether some didn't fit
+    fn layout_rows(
+        items: &[LegendItem],
+        offset: usize,
+        (cx_start, cx_end): (f64, f64),
+        (cy_start, cy_end): (f64, f64),
+        (c_margin_horizontal, c_margin_vertical): (f64, f64),
+        columns_layout: bool,
+    ) -> (Vec<ScreenRect>, bool) {
+        let mut cx = cx_start;
+        let mut cy = cy_start;
+        let mut positions: Vec<ScreenRect> = Vec::new();
+
+        // columns fit the widest item plus its swatch, which is as wide as it is tall
+        let columns = if columns_layout {
+            let max_width = items
+                .iter()
+                .map(|item| item.width + item.height)
+                .fold(0.0, f64::max);
+            let columns = (((cx_end - cx_start + c_margin_horizontal)
+                / (max_width + c_margin_horizontal))
+                .floor() as usize)
+                .max(1);
+            let column_width =
+                (cx_end - cx_start - c_margin_horizontal * (columns - 1) as f64) / columns as f64;
+            Some((columns, column_width))
+        } else {
+            None
+        };
+
+        for (index, item) in items.iter().skip(offset).enumerate() {
+            let width = match columns {
+                Some((columns, column_width)) => {
+                    if index > 0 && index % columns == 0 {
+                        cy += item.height + c_margin_vertical;
+                    }
+                    cx = cx_start + (index % columns) as f64 * (column_width + c_margin_horizontal);
+                    column_width
+                }
+                None => {
+                    if cx + item.width > cx_end {
+                        cx = cx_start;
+                        cy += item.height + c_margin_vertical;
+                    }
+                    item.width
+                }
+            };
+            if cy + item.height > cy_end {
+                let has_next = !positions.is_empty();
+                return (positions, has_next);
+            }
+            positions.push(ScreenRect::from_width(cx, cy, width, item.height));
+            cx += width + c_margin_horizontal;
+        }
+        (positions, false)
+    }
+
+    // stacks the items from `offset` one per row, returns their positions
+    // and whether some didn't fit
+    fn layout_column(
+        items: &[LegendItem],
+        offset: usize,
+        (cx_start, cx_end): (f64, f64),
+        (cy_start, cy_end): (f64, f64),
+        c_margin_vertical: f64,
+        fill_width: bool,
+        rtl: bool,
+    ) -> (Vec<ScreenRect>, bool) {
+        let mut positions = Vec::new();
+        let mut cy = cy_start;
+        for item in items.iter().skip(offset) {
+            if cy + item.height > cy_end {
+                let has_next = !positions.is_empty();
+                return (positions, has_next);
+            }
+            let width = if fill_width {
+                cx_end - cx_start
+            } else {
+                item.width.min(cx_end - cx_start)
+            };
+            let cx = if rtl { cx_end - width } else { cx_start };
+            positions.push(ScreenRect::from_width(cx, cy, width, item.height));
+            cy += item.height + c_margin_vertical;
+        }
+        (positions, false)
     }
 
     pub fn draw(&mut self, content: &Content) {
+        if matches!(
+            self.chart_config.borrow().legend_position,
+            LegendPosition::Hidden
+        ) {
+            return;
+        }
         self.resize(content);
         let screen_area_handle_rc = self.control_screen_area.get_handle();
         let screen_area_handle = screen_area_handle_rc.as_ref();
 
         let crc = screen_area_handle.crc.as_ref();
 
-        let color_white = JsValue::from_str("white");
+        let color_white = "white";
 
         {
             let conf = self.chart_config.borrow();
@@ -239,33 +439,89 @@ impl Legend {
 
         let offset = self.offset;
         let items = self.get_items(content);
+        let (columns_layout, rtl, c_font_width) = {
+            let conf = self.chart_config.borrow();
+            (
+                matches!(conf.legend_layout, LegendLayout::Columns),
+                conf.rtl,
+                conf.font_size_large.to_cpx_width(screen_area_handle),
+            )
+        };
+        if columns_layout {
+            crc.set_text_align(if rtl { "right" } else { "left" });
+        }
         for ((item, position), data_set) in items
             .iter()
             .skip(offset)
             .zip(self.positions.iter())
             .zip(content.data_sets.iter().skip(offset))
         {
-            let color = JsValue::from_str(item.color.as_str());
-            crc.set_fill_style(&color);
+            let color = item.color.as_str();
+            if columns_layout {
+                let c_swatch = item.height * 0.5;
+                let c_inset = (item.height - c_swatch) * 0.5;
+                let swatch_cx = if rtl {
+                    position.cx2 - c_inset - c_swatch
+                } else {
+                    position.cx1 + c_inset
+                };
+                crc.set_fill_style(color);
+                crc.set_stroke_style(color);
+                if data_set.alpha.get_end_value() == 0.0 {
+                    crc.stroke_rect(swatch_cx, position.cy1 + c_inset, c_swatch, c_swatch);
+                } else {
+                    crc.fill_rect(swatch_cx, position.cy1 + c_inset, c_swatch, c_swatch);
+                }
+                let max_chars =
+                    ((position.width() - item.height - c_inset) / c_font_width).max(1.0) as usize;
+                crc.fill_text(
+                    Tooltip::truncate_name(item.name.as_str(), max_chars).as_str(),
+                    if rtl {
+                        position.cx2 - item.height
+                    } else {
+                        position.cx1 + item.height
+                    },
+                    position.cy_center(),
+                );
+                continue;
+            }
+            crc.set_fill_style(color);
             if data_set.alpha.get_end_value() == 0.0 {
-                crc.set_stroke_style(&color);
-                crc.stroke_rect(position.cx1, position.cy1, item.width, item.height);
+                crc.set_stroke_style(color);
+                crc.stroke_rect(position.cx1, position.cy1, position.width(), item.height);
             } else {
-                crc.fill_rect(position.cx1, position.cy1, item.width, item.height);
-                crc.set_fill_style(&color_white);
+                crc.fill_rect(position.cx1, position.cy1, position.width(), item.height);
+                crc.set_fill_style(color_white);
             }
-            crc.fill_text(
-                item.name.as_str(),
-                position.cx1 + 0.5 * item.width,
-                position.cy1 + 0.5 * item.height,
-            )
-            .unwrap();
+            // a side strip can be narrower than the item
+            let name = if position.width() < item.width {
+                let max_chars =
+                    ((position.width() - item.height * 0.5) / c_font_width).max(1.0) as usize;
+                Tooltip::truncate_name(item.name.as_str(), max_chars)
+            } else {
+                item.name.clone()
+            };
+            crc.fill_text(name.as_str(), position.cx_center(), position.cy_center());
         }
 
         let conf = self.chart_config.borrow();
+        if let (Some(toggle_all), Some(toggle_none)) = (&self.toggle_all, &self.toggle_none) {
+            let v = conf.color_tick;
+            let color = format!("rgb({}, {}, {})", v.0, v.1, v.2);
+            crc.set_fill_style(color.as_str());
+            crc.set_stroke_style(color.as_str());
+            crc.set_text_align("center");
+            for (rect, hint) in [
+                (toggle_all, conf.hint_show_all.as_str()),
+                (toggle_none, conf.hint_hide_all.as_str()),
+            ] {
+                crc.stroke_rect(rect.cx1, rect.cy1, rect.width(), rect.height());
+                crc.fill_text(hint, rect.cx_center(), rect.cy_center());
+            }
+        }
         if let (Some(arrow_left), Some(arrow_right)) = (&self.arrow_left, &self.arrow_right) {
             let v = conf.color_preview_overlay;
-            crc.set_fill_style(&JsValue::from_str(
+            crc.set_fill_style(
                 format!(
                     "rgba({}, {}, {}, {})",
                     v.0,
@@ -274,14 +530,14 @@ impl Legend {
                     if self.offset > 0 { v.3 } else { v.3 * 0.5 }
                 )
                 .as_str(),
-            ));
+            );
             crc.fill_rect(
                 arrow_left.cx1,
                 arrow_left.cy1,
                 arrow_left.width(),
                 arrow_left.height(),
             );
-            crc.set_fill_style(&JsValue::from_str(
+            crc.set_fill_style(
                 format!(
                     "rgba({}, {}, {}, {})",
                     v.0,
@@ -290,7 +546,7 @@ impl Legend {
                     if self.has_next { v.3 } else { v.3 * 0.5 }
                 )
                 .as_str(),
-            ));
+            );
             crc.fill_rect(
                 arrow_right.cx1,
                 arrow_right.cy1,
@@ -306,42 +562,38 @@ impl Legend {
                 .as_str(),
             );
             let v = conf.color_preview_hint;
-            crc.set_fill_style(&JsValue::from_str(
-                format!("rgba({}, {}, {}, {})", v.0, v.1, v.2, v.3).as_str(),
-            ));
-            let c_width = arrow_left.width();
-            let c_height = arrow_left.height();
-            let c_size = c_width.min(c_height);
-            let c_horizontal = c_size * 0.3;
-            let c_vertical = c_size * 0.4;
-            let cx_center = arrow_left.cx_center();
-            let cy_center = arrow_left.cy_center();
-            crc.begin_path();
-            crc.move_to(
-                cx_center + c_horizontal * 0.35,
-                cy_center - c_vertical * 0.5,
-            );
-            crc.line_to(
-                cx_center + c_horizontal * 0.35,
-                cy_center + c_vertical * 0.5,
-            );
-            crc.line_to(cx_center - c_horizontal * 0.65, cy_center);
-            crc.close_path();
-            crc.fill();
-            let cx_center = arrow_right.cx_center();
-            let cy_center = arrow_right.cy_center();
-            crc.begin_path();
-            crc.move_to(
-                cx_center - c_horizontal * 0.35,
-                cy_center - c_vertical * 0.5,
-            );
-            crc.line_to(
-                cx_center - c_horizontal * 0.35,
-                cy_center + c_vertical * 0.5,
-            );
-            crc.line_to(cx_center + c_horizontal * 0.65, cy_center);
-            crc.close_path();
-            crc.fill();
+            crc.set_fill_style(format!("rgba({}, {}, {}, {})", v.0, v.1, v.2, v.3).as_str());
+            let c_size = arrow_left.width().min(arrow_left.height());
+            let c_length = c_size * 0.3;
+            let c_half_base = c_size * 0.2;
+            // glyphs point up & down on a side, otherwise sideways and the
+            // other way when the layout is mirrored
+            let direction = if matches!(
+                conf.legend_position,
+                LegendPosition::Left | LegendPosition::Right
+            ) {
+                (0.0, -1.0)
+            } else if conf.rtl {
+                (1.0, 0.0)
+            } else {
+                (-1.0, 0.0)
+            };
+            for (rect, (dx, dy)) in [
+                (arrow_left, direction),
+                (arrow_right, (-direction.0, -direction.1)),
+            ] {
+                let cx_base = rect.cx_center() - dx * c_length * 0.35;
+                let cy_base = rect.cy_center() - dy * c_length * 0.35;
+                crc.begin_path();
+                crc.move_to(cx_base - dy * c_half_base, cy_base + dx * c_half_base);
+                crc.line_to(cx_base + dy * c_half_base, cy_base - dx * c_half_base);
+                crc.line_to(
+                    rect.cx_center() + dx * c_length * 0.65,
+                    rect.cy_center() + dy * c_length * 0.65,
+                );
+                crc.close_path();
+                crc.fill();
+            }
         }
     }
     pub fn on_click(&mut self, content: &mut Content, time_us: f64) -> bool {
@@ -359,9 +611,29 @@ impl Legend {
                 }
             }
             if let Some(index) = clicked_index {
-                self.toggle_data_set(content, self.offset + index, time_us)
-                    .unwrap();
-                made_changes = true;
+                let index = self.offset + index;
+                let conf = self.chart_config.borrow();
+                made_changes |= Self::click_item(
+                    &mut self.last_click,
+                    content,
+                    index,
+                    time_us,
+                    conf.us_double_click,
+                    conf.animation,
+                );
+            }
+            for (toggle, alpha) in [(&self.toggle_all, 1.0), (&self.toggle_none, 0.0)] {
+                if toggle
+                    .as_ref()
+                    .is_some_and(|toggle| toggle.contains(cx, cy))
+                {
+                    let animation = self.chart_config.borrow().animation;
+                    for data_set in content.data_sets.iter_mut() {
+                        data_set.alpha.set_value(alpha, Some(time_us), animation);
+                    }
+                    self.last_click = None;
+                    made_changes = true;
+                }
             }
             if let Some(arrow_left) = &self.arrow_left {
                 if arrow_left.contains(cx, cy) {
@@ -378,6 +650,39 @@ impl Legend {
         }
         made_changes
     }
+    pub fn update_hover_preview(&mut self, content: &mut Content, time_us: f64) {
+        let mut hovered_index: Option<usize> = None;
+        let conf = self.chart_config.borrow();
+        if conf.legend_hover_preview && self.pointer_down.is_none() {
+            if let Some(pointer) = self.pointer.as_ref() {
+                let screen_area_handle = self.control_screen_area.get_handle();
+                let cx = screen_area_handle.get_cx(pointer);
+                let cy = screen_area_handle.get_cy(pointer);
+                hovered_index = self
+                    .positions
+                    .iter()
+                    .position(|position| position.contains(cx, cy))
+                    .map(|index| index + self.offset);
+            }
+        }
+        match hovered_index {
+            Some(index) => {
+                content.hovered_data_set = Some(index);
+                if content.hover_preview_mix.get_end_value() != 1.0 {
+                    content
+                        .hover_preview_mix
+                        .set_value(1.0, Some(time_us), conf.animation);
+                }
+            }
+            None => {
+                if content.hover_preview_mix.get_end_value() != 0.0 {
+                    content
+                        .hover_preview_mix
+                        .set_value(0.0, Some(time_us), conf.animation);
+                }
+            }
+        }
+    }
     pub fn on_long_press(&mut self, content: &mut Content, time_us: f64) -> usize {
         if let (Some(pointer_down_time_us), Some(pointer_down), Some(pointer)) = (
             &self.pointer_down_time_us,
@@ -387,7 +692,7 @@ impl Legend {
             let conf = self.chart_config.borrow();
 
             if time_us - *pointer_down_time_us > conf.us_long_press
-                && is_click(pointer_down, pointer)
+                && is_click(pointer_down, pointer, conf.drag_threshold)
             {
                 let screen_area_handle = self.control_screen_area.get_handle();
                 let mut clicked_index: Option<usize> = None;
@@ -400,13 +705,7 @@ impl Legend {
                     }
                 }
                 if let Some(index) = clicked_index {
-                    let index_to_show = index + self.offset;
-                    for (index, data_set) in content.data_sets.iter_mut().enumerate() {
-                        data_set.alpha.set_value(
-                            if index == index_to_show { 1.0 } else { 0.0 },
-                            Some(time_us),
-                        );
-                    }
+                    Self::isolate_data_set(content, index + self.offset, time_us, conf.animation);
                     self.pointer_down = None;
                     self.pointer_down_time_us = None;
                 }
@@ -417,11 +716,70 @@ impl Legend {
         }
     }
 
+    // a single click toggles right away; the second click of a double click
+    // overrides that toggle by isolating the series (or restoring all of them
+    // if it was isolated before the first click)
+    fn click_item(
+        last_click: &mut Option<LegendClick>,
+        content: &mut Content,
+        index: usize,
+        time_us: f64,
+        us_double_click: f64,
+        animation: Animation,
+    ) -> bool {
+        match last_click.take() {
+            Some(click) if click.index == index && time_us - click.time_us <= us_double_click => {
+                if click.was_isolated {
+                    for data_set in content.data_sets.iter_mut() {
+                        data_set.alpha.set_value(1.0, Some(time_us), animation);
+                    }
+                } else {
+                    Self::isolate_data_set(content, index, time_us, animation);
+                }
+                true
+            }
+            _ => {
+                let was_isolated = Self::is_isolated(content, index);
+                if Self::toggle_data_set(content, index, time_us, animation).is_err() {
+                    return false;
+                }
+                *last_click = Some(LegendClick {
+                    time_us,
+                    index,
+                    was_isolated,
+                });
+                true
+            }
+        }
+    }
+
+    fn isolate_data_set(
+        content: &mut Content,
+        index_to_show: usize,
+        time_us: f64,
+        animation: Animation,
+    ) {
+        for (index, data_set) in content.data_sets.iter_mut().enumerate() {
+            data_set.alpha.set_value(
+                if index == index_to_show { 1.0 } else { 0.0 },
+                Some(time_us),
+                animation,
+            );
+        }
+    }
+    fn is_isolated(content: &Content, index: usize) -> bool {
+        content
+            .data_sets
+            .iter()
+            .enumerate()
+            .all(|(index_, data_set)| (data_set.alpha.get_end_value() == 1.0) == (index_ == index))
+    }
+
     fn toggle_data_set(
-        &self,
         content: &mut Content,
         index: usize,
         time_us: f64,
+        animation: Animation,
     ) -> Result<(), String> {
         let number_of_data_sets = content.data_sets.len();
         if index >= number_of_data_sets {
@@ -440,13 +798,197 @@ impl Legend {
         {
             for (index_, data_set) in content.data_sets.iter_mut().enumerate() {
                 if index_ != index {
-                    data_set.alpha.set_value(1.0, Some(time_us));
+                    data_set.alpha.set_value(1.0, Some(time_us), animation);
                 }
             }
         } else {
             let alpha = &mut content.data_sets[index].alpha;
-            alpha.set_value(1.0 - alpha.get_end_value(), Some(time_us));
+            alpha.set_value(1.0 - alpha.get_end_value(), Some(time_us), animation);
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::animate::Animation;
+    use crate::legend::{Legend, LegendItem};
+    use crate::params::Content;
+    use crate::screen::ScreenRect;
+
+    #[test]
+    fn test_isolate_data_set() {
+        let mut content = Content::default();
+        for name in ["a", "b", "c"] {
+            content
+                .parse_and_add_data_set(name, vec![1.0, 2.0], vec![1.0, 2.0], (0, 0, 0), 0.0)
+                .unwrap();
+        }
+        assert!(!Legend::is_isolated(&content, 1));
+        Legend::isolate_data_set(&mut content, 1, 0.0, Animation::default());
+        assert!(Legend::is_isolated(&content, 1));
+        assert!(!Legend::is_isolated(&content, 0));
+        let alphas: Vec<f64> = content
+            .data_sets
+            .iter()
+            .map(|data_set| data_set.alpha.get_end_value())
+            .collect();
+        assert_eq!(alphas, vec![0.0, 1.0, 0.0]);
+    }
+    #[test]
+    fn test_click_item() {
+        let mut content = Content::default();
+        for name in ["a", "b", "c"] {
+            content
+                .parse_and_add_data_set(name, vec![1.0, 2.0], vec![1.0, 2.0], (0, 0, 0), 0.0)
+                .unwrap();
+        }
+        let alphas = |content: &Content| -> Vec<f64> {
+            content
+                .data_sets
+                .iter()
+                .map(|data_set| data_set.alpha.get_end_value())
+                .collect()
+        };
+        let mut last_click = None;
+        let mut click = |content: &mut Content, index: usize, time_us: f64| {
+            Legend::click_item(
+                &mut last_click,
+                content,
+                index,
+                time_us,
+                300.0,
+                Animation::default(),
+            )
+        };
+
+        // a single click toggles right away
+        assert!(click(&mut content, 1, 0.0));
+        assert_eq!(alphas(&content), vec![1.0, 0.0, 1.0]);
+        // the second click of a double click isolates the series instead
+        assert!(click(&mut content, 1, 200.0));
+        assert_eq!(alphas(&content), vec![0.0, 1.0, 0.0]);
+
+        // another double click restores everything
+        click(&mut content, 1, 2000.0);
+        click(&mut content, 1, 2100.0);
+        assert_eq!(alphas(&content), vec![1.0, 1.0, 1.0]);
+
+        // slow clicks are two toggles
+        click(&mut content, 2, 5000.0);
+        click(&mut content, 2, 5400.0);
+        assert_eq!(alphas(&content), vec![1.0, 1.0, 1.0]);
+
+        // as are quick clicks on different items
+        click(&mut content, 0, 9000.0);
+        click(&mut content, 1, 9100.0);
+        assert_eq!(alphas(&content), vec![0.0, 0.0, 1.0]);
+
+        // a double click doesn't span a third click
+        click(&mut content, 2, 12000.0);
+        click(&mut content, 2, 12100.0);
+        click(&mut content, 2, 12200.0);
+        assert_eq!(alphas(&content), vec![1.0, 1.0, 0.0]);
+
+        // an out of bound index is ignored
+        assert!(!click(&mut content, 5, 20000.0));
+        assert!(last_click.is_none());
+    }
+    #[test]
+    fn test_layout_rows() {
+        let items: Vec<LegendItem> = [30.0, 80.0, 50.0, 40.0, 20.0]
+            .iter()
+            .map(|&width| LegendItem {
+                width,
+                height: 10.0,
+                color: String::new(),
+                name: String::new(),
+            })
+            .collect();
+        let rects = |positions: &[ScreenRect]| -> Vec<(f64, f64, f64, f64)> {
+            positions
+                .iter()
+                .map(|p| (p.cx1, p.cy1, p.cx2, p.cy2))
+                .collect()
+        };
+
+        // flow wraps by text width, leaving ragged rows
+        let (positions, has_next) =
+            Legend::layout_rows(&items, 0, (0.0, 200.0), (0.0, 100.0), (10.0, 2.0), false);
+        assert!(!has_next);
+        assert_eq!(
+            rects(&positions),
+            vec![
+                (0.0, 0.0, 30.0, 10.0),
+                (40.0, 0.0, 120.0, 10.0),
+                (130.0, 0.0, 180.0, 10.0),
+                (0.0, 12.0, 40.0, 22.0),
+                (50.0, 12.0, 70.0, 22.0),
+            ]
+        );
+
+        // columns fit the widest item with its swatch: (200 + 10) / (90 + 10)
+        // makes 2 columns of (200 - 10) / 2
+        let (positions, has_next) =
+            Legend::layout_rows(&items, 0, (0.0, 200.0), (0.0, 100.0), (10.0, 2.0), true);
+        assert!(!has_next);
+        assert_eq!(
+            rects(&positions),
+            vec![
+                (0.0, 0.0, 95.0, 10.0),
+                (105.0, 0.0, 200.0, 10.0),
+                (0.0, 12.0, 95.0, 22.0),
+                (105.0, 12.0, 200.0, 22.0),
+                (0.0, 24.0, 95.0, 34.0),
+            ]
+        );
+
+        // pages keep working with the grid
+        let (positions, has_next) =
+            Legend::layout_rows(&items, 0, (0.0, 200.0), (0.0, 25.0), (10.0, 2.0), true);
+        assert!(has_next);
+        assert_eq!(positions.len(), 4);
+        let (positions, has_next) =
+            Legend::layout_rows(&items, 4, (0.0, 200.0), (0.0, 25.0), (10.0, 2.0), true);
+        assert!(!has_next);
+        assert_eq!(rects(&positions), vec![(0.0, 0.0, 95.0, 10.0)]);
+    }
+    #[test]
+    fn test_layout_column() {
+        let items: Vec<LegendItem> = [30.0, 80.0, 50.0, 40.0]
+            .iter()
+            .map(|&width| LegendItem {
+                width,
+                height: 10.0,
+                color: String::new(),
+                name: String::new(),
+            })
+            .collect();
+        let (positions, has_next) =
+            Legend::layout_column(&items, 0, (0.0, 60.0), (0.0, 35.0), 2.0, false, false);
+        assert!(has_next);
+        let rects: Vec<(f64, f64, f64, f64)> = positions
+            .iter()
+            .map(|p| (p.cx1, p.cy1, p.cx2, p.cy2))
+            .collect();
+        // one per row, the wide one clipped to the strip
+        assert_eq!(
+            rects,
+            vec![
+                (0.0, 0.0, 30.0, 10.0),
+                (0.0, 12.0, 60.0, 22.0),
+                (0.0, 24.0, 50.0, 34.0),
+            ]
+        );
+
+        let (positions, has_next) =
+            Legend::layout_column(&items, 3, (0.0, 60.0), (0.0, 35.0), 2.0, false, true);
+        assert!(!has_next);
+        assert_eq!(positions.len(), 1);
+        assert_eq!((positions[0].cx1, positions[0].cx2), (20.0, 60.0));
+
+        let (positions, _) =
+            Legend::layout_column(&items, 0, (0.0, 60.0), (0.0, 35.0), 2.0, true, false);
+        assert!(positions.iter().all(|p| p.width() == 60.0));
+    }
+}
@@ -5,51 +5,188 @@
  *
  * Copyright (C) 2023, Nikita Almakov
  */
+#[cfg(feature = "wasm")]
 #[macro_use]
 mod debug;
-mod animate;
+pub mod animate;
+#[cfg(feature = "wasm")]
 mod camera;
+#[cfg(feature = "wasm")]
 mod controls;
-mod data_set;
+pub mod data_set;
+pub mod decimate;
+pub mod error;
+#[cfg(feature = "wasm")]
 mod events;
-mod grid;
+pub mod grid;
+#[cfg(feature = "wasm")]
 mod legend;
+#[cfg(feature = "wasm")]
 mod main_chart;
+#[cfg(feature = "wasm")]
 mod manager;
-mod params;
+pub mod params;
+pub mod pos;
+#[cfg(feature = "wasm")]
 mod preview;
-mod scale;
+pub mod render;
+pub mod scale;
+#[cfg(feature = "wasm")]
 mod screen;
+#[cfg(feature = "debug")]
+pub mod snapshot;
+#[cfg(feature = "wasm")]
 mod tooltip;
+#[cfg(feature = "wasm")]
 mod utils;
+#[cfg(feature = "wasm")]
 mod versioned;
+#[cfg(feature = "wasm")]
 use crate::manager::{get_or_create_manager_addr, ChartManager};
+#[cfg(feature = "wasm")]
 use std::pin::Pin;
 
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
 #[global_allocator]
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
+#[cfg(feature = "wasm")]
 fn get_pinned_manager() -> Pin<Box<ChartManager>> {
     Box::into_pin(unsafe { Box::from_raw(get_or_create_manager_addr() as *mut ChartManager) })
 }
+#[cfg(feature = "wasm")]
 fn destruct_pinned_manager(manager: Pin<Box<ChartManager>>) {
     Box::into_raw(unsafe { Pin::into_inner_unchecked(manager) });
 }
 
+#[cfg(feature = "wasm")]
 #[wasm_bindgen(js_name = createMain)]
-pub fn create_main(raw_params: JsValue, raw_config: JsValue) -> Result<String, String> {
+pub fn create_main(raw_params: JsValue, raw_config: JsValue) -> Result<String, JsValue> {
     let mut pinned_manager = get_pinned_manager();
     let result = pinned_manager.as_mut().create_main(raw_params, raw_config);
     destruct_pinned_manager(pinned_manager);
-    result
+    result.map_err(|e| e.to_js_value())
 }
 
+#[cfg(feature = "wasm")]
 #[wasm_bindgen(js_name = destroyMain)]
-pub fn destroy_main(chart_id: JsValue) -> Result<(), String> {
+pub fn destroy_main(chart_id: JsValue) -> Result<(), JsValue> {
     let mut pinned_manager = get_pinned_manager();
     let result = pinned_manager.as_mut().destroy_main(chart_id);
     destruct_pinned_manager(pinned_manager);
+    result.map_err(|e| e.to_js_value())
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = destroyAll)]
+pub fn destroy_all() {
+    let mut pinned_manager = get_pinned_manager();
+    pinned_manager.as_mut().destroy_all();
+    destruct_pinned_manager(pinned_manager);
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = getChartIds)]
+pub fn get_chart_ids() -> js_sys::Array {
+    let pinned_manager = get_pinned_manager();
+    let result = pinned_manager.get_chart_ids();
+    destruct_pinned_manager(pinned_manager);
     result
 }
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = exportImage)]
+pub fn export_image(chart_id: JsValue, scale: Option<f64>) -> Result<String, JsValue> {
+    let pinned_manager = get_pinned_manager();
+    let result = pinned_manager.export_image(chart_id, scale.unwrap_or(1.0));
+    destruct_pinned_manager(pinned_manager);
+    result.map_err(|e| e.to_js_value())
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = exportSvg)]
+pub fn export_svg(chart_id: JsValue) -> Result<String, JsValue> {
+    let pinned_manager = get_pinned_manager();
+    let result = pinned_manager.export_svg(chart_id);
+    destruct_pinned_manager(pinned_manager);
+    result.map_err(|e| e.to_js_value())
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = nearestPointAt)]
+pub fn nearest_point_at(chart_id: JsValue, css_x: f64, css_y: f64) -> Result<JsValue, JsValue> {
+    let pinned_manager = get_pinned_manager();
+    let result = pinned_manager.nearest_point_at(chart_id, css_x, css_y);
+    destruct_pinned_manager(pinned_manager);
+    result.map_err(|e| e.to_js_value())
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = setConnectGaps)]
+pub fn set_connect_gaps(chart_id: JsValue, connect_gaps: bool) -> Result<(), JsValue> {
+    let pinned_manager = get_pinned_manager();
+    let result = pinned_manager.set_connect_gaps(chart_id, connect_gaps);
+    destruct_pinned_manager(pinned_manager);
+    result.map_err(|e| e.to_js_value())
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = setSeriesVisibility)]
+pub fn set_series_visibility(
+    chart_id: JsValue,
+    name: String,
+    visible: bool,
+) -> Result<(), JsValue> {
+    let pinned_manager = get_pinned_manager();
+    let result = pinned_manager.set_series_visibility(chart_id, name, visible);
+    destruct_pinned_manager(pinned_manager);
+    result.map_err(|e| e.to_js_value())
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = getVisibleSeries)]
+pub fn get_visible_series(chart_id: JsValue) -> Result<js_sys::Array, JsValue> {
+    let pinned_manager = get_pinned_manager();
+    let result = pinned_manager.get_visible_series(chart_id);
+    destruct_pinned_manager(pinned_manager);
+    result.map_err(|e| e.to_js_value())
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = updateData)]
+pub fn update_data(chart_id: JsValue, raw_params: JsValue) -> Result<(), JsValue> {
+    let pinned_manager = get_pinned_manager();
+    let result = pinned_manager.update_data(chart_id, raw_params);
+    destruct_pinned_manager(pinned_manager);
+    result.map_err(|e| e.to_js_value())
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn refresh(chart_id: JsValue) -> Result<(), JsValue> {
+    let pinned_manager = get_pinned_manager();
+    let result = pinned_manager.refresh(chart_id);
+    destruct_pinned_manager(pinned_manager);
+    result.map_err(|e| e.to_js_value())
+}
+
+#[cfg(feature = "debug")]
+#[wasm_bindgen(js_name = debugSnapshot)]
+pub fn debug_snapshot(chart_id: JsValue) -> Result<String, JsValue> {
+    let pinned_manager = get_pinned_manager();
+    let result = pinned_manager.debug_snapshot(chart_id);
+    destruct_pinned_manager(pinned_manager);
+    result.map_err(|e| e.to_js_value())
+}
+
+#[cfg(feature = "debug")]
+#[wasm_bindgen(js_name = debugSnapshotDiff)]
+pub fn debug_snapshot_diff(chart_id: JsValue) -> Result<js_sys::Array, JsValue> {
+    let pinned_manager = get_pinned_manager();
+    let result = pinned_manager.debug_snapshot_diff(chart_id);
+    destruct_pinned_manager(pinned_manager);
+    result.map_err(|e| e.to_js_value())
+}
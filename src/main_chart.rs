@@ -9,13 +9,22 @@ use crate::animate::ANIMATED_NUMBERS_COUNT;
 use crate::camera::Camera;
 use crate::controls::ControlEvent;
 use crate::controls::{MouseControls, TouchControls, WatchControls};
-use crate::events::JsEventListener;
+use crate::decimate::DECIMATION_REFINEMENTS_PENDING;
+use crate::error::ChartError;
+use crate::events::{JsEventListener, JsResizeObserver};
 use crate::legend::Legend;
-use crate::params::{ChartConfig, ChartParams, ClientCaps, Content};
+use crate::params::{
+    ChartConfig, ChartMode, ChartParams, ClientCaps, Content, DataType, LegendPosition, PanAxis,
+    VerboseFormat,
+};
 use crate::preview::Preview;
+use crate::render::{Renderer, SvgRenderer};
 use crate::scale::Scale;
 use crate::screen::{CoordSpaceHandle, Padding, Screen, ScreenArea, ScreenPos, Size};
+#[cfg(feature = "debug")]
+use crate::snapshot::ChartSnapshot;
 use crate::tooltip::Tooltip;
+use crate::utils::is_click;
 use std::cell::RefCell;
 use std::marker::PhantomPinned;
 use std::pin::Pin;
@@ -27,10 +36,44 @@ use wasm_bindgen::prelude::*;
 const CSS_DISABLE_DEFAULT_LONG_TOUCH: &'static str =
     "-webkit-touch-callout: none !important; -webkit-user-select: none !important";
 const CSS_DISABLE_TOUCH_GESTURES: &'static str = "touch-action: none";
+const CSS_ALLOW_VERTICAL_TOUCH_PAN: &str = "touch-action: pan-y";
+const CSS_ALLOW_HORIZONTAL_TOUCH_PAN: &str = "touch-action: pan-x";
+// canvases are reallocated once resizing settles, not on every event
+const RESIZE_DEBOUNCE_US: f64 = 100_000.0;
+// everything the content canvas depends on, apart from animations (those
+// redraw it anyway) and config or data changes (those drop the key)
+#[derive(PartialEq)]
+pub struct ContentLayerKey {
+    camera_window: [f64; 4],
+    preview_window: Option<[f64; 4]>,
+    alphas: Vec<f64>,
+    hover_preview: Option<(usize, f64)>,
+    selected_coords: Option<(f64, f64)>,
+    coarse: bool,
+}
+
 pub trait DrawChart {
     fn on_control_event(&mut self, event: &ControlEvent, time_us: f64);
     fn on_resize(&mut self);
     fn draw(&mut self, time_us: f64);
+    fn export_image(&mut self, scale: f64, time_us: f64) -> Result<String, String>;
+    fn export_svg(&mut self, time_us: f64) -> String;
+    fn nearest_point_at(&mut self, pos: &ScreenPos, time_us: f64) -> JsValue;
+    fn set_connect_gaps(&mut self, connect_gaps: bool);
+    fn set_series_visibility(
+        &mut self,
+        name: &str,
+        visible: bool,
+        time_us: f64,
+    ) -> Result<(), String>;
+    fn get_visible_series(&self) -> js_sys::Array;
+    fn update_data(&mut self, raw_params: &JsValue, time_us: f64) -> Result<(), ChartError>;
+    fn refresh(&mut self, time_us: f64);
+    #[cfg(feature = "debug")]
+    fn debug_snapshot(&mut self, time_us: f64) -> String;
+    // changes since the last debug_snapshot
+    #[cfg(feature = "debug")]
+    fn debug_snapshot_diff(&mut self, time_us: f64) -> Result<Vec<String>, String>;
 }
 pub struct MainChart<T>
 where
@@ -43,11 +86,18 @@ where
     pub content_screen: Rc<Screen>,
     pub control_screen: Rc<Screen>,
 
-    pub preview: Preview<T>,
+    pub preview: Option<Preview<T>>,
     pub camera: Camera<T>,
 
     pub legend: Legend,
     pub dirty: bool,
+    // the content canvas is kept as is while the key matches, e.g. when only
+    // the tooltip follows the pointer
+    pub content_layer_key: Option<ContentLayerKey>,
+    // the top of it is reserved for the title
+    content_padding: Padding,
+    on_ready: Option<js_sys::Function>,
+    first_draw_done: bool,
 
     control_watcher: Rc<RefCell<Box<dyn WatchControls>>>,
     touch_device: bool,
@@ -55,8 +105,12 @@ where
     pointer_move: Option<JsEventListener>,
     pointer_out: Option<JsEventListener>,
     pointer_up: Option<JsEventListener>,
+    resize_observer: Option<JsResizeObserver>,
+    resize_settle_time_us: Option<f64>,
     animation_frame_requested: bool,
     request_animation_frame_closure: Option<Closure<dyn Fn(JsValue)>>,
+    #[cfg(feature = "debug")]
+    debug_baseline: Option<ChartSnapshot>,
     _pin: PhantomPinned,
 }
 
@@ -66,12 +120,13 @@ where
 {
     pub fn new(
         mut params: ChartParams,
-        config: ChartConfig,
+        mut config: ChartConfig,
         client_caps: Rc<RefCell<ClientCaps>>,
         main_scale: T,
         preview_scale: T,
         touch_device: bool,
     ) -> Result<Pin<Box<Self>>, String> {
+        config.sync_animation(client_caps.borrow().prefers_reduced_motion);
         let config = Rc::new(RefCell::new(config));
         let conf = config.borrow();
         let content_screen = Rc::new(Screen::new(
@@ -87,55 +142,56 @@ where
             format!(
                 "display: block; width: 100%; height: 100%; position: absolute; left: 0; top: 0; {}; {}",
                 CSS_DISABLE_DEFAULT_LONG_TOUCH,
-                CSS_DISABLE_TOUCH_GESTURES,
+                // the browser keeps scrolling the page along the locked axis
+                match conf.pan_axis {
+                    PanAxis::X => CSS_ALLOW_VERTICAL_TOUCH_PAN,
+                    PanAxis::Y => CSS_ALLOW_HORIZONTAL_TOUCH_PAN,
+                    PanAxis::Both => CSS_DISABLE_TOUCH_GESTURES,
+                },
             )
             .as_str(),
         )?);
 
-        let content_padding = Padding::new([
-            Size::Pct(0.0),
-            Size::Pct(0.0),
-            Size::Pct(conf.layout_preview_height + conf.layout_legend_height),
-            Size::Pct(0.0),
-        ]);
-        let preview_padding = Padding::new([
-            Size::Pct(conf.layout_content_height),
-            Size::Pct(0.0),
-            Size::Pct(conf.layout_legend_height),
-            Size::Pct(0.0),
-        ]);
-        let legend_padding = Padding::new([
-            Size::Pct(conf.layout_content_height + conf.layout_preview_height),
-            Size::Pct(0.0),
-            Size::Pct(0.0),
-            Size::Pct(0.0),
-        ]);
+        let content_padding = Padding::new(get_content_padding(&conf, &params.content));
+        let (preview_padding, legend_padding) = get_preview_n_legend_paddings(&conf);
+        let preview_padding = Padding::new(preview_padding);
+        let legend_padding = Padding::new(legend_padding);
 
+        let mut tooltip = Tooltip::new(Rc::clone(&config));
+        tooltip.on_tooltip = params.on_tooltip.take();
         let camera = Camera::new(
             Rc::clone(&config),
             Rc::clone(&client_caps),
             ScreenArea::new(Rc::clone(&content_screen), content_padding.clone()),
-            ScreenArea::new(Rc::clone(&control_screen), content_padding),
+            ScreenArea::new(Rc::clone(&control_screen), content_padding.clone()),
             main_scale,
-            Tooltip::new(Rc::clone(&config)),
+            tooltip,
             &mut params.content,
         );
 
-        let preview = Preview::new(
-            Rc::clone(&config),
-            Rc::clone(&client_caps),
-            ScreenArea::new(Rc::clone(&content_screen), preview_padding.clone())
-                .sub_area(camera.content_padding.clone()),
-            ScreenArea::new(Rc::clone(&control_screen), preview_padding)
-                .sub_area(camera.content_padding.clone()),
-            preview_scale,
-            &mut params.content,
-        );
+        let preview = if conf.show_preview {
+            Some(Preview::new(
+                Rc::clone(&config),
+                Rc::clone(&client_caps),
+                ScreenArea::new(Rc::clone(&content_screen), preview_padding.clone())
+                    .sub_area(camera.content_padding.clone()),
+                ScreenArea::new(Rc::clone(&control_screen), preview_padding)
+                    .sub_area(camera.content_padding.clone()),
+                preview_scale,
+                &mut params.content,
+            ))
+        } else {
+            None
+        };
 
+        // a legend on a side is a strip of its own, not aligned to the plot
+        let legend_screen_area = ScreenArea::new(Rc::clone(&control_screen), legend_padding);
         let legend = Legend::new(
             Rc::clone(&config),
-            ScreenArea::new(Rc::clone(&control_screen), legend_padding)
-                .sub_area(camera.content_padding.clone()),
+            match conf.legend_position {
+                LegendPosition::Left | LegendPosition::Right => legend_screen_area,
+                _ => legend_screen_area.sub_area(camera.content_padding.clone()),
+            },
         );
 
         let mut chart = Box::pin(Self {
@@ -149,18 +205,26 @@ where
             camera,
             legend,
             dirty: true,
+            content_layer_key: None,
+            content_padding,
+            on_ready: params.on_ready,
+            first_draw_done: false,
             control_watcher: Rc::new(RefCell::new(if touch_device {
-                Box::new(TouchControls::new())
+                Box::new(TouchControls::new(conf.drag_threshold))
             } else {
-                Box::new(MouseControls::new())
+                Box::new(MouseControls::new(conf.drag_threshold))
             })),
             touch_device,
             pointer_move: None,
             pointer_out: None,
             pointer_down: None,
             pointer_up: None,
+            resize_observer: None,
+            resize_settle_time_us: None,
             animation_frame_requested: false,
             request_animation_frame_closure: None,
+            #[cfg(feature = "debug")]
+            debug_baseline: None,
             _pin: PhantomPinned,
         });
         Self::ensure_listeners_are_set_up(chart.as_mut());
@@ -256,6 +320,21 @@ where
                 Box::into_raw(unsafe { Pin::into_inner_unchecked(obj) });
             }),
         ));
+        if let Ok(Some(container)) = web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap()
+            .query_selector(chart.container_selector.as_str())
+        {
+            chart.resize_observer = JsResizeObserver::new(
+                &container,
+                Box::new(move |_: JsValue| {
+                    // only borrowed, the chart stays owned by its pinned box
+                    let chart = unsafe { &mut *(chart_ptr as *mut Self) };
+                    chart.on_resize();
+                }),
+            );
+        }
         if chart.request_animation_frame_closure.is_none() {
             let closure = Closure::new(Box::new(move |time_ms: JsValue| {
                 let time_us = time_ms.as_f64().unwrap() * 1000.0;
@@ -285,11 +364,39 @@ where
         }
     }
 
+    fn sync_animation(&self) {
+        self.config
+            .borrow_mut()
+            .sync_animation(self.client_caps.borrow().prefers_reduced_motion);
+    }
+
+    fn preview_pointer_down(&self) -> bool {
+        self.preview
+            .as_ref()
+            .is_some_and(|preview| preview.pointer_down.is_some())
+    }
+    fn preview_contains_pos(&mut self, pos: &ScreenPos) -> bool {
+        self.preview.as_mut().is_some_and(|preview| {
+            preview
+                .control_coord_space
+                .screen_area
+                .get_handle()
+                .contains_pos(pos)
+        })
+    }
+    fn grip_held(&self) -> bool {
+        self.preview
+            .as_ref()
+            .is_some_and(|preview| preview.grip_hold_coord_offset.is_some())
+    }
     fn drag_camera(&mut self, time_us: f64) {
+        let Some(preview) = self.preview.as_mut() else {
+            return;
+        };
         if let (Some(pos), Some(grip_hold_coord_offset)) =
-            (&self.preview.pointer, self.preview.grip_hold_coord_offset)
+            (&preview.pointer, preview.grip_hold_coord_offset)
         {
-            let preview_coord_space = self.preview.control_coord_space.get_handle(time_us);
+            let preview_coord_space = preview.control_coord_space.get_handle(time_us);
             if let Some(coord) = preview_coord_space.get_coord(pos) {
                 self.camera
                     .move_to(&mut self.content, grip_hold_coord_offset + coord, time_us);
@@ -303,6 +410,10 @@ where
         pos1: &ScreenPos,
         pos2: &ScreenPos,
     ) -> Option<(f64, f64)> {
+        // a jittery tap shouldn't select, let alone zoom into, a sliver
+        if is_click(pos1, pos2, self.config.borrow().drag_threshold) {
+            return None;
+        }
         let screen_area_handle = coord_space_handle.screen_area_handle.as_ref();
         let (pos_left, pos_right) = if pos1.0 < pos2.0 {
             (pos1, pos2)
@@ -332,21 +443,167 @@ where
                 selected_coords = self.calc_selected_coords(handle, down_pos, pos);
             }
         }
-        if self.preview.pointer_down.is_some() {
-            if let (Some(down_pos), Some(pos)) = (&self.preview.pointer_down, &self.preview.pointer)
+        if let Some(preview) = self.preview.as_mut() {
+            if let (Some(down_pos), Some(pos)) =
+                (preview.pointer_down.clone(), preview.pointer.clone())
             {
-                let handle = self.preview.coord_space.get_handle(time_us);
-                selected_coords = self.calc_selected_coords(handle, down_pos, pos);
+                let handle = preview.coord_space.get_handle(time_us);
+                selected_coords = self.calc_selected_coords(handle, &down_pos, &pos);
             }
         }
         selected_coords
     }
+    fn get_content_layer_key(&mut self, time_us: f64) -> ContentLayerKey {
+        // sharpens back once the pointer is released
+        self.camera.coarse = self.config.borrow().adaptive_quality
+            && (self.camera.pointer_down.is_some()
+                || self.camera.pinch_coords.is_some()
+                || self.preview_pointer_down());
+        let handle = self.camera.coord_space.get_handle(time_us);
+        let camera_window = [
+            handle.scale.get_coord_min(),
+            handle.scale.get_coord_max(),
+            handle.scale.get_value_min(),
+            handle.scale.get_value_max(),
+        ];
+        let preview_window = self.preview.as_mut().map(|preview| {
+            let handle = preview.coord_space.get_handle(time_us);
+            [
+                handle.scale.get_coord_min(),
+                handle.scale.get_coord_max(),
+                handle.scale.get_value_min(),
+                handle.scale.get_value_max(),
+            ]
+        });
+        ContentLayerKey {
+            camera_window,
+            preview_window,
+            alphas: self
+                .content
+                .data_sets
+                .iter_mut()
+                .map(|data_set| data_set.alpha.get_value(time_us))
+                .collect(),
+            hover_preview: self.content.get_hover_preview(time_us),
+            selected_coords: if !self.grip_held() {
+                self.get_selected_coords(time_us)
+            } else {
+                None
+            },
+            coarse: self.camera.coarse,
+        }
+    }
+    fn draw_content_layer(&mut self, time_us: f64) {
+        let animated_numbers_count = ANIMATED_NUMBERS_COUNT.load(Ordering::Relaxed);
+        let key = self.get_content_layer_key(time_us);
+        if self.content_layer_key.as_ref() == Some(&key) {
+            return;
+        }
+        self.content_screen.clear();
+        self.draw_title(time_us);
+        if self.content.data_sets.is_empty() {
+            self.draw_no_data(time_us);
+            self.content_layer_key = Some(key);
+            return;
+        }
+        self.camera.draw(&mut self.content, time_us);
+        if let Some(preview) = self.preview.as_mut() {
+            preview.draw(&mut self.content, time_us);
+        }
+        if !self.grip_held() {
+            self.draw_selected_area(time_us);
+        }
+        let animated = ANIMATED_NUMBERS_COUNT.load(Ordering::Relaxed) > animated_numbers_count
+            || DECIMATION_REFINEMENTS_PENDING.load(Ordering::Relaxed) > 0;
+        self.content_layer_key = if animated { None } else { Some(key) };
+    }
+    // every resize event pushes the sync back, so a drag syncs once it stops
+    fn postpone_resize(resize_settle_time_us: &mut Option<f64>, time_us: f64) {
+        *resize_settle_time_us = Some(time_us + RESIZE_DEBOUNCE_US);
+    }
+    // whether a pending resize has settled, None when there is none
+    fn take_settled_resize(resize_settle_time_us: &mut Option<f64>, time_us: f64) -> Option<bool> {
+        let settled = time_us >= (*resize_settle_time_us)?;
+        if settled {
+            *resize_settle_time_us = None;
+        }
+        Some(settled)
+    }
+    // the callback goes out on the first completed draw only
+    fn take_on_ready<F>(first_draw_done: &mut bool, on_ready: &mut Option<F>) -> Option<F> {
+        if *first_draw_done {
+            return None;
+        }
+        *first_draw_done = true;
+        on_ready.take()
+    }
+    // deferred, so the callback is free to call back into the chart
+    fn notify_ready(&mut self) {
+        if let Some(on_ready) = Self::take_on_ready(&mut self.first_draw_done, &mut self.on_ready) {
+            web_sys::window()
+                .unwrap()
+                .set_timeout_with_callback_and_timeout_and_arguments_1(
+                    &on_ready,
+                    0,
+                    &JsValue::from_str(self.container_selector.as_str()),
+                )
+                .unwrap();
+        }
+    }
+    fn draw_title(&mut self, time_us: f64) {
+        let title = match self.content.name.as_ref() {
+            _ if self.config.borrow().mode == ChartMode::Sparkline => return,
+            Some(title) => title,
+            None => return,
+        };
+        let coord_space_handle = self.camera.coord_space.get_handle(time_us);
+        let screen_area_handle = coord_space_handle.screen_area_handle.as_ref();
+        let crc = screen_area_handle.crc.as_ref();
+        let conf = self.config.borrow();
+        let font_height = conf.title_font_size.to_cpx_height(screen_area_handle);
+        crc.set_text_align("center");
+        crc.set_text_baseline("middle");
+        crc.set_font(format!("{}px {}", font_height, conf.font_standard.as_str()).as_str());
+        let v = conf.title_color;
+        crc.set_fill_style(format!("rgb({}, {}, {})", v.0, v.1, v.2).as_str());
+        crc.fill_text(
+            title.as_str(),
+            (screen_area_handle.left_cx() + screen_area_handle.right_cx()) * 0.5,
+            screen_area_handle.top_cy()
+                - get_title_padding(&conf, &self.content).to_cpx_height(screen_area_handle) * 0.5,
+        );
+    }
+    fn draw_no_data(&mut self, time_us: f64) {
+        let coord_space_handle = self.camera.coord_space.get_handle(time_us);
+        let screen_area_handle = coord_space_handle.screen_area_handle.as_ref();
+        let crc = screen_area_handle.crc.as_ref();
+        let conf = self.config.borrow();
+        crc.set_text_align("center");
+        crc.set_text_baseline("middle");
+        crc.set_font(
+            format!(
+                "{}px {}",
+                conf.font_size_large.to_cpx_height(screen_area_handle),
+                conf.font_standard.as_str()
+            )
+            .as_str(),
+        );
+        let v = conf.color_tooltip_font;
+        crc.set_fill_style(format!("rgba({}, {}, {}, {})", v.0, v.1, v.2, v.3).as_str());
+        crc.fill_text(
+            conf.hint_no_data.as_str(),
+            (screen_area_handle.left_cx() + screen_area_handle.right_cx()) * 0.5,
+            (screen_area_handle.top_cy() + screen_area_handle.bottom_cy()) * 0.5,
+        );
+    }
     fn draw_selected_area(&mut self, time_us: f64) {
         let selected_coords = self.get_selected_coords(time_us);
         if let Some((left_coord, right_coord)) = selected_coords {
-            for coord_space in
-                [&mut self.camera.coord_space, &mut self.preview.coord_space].into_iter()
-            {
+            for coord_space in std::iter::once(&mut self.camera.coord_space).chain(
+                self.preview
+                    .as_mut()
+                    .map(|preview| &mut preview.coord_space),
+            ) {
                 let coord_space_handle = coord_space.get_handle(time_us);
                 let coord_min = coord_space_handle.scale.get_coord_min();
                 let coord_max = coord_space_handle.scale.get_coord_max();
@@ -361,8 +618,7 @@ where
                 let crc = screen_area_handle.crc.as_ref();
                 let conf = self.config.borrow();
                 let v = conf.color_camera_grip;
-                let color =
-                    JsValue::from_str(format!("rgba({}, {}, {}, {})", v.0, v.1, v.2, v.3).as_str());
+                let color = format!("rgba({}, {}, {}, {})", v.0, v.1, v.2, v.3);
 
                 let left_x = coord_space_handle.get_cx(left_coord);
                 let right_x = coord_space_handle.get_cx(right_coord);
@@ -370,9 +626,150 @@ where
                 let bottom_y = screen_area_handle.bottom_cy();
                 crc.set_fill_style(&color);
                 crc.fill_rect(left_x, top_y, right_x - left_x, bottom_y - top_y);
+
+                // time spans read better as durations than as two timestamps
+                // datetime coords are in seconds, dates in ms
+                let ms_per_coord = match self.content.coord_type {
+                    DataType::Number => None,
+                    DataType::Date => Some(1.0),
+                    DataType::DateTime { .. } => Some(1000.0),
+                };
+                if let Some(ms_per_coord) = ms_per_coord.filter(|_| conf.selection_duration) {
+                    let c_font_size = conf.font_size_small.to_cpx_height(screen_area_handle);
+                    let v = conf.color_tick;
+                    crc.set_fill_style(format!("rgb({}, {}, {})", v.0, v.1, v.2).as_str());
+                    crc.set_font(
+                        format!("{}px {}", c_font_size, conf.font_standard.as_str()).as_str(),
+                    );
+                    crc.set_text_align("center");
+                    crc.set_text_baseline("top");
+                    crc.fill_text(
+                        VerboseFormat::Duration
+                            .format_values(
+                                Some((right_coord - left_coord) * ms_per_coord).into_iter(),
+                                |x| x,
+                                0.0,
+                                0.0,
+                            )
+                            .pop()
+                            .unwrap()
+                            .as_str(),
+                        (left_x + right_x) * 0.5,
+                        top_y + c_font_size * 0.5,
+                    );
+                }
+            }
+        }
+    }
+    #[cfg(feature = "debug")]
+    fn capture_snapshot(&mut self, time_us: f64) -> ChartSnapshot {
+        let coord_space = &self.camera.coord_space;
+        let (coord_min, coord_max, value_min, value_max) = (
+            coord_space.coord_min.get_end_value(),
+            coord_space.coord_max.get_end_value(),
+            coord_space.value_min.get_end_value(),
+            coord_space.value_max.get_end_value(),
+        );
+        let coord_short_verbose_len = self.content.coord_short_verbose_len as f64;
+        ChartSnapshot {
+            coord_min,
+            coord_max,
+            value_min,
+            value_max,
+            zoomed_in: self.camera.zoomed_in,
+            coord_ticks: self
+                .camera
+                .get_coord_ticks(coord_short_verbose_len, time_us)
+                .iter()
+                .map(|tick| tick.value)
+                .collect(),
+            value_ticks: self
+                .camera
+                .get_value_ticks(time_us)
+                .iter()
+                .map(|tick| tick.value)
+                .collect(),
+            visible_series: self
+                .content
+                .data_sets
+                .iter()
+                .filter(|data_set| data_set.alpha.get_end_value() > 0.0)
+                .map(|data_set| data_set.name.clone())
+                .collect(),
+            tooltip_visible: self.camera.tooltip.visible,
+            tooltip_pinned: !self.camera.pointer_clicked.is_empty(),
+            matched_coord: self.camera.tooltip.matched_coord,
+            legend_offset: self.legend.offset,
+            legend_items_shown: self.legend.positions.len(),
+            legend_has_next: self.legend.has_next,
+        }
+    }
+    // instead of starting a selection, the tooltip follows the held finger
+    fn detect_touch_hold(&mut self, time_us: f64) {
+        if !self.touch_device || self.camera.touch_hold {
+            return;
+        }
+        let held = Self::is_touch_held(
+            self.camera.pointer_down_time_us,
+            self.camera.pointer_down.as_ref(),
+            self.camera.pointer.as_ref(),
+            time_us,
+            &self.config.borrow(),
+        );
+        if held {
+            self.camera.touch_hold = true;
+            self.camera.pointer_down = None;
+            self.camera.pointer_down_time_us = None;
+        }
+    }
+    // a finger kept down in place for longer than a long press
+    fn is_touch_held(
+        pointer_down_time_us: Option<f64>,
+        pointer_down: Option<&ScreenPos>,
+        pointer: Option<&ScreenPos>,
+        time_us: f64,
+        conf: &ChartConfig,
+    ) -> bool {
+        match (pointer_down_time_us, pointer_down, pointer) {
+            (Some(pointer_down_time_us), Some(pointer_down), Some(pointer)) => {
+                time_us - pointer_down_time_us > conf.us_long_press
+                    && is_click(pointer_down, pointer, conf.drag_threshold)
             }
+            _ => false,
         }
     }
+    fn end_touch_hold(&mut self) {
+        self.camera.touch_hold = false;
+        self.camera.pointer = None;
+    }
+    // data sets of the new content are indexed already
+    fn content_replaced(&mut self, time_us: f64) {
+        self.content_layer_key = None;
+        *self.content_padding.get_mut() = get_content_padding(&self.config.borrow(), &self.content);
+        self.content.recompute_global_bounds();
+        let window = if self.camera.zoomed_in {
+            Some((
+                self.camera.control_coord_space.coord_min.get_end_value(),
+                self.camera.control_coord_space.coord_max.get_end_value(),
+            ))
+        } else {
+            None
+        };
+        let window = self.config.borrow().update_zoom_policy.get_window(
+            window,
+            self.content.global_coord_min,
+            self.content.global_coord_max,
+        );
+        self.camera
+            .content_replaced(&mut self.content, window, time_us);
+        if let Some(preview) = self.preview.as_mut() {
+            preview.content_replaced(&mut self.content, time_us);
+        }
+        self.legend.last_canvas_height = 0.0; // forcing resize
+                                              // a double click can't span series that may have been reordered
+        self.legend.last_click = None;
+        self.request_animation_frame();
+    }
     fn camera_pointer_up(&mut self, time_us: f64) {
         if let Some((left_coord, right_coord)) = self.get_selected_coords(time_us) {
             self.camera
@@ -382,16 +779,17 @@ where
         self.camera.pointer_down_time_us = None;
     }
     fn preview_pointer_up(&mut self, time_us: f64) {
-        if self.preview.grip_hold_coord_offset.is_none() {
+        if !self.grip_held() {
             if let Some((left_coord, right_coord)) = self.get_selected_coords(time_us) {
                 self.camera
                     .zoom_by_coords(&mut self.content, left_coord, right_coord, time_us);
             }
-        } else {
-            self.preview.grip_hold_coord_offset = None;
         }
-        self.preview.pointer_down = None;
-        self.preview.pointer_down_time_us = None;
+        if let Some(preview) = self.preview.as_mut() {
+            preview.grip_hold_coord_offset = None;
+            preview.pointer_down = None;
+            preview.pointer_down_time_us = None;
+        }
     }
     fn legend_pointer_up(&mut self, time_us: f64) {
         self.legend.pointer_down = None;
@@ -403,11 +801,14 @@ where
         let camera_coord_max = camera_space.scale.get_coord_max();
         let grip_coord = (camera_coord_min + camera_coord_max) * 0.5;
 
-        if let Some(pos) = self.preview.pointer.clone() {
-            let preview_space = self.preview.control_coord_space.get_handle(time_us);
+        let Some(preview) = self.preview.as_mut() else {
+            return;
+        };
+        if let Some(pos) = preview.pointer.clone() {
+            let preview_space = preview.control_coord_space.get_handle(time_us);
             if let Some(coord) = preview_space.get_coord(&pos) {
                 if coord <= camera_coord_max && coord >= camera_coord_min {
-                    self.preview.grip_hold_coord_offset = Some(grip_coord - coord);
+                    preview.grip_hold_coord_offset = Some(grip_coord - coord);
                 }
             }
         }
@@ -418,6 +819,11 @@ where
     T: Scale,
 {
     fn on_control_event(&mut self, event: &ControlEvent, time_us: f64) {
+        self.sync_animation();
+        // sparklines are static
+        if self.config.borrow().mode == ChartMode::Sparkline {
+            return;
+        }
         match event {
             ControlEvent::PointerDown { pos } => {
                 let hit_camera = self
@@ -428,19 +834,16 @@ where
                     .contains_pos(pos);
                 if hit_camera {
                     self.camera.pointer = Some(pos.to_owned());
+                    self.camera.track_pointer_rest(pos, time_us);
                     self.camera.pointer_down = Some(pos.to_owned());
                     self.camera.pointer_down_time_us = Some(time_us);
                 } else {
-                    let hit_preview = self
-                        .preview
-                        .control_coord_space
-                        .screen_area
-                        .get_handle()
-                        .contains_pos(pos);
-                    if hit_preview {
-                        self.preview.pointer = Some(pos.to_owned());
-                        self.preview.pointer_down = Some(pos.to_owned());
-                        self.preview.pointer_down_time_us = Some(time_us);
+                    let hit_preview = self.preview_contains_pos(pos);
+                    let hit_preview = self.preview.as_mut().filter(|_| hit_preview);
+                    if let Some(preview) = hit_preview {
+                        preview.pointer = Some(pos.to_owned());
+                        preview.pointer_down = Some(pos.to_owned());
+                        preview.pointer_down_time_us = Some(time_us);
                         if self.camera.zoomed_in {
                             self.try_to_grab_camera_grip(time_us);
                         }
@@ -459,6 +862,14 @@ where
                 };
             }
             ControlEvent::PointerMoved { pos } => {
+                let down_pos = self.camera.pointer_down.as_ref().or(self
+                    .preview
+                    .as_ref()
+                    .and_then(|preview| preview.pointer_down.as_ref()));
+                let pos = &match down_pos {
+                    Some(down_pos) => self.config.borrow().pan_axis.lock_pos(pos, down_pos),
+                    None => pos.to_owned(),
+                };
                 let hit_camera = self
                     .camera
                     .control_coord_space
@@ -467,31 +878,30 @@ where
                     .contains_pos(pos);
                 if hit_camera {
                     self.camera.pointer = Some(pos.to_owned());
+                    self.camera.track_pointer_rest(pos, time_us);
                 } else {
                     if self.camera.pointer_down.is_some() {
                         self.camera_pointer_up(time_us);
                     }
                     self.camera.pointer = None;
 
-                    let hit_preview = self
-                        .preview
-                        .control_coord_space
-                        .screen_area
-                        .get_handle()
-                        .contains_pos(pos);
-
-                    if hit_preview {
-                        self.preview.pointer = Some(pos.to_owned());
-                        if self.preview.pointer_down.is_some()
-                            && self.preview.grip_hold_coord_offset.is_some()
+                    let hit_preview = self.preview_contains_pos(pos);
+                    let hit_preview = self.preview.as_mut().filter(|_| hit_preview);
+
+                    if let Some(preview) = hit_preview {
+                        preview.pointer = Some(pos.to_owned());
+                        if preview.pointer_down.is_some()
+                            && preview.grip_hold_coord_offset.is_some()
                         {
                             self.drag_camera(time_us);
                         }
                     } else {
-                        if self.preview.pointer_down.is_some() {
+                        if self.preview_pointer_down() {
                             self.preview_pointer_up(time_us);
                         }
-                        self.preview.pointer = None;
+                        if let Some(preview) = self.preview.as_mut() {
+                            preview.pointer = None;
+                        }
 
                         let hit_legend = self
                             .legend
@@ -510,7 +920,7 @@ where
                 }
             }
             ControlEvent::PointerUp | ControlEvent::PointerLeft => {
-                if self.preview.pointer_down.is_some() {
+                if self.preview_pointer_down() {
                     self.preview_pointer_up(time_us);
                 } else {
                     if self.camera.pointer_down.is_some() {
@@ -521,27 +931,31 @@ where
                         }
                     }
                 }
-                self.camera.pointer = None;
-                self.preview.pointer = None;
+                self.end_touch_hold();
+                if let Some(preview) = self.preview.as_mut() {
+                    preview.pointer = None;
+                }
                 self.legend.pointer = None;
             }
+            // releasing a held finger is no tap, so nothing gets pinned
+            ControlEvent::PointerClicked if self.camera.touch_hold => {
+                self.end_touch_hold();
+            }
             ControlEvent::PointerClicked => {
-                if self.preview.pointer_down.is_some() {
+                let preview_clicked = self
+                    .preview
+                    .as_mut()
+                    .filter(|preview| preview.pointer_down.is_some());
+                if let Some(preview) = preview_clicked {
+                    preview.pointer_down = None;
+                    preview.pointer_down_time_us = None;
+                    preview.grip_hold_coord_offset = None;
                     if self.camera.zoomed_in {
                         self.camera.zoom_out(&mut self.content, time_us);
                     }
-                    self.preview.pointer_down = None;
-                    self.preview.pointer_down_time_us = None;
-                    self.preview.grip_hold_coord_offset = None;
                 } else {
                     if self.camera.pointer_down.is_some() {
-                        if self.camera.pointer_clicked.is_some() {
-                            self.camera.pointer_clicked = None;
-                            self.camera.pointer_clicked_time_us = None;
-                        } else {
-                            self.camera.pointer_clicked = self.camera.pointer.clone();
-                            self.camera.pointer_clicked_time_us = Some(time_us);
-                        }
+                        self.camera.toggle_pin(&self.content, time_us);
                         self.camera.pointer_down = None;
                         self.camera.pointer_down_time_us = None;
                     } else {
@@ -553,8 +967,9 @@ where
                                     self.camera.control_coord_space.coord_max.get_end_value(),
                                     time_us,
                                 );
-                                self.preview
-                                    .update_by_content(&mut self.content, Some(time_us));
+                                if let Some(preview) = self.preview.as_mut() {
+                                    preview.update_by_content(&mut self.content, Some(time_us));
+                                }
                             }
                             self.legend.pointer_down = None;
                             self.legend.pointer_down_time_us = None;
@@ -563,7 +978,7 @@ where
                 }
             }
             ControlEvent::PinchStarted { pos1, pos2 } => {
-                if self.preview.pointer_down.is_some() {
+                if self.preview_pointer_down() {
                     self.preview_pointer_up(time_us);
                 } else {
                     if self.camera.pointer_down.is_some() {
@@ -574,8 +989,10 @@ where
                         }
                     }
                 }
-                self.camera.pointer = None;
-                self.preview.pointer = None;
+                self.end_touch_hold();
+                if let Some(preview) = self.preview.as_mut() {
+                    preview.pointer = None;
+                }
                 self.legend.pointer = None;
 
                 let camera_coord_space_handle = self.camera.control_coord_space.get_handle(time_us);
@@ -602,7 +1019,8 @@ where
                 }
             }
             ControlEvent::PinchUpdated { pos1, pos2 } => {
-                if pos1.0 == pos2.0 {
+                // pinches zoom along x only
+                if pos1.0 == pos2.0 || matches!(self.config.borrow().pan_axis, PanAxis::Y) {
                     return;
                 }
                 let camera_coord_space_handle = self.camera.control_coord_space.get_handle(time_us);
@@ -641,12 +1059,28 @@ where
     }
     fn draw(&mut self, time_us: f64) {
         ANIMATED_NUMBERS_COUNT.store(0, Ordering::Relaxed);
+        match Self::take_settled_resize(&mut self.resize_settle_time_us, time_us) {
+            Some(true) => {
+                self.content_screen.schedule_canvas_size_sync();
+                self.control_screen.schedule_canvas_size_sync();
+                self.content_layer_key = None;
+            }
+            Some(false) => self.request_animation_frame(),
+            None => {}
+        }
+        self.sync_animation();
         self.legend.on_long_press(&mut self.content, time_us);
-        self.content_screen.clear();
+        self.detect_touch_hold(time_us);
+        self.legend.update_hover_preview(&mut self.content, time_us);
+        self.draw_content_layer(time_us);
         self.control_screen.clear();
+        self.notify_ready();
+        // nothing to point at, zoom into or toggle
+        if self.content.data_sets.is_empty() {
+            return;
+        }
 
-        self.camera.draw(&mut self.content, time_us);
-        self.preview.draw(&mut self.content, time_us);
+        self.camera.draw_tooltips(&mut self.content, time_us);
         let grip = if self.camera.zoomed_in {
             let coord_min = self.camera.control_coord_space.coord_min.get_end_value();
             let coord_max = self.camera.control_coord_space.coord_max.get_end_value();
@@ -654,23 +1088,444 @@ where
         } else {
             None
         };
-        self.preview.draw_grip(grip, time_us);
-        if self.preview.grip_hold_coord_offset.is_none() {
-            self.draw_selected_area(time_us);
+        if let Some(preview) = self.preview.as_mut() {
+            preview.draw_grip(grip, time_us);
+            preview.draw_tooltip(&self.content, time_us);
         }
         self.legend.draw(&self.content);
 
+        let decimation_refinements_pending =
+            DECIMATION_REFINEMENTS_PENDING.swap(0, Ordering::Relaxed) > 0;
         if ANIMATED_NUMBERS_COUNT.load(Ordering::Relaxed) > 0
             || self.legend.pointer_down_time_us.is_some()
+            || (self.touch_device && self.camera.pointer_down_time_us.is_some())
+            || decimation_refinements_pending
+            || self.camera.is_tooltip_pending(time_us)
         {
             self.request_animation_frame();
         }
     }
+    fn export_image(&mut self, scale: f64, time_us: f64) -> Result<String, String> {
+        if !(scale.is_finite() && scale > 0.0) {
+            return Err(format!("invalid export scale: {}", scale));
+        }
+        self.content_screen.begin_offscreen(scale)?;
+        if let Err(e) = self.control_screen.begin_offscreen(scale) {
+            self.content_screen.end_offscreen();
+            return Err(e);
+        }
+        // legend items are measured in canvas pixels, so remeasure them at both scales
+        self.legend.items = None;
+        self.content_layer_key = None;
+        self.draw(time_us);
+        let content_canvas = self.content_screen.end_offscreen().unwrap();
+        let control_canvas = self.control_screen.end_offscreen().unwrap();
+        self.content_layer_key = None;
+        self.legend.items = None;
+        self.legend.last_canvas_height = 0.0; // forcing resize
+        self.request_animation_frame();
+
+        // content & controls live on two stacked canvases, so flatten them
+        let crc = content_canvas
+            .get_context("2d")
+            .unwrap()
+            .unwrap()
+            .dyn_into::<web_sys::CanvasRenderingContext2d>()
+            .unwrap();
+        crc.draw_image_with_html_canvas_element(&control_canvas, 0.0, 0.0)
+            .unwrap();
+        content_canvas
+            .to_data_url()
+            .map_err(|_| "failed to export image".to_string())
+    }
+    fn export_svg(&mut self, time_us: f64) -> String {
+        let renderer = Rc::new(SvgRenderer::new(self.config.borrow().font_width_coeff));
+        // content & controls are recorded into the same document, in drawing order
+        self.content_screen
+            .begin_recording(Rc::clone(&renderer) as Rc<dyn Renderer>);
+        self.control_screen
+            .begin_recording(Rc::clone(&renderer) as Rc<dyn Renderer>);
+        self.legend.items = None;
+        self.content_layer_key = None;
+        self.draw(time_us);
+        self.content_screen.end_recording();
+        self.control_screen.end_recording();
+        self.content_layer_key = None;
+        self.legend.items = None;
+        self.legend.last_canvas_height = 0.0; // forcing resize
+        self.request_animation_frame();
+
+        let state = self.content_screen.get_state();
+        renderer.to_svg(state.canvas_width, state.canvas_height)
+    }
+    fn nearest_point_at(&mut self, pos: &ScreenPos, time_us: f64) -> JsValue {
+        match self.camera.nearest_point_at(&self.content, pos, time_us) {
+            Some((data_set, data_point)) => {
+                let result = js_sys::Object::new();
+                for (key, value) in [
+                    ("name", JsValue::from_str(data_set.name.as_str())),
+                    ("coord", JsValue::from_f64(data_point.coord)),
+                    ("value", JsValue::from_f64(data_point.value)),
+                ] {
+                    js_sys::Reflect::set(&result, &JsValue::from_str(key), &value).unwrap();
+                }
+                result.into()
+            }
+            None => JsValue::NULL,
+        }
+    }
+    fn set_connect_gaps(&mut self, connect_gaps: bool) {
+        self.content.connect_gaps = connect_gaps;
+        self.content_layer_key = None;
+        self.request_animation_frame();
+    }
+    // rescales the views like a legend click does
+    fn set_series_visibility(
+        &mut self,
+        name: &str,
+        visible: bool,
+        time_us: f64,
+    ) -> Result<(), String> {
+        let animation = self.config.borrow().animation;
+        self.content
+            .set_data_set_visibility(name, visible, time_us, animation)?;
+        self.camera.zoom_by_coords(
+            &mut self.content,
+            self.camera.control_coord_space.coord_min.get_end_value(),
+            self.camera.control_coord_space.coord_max.get_end_value(),
+            time_us,
+        );
+        if let Some(preview) = self.preview.as_mut() {
+            preview.update_by_content(&mut self.content, Some(time_us));
+        }
+        self.request_animation_frame();
+        Ok(())
+    }
+    fn get_visible_series(&self) -> js_sys::Array {
+        self.content
+            .get_visible_data_set_names()
+            .into_iter()
+            .map(JsValue::from_str)
+            .collect()
+    }
+    fn update_data(&mut self, raw_params: &JsValue, time_us: f64) -> Result<(), ChartError> {
+        let conf = self.config.borrow();
+        let mut content = Content::from_raw(
+            raw_params,
+            self.content.coord_type,
+            self.content.value_type,
+            &conf,
+        )?;
+        content.sort_data_sets(&conf.sort_data_sets_by);
+        // data sets hidden via the legend stay hidden
+        for data_set in content.data_sets.iter_mut() {
+            let hidden = self.content.data_sets.iter().any(|old_data_set| {
+                old_data_set.name == data_set.name && old_data_set.alpha.get_end_value() == 0.0
+            });
+            if hidden {
+                data_set.alpha.set_value(0.0, None, conf.animation);
+            }
+        }
+        content.connect_gaps = self.content.connect_gaps;
+        drop(conf);
+
+        self.content = content;
+        self.camera.pointer_clicked.clear();
+        self.camera.pointer_clicked_time_us = None;
+        self.legend.items = None;
+        self.legend.offset = 0;
+        self.content_replaced(time_us);
+        Ok(())
+    }
+    fn refresh(&mut self, time_us: f64) {
+        let decimation_target_points = self.config.borrow().decimation_target_points;
+        for data_set in self.content.data_sets.iter_mut() {
+            data_set.reindex();
+            if let Some(target_points) = decimation_target_points {
+                data_set.precompute_decimation(target_points);
+            }
+        }
+        self.content_replaced(time_us);
+    }
     fn on_resize(&mut self) {
-        self.content_screen.schedule_canvas_size_sync();
-        self.control_screen.schedule_canvas_size_sync();
+        Self::postpone_resize(&mut self.resize_settle_time_us, Self::get_time_us());
         self.request_animation_frame();
     }
+    #[cfg(feature = "debug")]
+    fn debug_snapshot(&mut self, time_us: f64) -> String {
+        let snapshot = self.capture_snapshot(time_us);
+        let json = snapshot.to_json();
+        self.debug_baseline = Some(snapshot);
+        json
+    }
+    #[cfg(feature = "debug")]
+    fn debug_snapshot_diff(&mut self, time_us: f64) -> Result<Vec<String>, String> {
+        let snapshot = self.capture_snapshot(time_us);
+        self.debug_baseline
+            .as_ref()
+            .map(|baseline| baseline.diff(&snapshot))
+            .ok_or_else(|| "no snapshot taken yet".to_string())
+    }
 }
 
 // https://chartio.com/learn/charts/line-chart-complete-guide/
+
+// share of the width a legend on a side takes
+const LEGEND_SIDE_WIDTH: f64 = 0.2;
+
+// a line of the title font with a small gap below it
+fn get_title_padding(config: &ChartConfig, content: &Content) -> Size {
+    match content.name {
+        _ if config.mode == ChartMode::Sparkline => Size::Pct(0.0),
+        Some(_) => Size::Sum(vec![
+            config.title_font_size.clone(),
+            config.font_size_small.clone(),
+        ]),
+        None => Size::Pct(0.0),
+    }
+}
+
+fn get_content_padding(config: &ChartConfig, content: &Content) -> [Size; 4] {
+    let title = get_title_padding(config, content);
+    let (preview_height, legend_height) =
+        (config.layout_preview_height, config.layout_legend_height);
+    match config.legend_position {
+        LegendPosition::Bottom => [
+            title,
+            Size::Pct(0.0),
+            Size::Pct(preview_height + legend_height),
+            Size::Pct(0.0),
+        ],
+        LegendPosition::Top => [
+            Size::Sum(vec![Size::Pct(legend_height), title]),
+            Size::Pct(0.0),
+            Size::Pct(preview_height),
+            Size::Pct(0.0),
+        ],
+        LegendPosition::Hidden => [
+            title,
+            Size::Pct(0.0),
+            Size::Pct(preview_height),
+            Size::Pct(0.0),
+        ],
+        LegendPosition::Left => [
+            title,
+            Size::Pct(0.0),
+            Size::Pct(preview_height),
+            Size::Pct(LEGEND_SIDE_WIDTH),
+        ],
+        LegendPosition::Right => [
+            title,
+            Size::Pct(LEGEND_SIDE_WIDTH),
+            Size::Pct(preview_height),
+            Size::Pct(0.0),
+        ],
+    }
+}
+
+// the legend height goes to the content when the legend is hidden or on a side
+fn get_preview_n_legend_paddings(config: &ChartConfig) -> ([Size; 4], [Size; 4]) {
+    let (content_height, preview_height, legend_height) = (
+        config.layout_content_height,
+        config.layout_preview_height,
+        config.layout_legend_height,
+    );
+    match config.legend_position {
+        LegendPosition::Bottom => (
+            [
+                Size::Pct(content_height),
+                Size::Pct(0.0),
+                Size::Pct(legend_height),
+                Size::Pct(0.0),
+            ],
+            [
+                Size::Pct(content_height + preview_height),
+                Size::Pct(0.0),
+                Size::Pct(0.0),
+                Size::Pct(0.0),
+            ],
+        ),
+        LegendPosition::Top => (
+            [
+                Size::Pct(legend_height + content_height),
+                Size::Pct(0.0),
+                Size::Pct(0.0),
+                Size::Pct(0.0),
+            ],
+            [
+                Size::Pct(0.0),
+                Size::Pct(0.0),
+                Size::Pct(content_height + preview_height),
+                Size::Pct(0.0),
+            ],
+        ),
+        LegendPosition::Hidden => (
+            [
+                Size::Pct(content_height + legend_height),
+                Size::Pct(0.0),
+                Size::Pct(0.0),
+                Size::Pct(0.0),
+            ],
+            [
+                Size::Pct(1.0),
+                Size::Pct(0.0),
+                Size::Pct(0.0),
+                Size::Pct(0.0),
+            ],
+        ),
+        LegendPosition::Left => (
+            [
+                Size::Pct(content_height + legend_height),
+                Size::Pct(0.0),
+                Size::Pct(0.0),
+                Size::Pct(LEGEND_SIDE_WIDTH),
+            ],
+            [
+                Size::Pct(0.0),
+                Size::Pct(1.0 - LEGEND_SIDE_WIDTH),
+                Size::Pct(0.0),
+                Size::Pct(0.0),
+            ],
+        ),
+        LegendPosition::Right => (
+            [
+                Size::Pct(content_height + legend_height),
+                Size::Pct(LEGEND_SIDE_WIDTH),
+                Size::Pct(0.0),
+                Size::Pct(0.0),
+            ],
+            [
+                Size::Pct(0.0),
+                Size::Pct(0.0),
+                Size::Pct(0.0),
+                Size::Pct(1.0 - LEGEND_SIDE_WIDTH),
+            ],
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::main_chart::{MainChart, RESIZE_DEBOUNCE_US};
+    use crate::params::ChartConfig;
+    use crate::scale::LinearScale;
+    use crate::screen::ScreenPos;
+
+    #[test]
+    fn test_take_on_ready() {
+        let mut first_draw_done = false;
+        let mut on_ready = Some("callback");
+        assert_eq!(
+            MainChart::<LinearScale>::take_on_ready(&mut first_draw_done, &mut on_ready),
+            Some("callback")
+        );
+        assert!(first_draw_done);
+        // later draws don't call it again, even if it was set back
+        on_ready = Some("callback");
+        assert_eq!(
+            MainChart::<LinearScale>::take_on_ready(&mut first_draw_done, &mut on_ready),
+            None
+        );
+
+        let mut first_draw_done = false;
+        let mut on_ready: Option<&str> = None;
+        assert_eq!(
+            MainChart::<LinearScale>::take_on_ready(&mut first_draw_done, &mut on_ready),
+            None
+        );
+        assert!(first_draw_done);
+    }
+
+    #[test]
+    fn test_resize_debounce() {
+        type Chart = MainChart<LinearScale>;
+        let mut resize_settle_time_us = None;
+        assert_eq!(
+            Chart::take_settled_resize(&mut resize_settle_time_us, 0.0),
+            None
+        );
+
+        // a drag keeps pushing the sync back
+        for time_us in [0.0, 50_000.0, 90_000.0] {
+            Chart::postpone_resize(&mut resize_settle_time_us, time_us);
+        }
+        assert_eq!(
+            Chart::take_settled_resize(&mut resize_settle_time_us, 150_000.0),
+            Some(false)
+        );
+        assert_eq!(
+            Chart::take_settled_resize(&mut resize_settle_time_us, 90_000.0 + RESIZE_DEBOUNCE_US),
+            Some(true)
+        );
+        // synced once
+        assert_eq!(
+            Chart::take_settled_resize(&mut resize_settle_time_us, 500_000.0),
+            None
+        );
+    }
+
+    #[test]
+    fn test_resize_sources_coalesce() {
+        type Chart = MainChart<LinearScale>;
+        let mut resize_settle_time_us = None;
+        // the observer reports right after observe() & again along with a
+        // window resize, both land in the same settle window
+        Chart::postpone_resize(&mut resize_settle_time_us, 0.0);
+        Chart::postpone_resize(&mut resize_settle_time_us, 16_000.0);
+        Chart::postpone_resize(&mut resize_settle_time_us, 16_500.0);
+        let syncs = (0..20)
+            .map(|frame| frame as f64 * 16_000.0)
+            .filter(|&time_us| {
+                Chart::take_settled_resize(&mut resize_settle_time_us, time_us) == Some(true)
+            })
+            .count();
+        assert_eq!(syncs, 1);
+    }
+
+    #[test]
+    fn test_is_touch_held() {
+        type Chart = MainChart<LinearScale>;
+        let conf = ChartConfig::default();
+        let down = ScreenPos(10.0, 10.0);
+        let after_long_press = conf.us_long_press + 1.0;
+        assert!(Chart::is_touch_held(
+            Some(0.0),
+            Some(&down),
+            Some(&ScreenPos(10.0, 10.0)),
+            after_long_press,
+            &conf
+        ));
+        // a small jitter still counts as held in place
+        assert!(Chart::is_touch_held(
+            Some(0.0),
+            Some(&down),
+            Some(&ScreenPos(10.0 + conf.drag_threshold / 2.0, 10.0)),
+            after_long_press,
+            &conf
+        ));
+        // not long enough yet
+        assert!(!Chart::is_touch_held(
+            Some(0.0),
+            Some(&down),
+            Some(&down),
+            conf.us_long_press,
+            &conf
+        ));
+        // the finger moved, it's a drag
+        assert!(!Chart::is_touch_held(
+            Some(0.0),
+            Some(&down),
+            Some(&ScreenPos(10.0 + conf.drag_threshold * 2.0, 10.0)),
+            after_long_press,
+            &conf
+        ));
+        // no finger down
+        assert!(!Chart::is_touch_held(
+            None,
+            None,
+            Some(&down),
+            after_long_press,
+            &conf
+        ));
+    }
+}
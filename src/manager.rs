@@ -5,10 +5,13 @@
  *
  * Copyright (C) 2023, Nikita Almakov
  */
+use crate::debug::console_warn;
+use crate::error::{ChartError, ErrorCode};
 use crate::events::JsEventListener;
 use crate::main_chart::{DrawChart, MainChart};
-use crate::params::{ChartConfig, ChartParams, ClientCaps};
+use crate::params::{ChartConfig, ChartParams, ClientCaps, Content, ValueScale};
 use crate::scale::{LinearScale, LogScale, Scale};
+use crate::screen::ScreenPos;
 use js_sys::Reflect;
 use std::cell::RefCell;
 use std::marker::PhantomPinned;
@@ -19,6 +22,8 @@ use wasm_bindgen::prelude::*;
 pub struct ChartManager {
     global_window_resize: Option<JsEventListener>,
     global_orintation_change: Option<JsEventListener>,
+    global_reduced_motion_change: Option<JsEventListener>,
+    global_device_pixel_ratio_change: Option<JsEventListener>,
     charts: Rc<RefCell<Vec<Pin<Box<dyn DrawChart>>>>>,
     chart_ids: Vec<String>,
     touch_device: bool,
@@ -32,6 +37,8 @@ impl ChartManager {
         Self {
             global_window_resize: None,
             global_orintation_change: None,
+            global_reduced_motion_change: None,
+            global_device_pixel_ratio_change: None,
             charts: Rc::new(RefCell::new(Vec::new())),
             chart_ids: Vec::new(),
             touch_device,
@@ -43,57 +50,65 @@ impl ChartManager {
         mut self: Pin<&mut Self>,
         raw_params: JsValue,
         raw_config: JsValue,
-    ) -> Result<String, String> {
-        let chart_config =
-            ChartConfig::from_raw(&raw_config).map_err(|e| format!("config: {}", e.as_str()))?;
-        let mut chart_params = ChartParams::from(&raw_params, &chart_config)
-            .map_err(|e| format!("params: {}", e.as_str()))?;
+    ) -> Result<String, ChartError> {
+        let mut chart_config =
+            ChartConfig::from_raw(&raw_config).map_err(|e| e.with_code(ErrorCode::Config))?;
+        let mut chart_params = ChartParams::from(&raw_params, &chart_config)?;
 
+        // colors are bound to data sets while parsing, so sorting keeps them
         chart_params
             .content
             .sort_data_sets(&chart_config.sort_data_sets_by);
 
-        let content_wrapper_selector =
-            Self::inject_content_wrapper(chart_params.selector.as_str())?;
+        let container = Self::query_container(chart_params.selector.as_str())?;
+        // re-mounting into the same container (e.g. an SPA re-rendering the
+        // component) replaces the chart instead of stacking a second wrapper
+        if let Some(index) = self.find_mounted_chart(&container) {
+            let chart_id = JsValue::from_str(self.chart_ids[index].as_str());
+            self.as_mut().destroy_main(chart_id)?;
+        }
+        let content_wrapper_selector = Self::inject_content_wrapper(&container);
         unsafe { self.as_mut().get_unchecked_mut() }
             .chart_ids
             .push(content_wrapper_selector.clone());
         chart_params.selector = content_wrapper_selector.clone();
 
-        let log_main_scale = LogScale::new(&chart_params.content);
-        let linear_main_scale = LinearScale::new(&chart_params.content);
-        let mut min_log_covered_square: f64 = f64::MAX;
-        let mut min_linear_covered_square: f64 = f64::MAX;
-        for data_set in chart_params.content.data_sets.iter() {
-            let log_covered_square = log_main_scale.normalize_value(data_set.meta.max)
-                - log_main_scale.normalize_value(data_set.meta.min);
-            let linear_covered_square = linear_main_scale.normalize_value(data_set.meta.max)
-                - linear_main_scale.normalize_value(data_set.meta.min);
-            if log_covered_square != linear_covered_square {
-                min_log_covered_square = min_log_covered_square.min(log_covered_square);
-                min_linear_covered_square = min_linear_covered_square.min(linear_covered_square);
-            }
+        let use_log_scale = Self::resolve_log_scale(
+            &chart_config.value_scale,
+            &chart_params.content,
+            chart_config.auto_log_scale_threshold,
+        )
+        .unwrap_or_else(|| {
+            console_warn("valueScale: log requires positive values, falling back to linear");
+            false
+        });
+        // zero is never on a log axis & padding may not go below the data
+        if use_log_scale {
+            chart_config.begin_at_zero = false;
+            chart_params.content.begin_at_zero = false;
+            chart_config.value_padding = 0.0;
+            chart_params.content.value_padding = 0.0;
         }
 
-        if min_log_covered_square
-            > min_linear_covered_square * chart_config.auto_log_scale_threshold
-        {
+        if use_log_scale {
+            let main_scale = LogScale::new(&chart_params.content);
             let preview_scale = LogScale::new(&chart_params.content);
             self.charts.borrow_mut().push(MainChart::new(
                 chart_params,
                 chart_config,
                 Rc::clone(&self.client_caps),
-                log_main_scale,
+                main_scale,
                 preview_scale,
                 self.touch_device,
             )?);
         } else {
+            let main_scale = LinearScale::new(&chart_params.content);
             let preview_scale = LinearScale::new(&chart_params.content);
             self.charts.borrow_mut().push(MainChart::new(
                 chart_params,
                 chart_config,
                 Rc::clone(&self.client_caps),
-                linear_main_scale,
+                main_scale,
                 preview_scale,
                 self.touch_device,
             )?);
@@ -103,15 +118,57 @@ impl ChartManager {
         Ok(content_wrapper_selector)
     }
 
-    pub fn destroy_main(mut self: Pin<&mut Self>, chart_id: JsValue) -> Result<(), String> {
+    // the log scale is meant for strictly positive data, anything else gets
+    // distorted by the shift it applies; None when log is asked for anyway
+    fn resolve_log_scale(
+        value_scale: &ValueScale,
+        content: &Content,
+        auto_log_scale_threshold: f64,
+    ) -> Option<bool> {
+        match value_scale {
+            ValueScale::Auto => Some(
+                content.is_strictly_positive()
+                    && Self::prefers_log_scale(content, auto_log_scale_threshold),
+            ),
+            ValueScale::Linear => Some(false),
+            ValueScale::Log => content.is_strictly_positive().then_some(true),
+        }
+    }
+
+    fn prefers_log_scale(content: &Content, auto_log_scale_threshold: f64) -> bool {
+        let log_scale = LogScale::new(content);
+        let linear_scale = LinearScale::new(content);
+        let mut min_log_covered_square: f64 = f64::MAX;
+        let mut min_linear_covered_square: f64 = f64::MAX;
+        for data_set in content.data_sets.iter() {
+            let log_covered_square = log_scale.normalize_value(data_set.meta.max)
+                - log_scale.normalize_value(data_set.meta.min);
+            let linear_covered_square = linear_scale.normalize_value(data_set.meta.max)
+                - linear_scale.normalize_value(data_set.meta.min);
+            if log_covered_square != linear_covered_square {
+                min_log_covered_square = min_log_covered_square.min(log_covered_square);
+                min_linear_covered_square = min_linear_covered_square.min(linear_covered_square);
+            }
+        }
+        min_log_covered_square > min_linear_covered_square * auto_log_scale_threshold
+    }
+
+    fn get_chart_index(&self, chart_id: &JsValue) -> Result<usize, String> {
         let chart_id = chart_id
             .as_string()
             .ok_or_else(|| "not a string".to_string())?;
-        let index = self
-            .chart_ids
+        self.find_chart_index(chart_id.as_str())
+    }
+    fn find_chart_index(&self, chart_id: &str) -> Result<usize, String> {
+        self.chart_ids
             .iter()
-            .position(|id| id == chart_id.as_str())
-            .ok_or_else(|| "chart not found by id".to_string())?;
+            .position(|id| id == chart_id)
+            .ok_or_else(|| "chart not found by id".to_string())
+    }
+
+    pub fn destroy_main(mut self: Pin<&mut Self>, chart_id: JsValue) -> Result<(), ChartError> {
+        let index = self.get_chart_index(&chart_id)?;
+        let chart_id = chart_id.as_string().unwrap();
         let document = web_sys::window().unwrap().document().unwrap();
         let chart_wrapper = document
             .query_selector(chart_id.as_str())
@@ -129,21 +186,182 @@ impl ChartManager {
         Ok(())
     }
 
+    // leaves the manager empty, so a later create_main sets it up again
+    pub fn destroy_all(mut self: Pin<&mut Self>) {
+        let chart_ids = unsafe { self.as_mut().get_unchecked_mut() }.clear();
+        if chart_ids.is_empty() {
+            return;
+        }
+        let document = web_sys::window().unwrap().document().unwrap();
+        for chart_id in chart_ids {
+            if let Ok(Some(chart_wrapper)) = document.query_selector(chart_id.as_str()) {
+                chart_wrapper.remove();
+            }
+        }
+    }
+    // drops every chart & listener, returns the ids of the wrappers left to remove
+    fn clear(&mut self) -> Vec<String> {
+        self.charts.borrow_mut().clear();
+        self.uninstall_listeners();
+        std::mem::take(&mut self.chart_ids)
+    }
+
+    pub fn get_chart_ids(&self) -> js_sys::Array {
+        self.chart_ids
+            .iter()
+            .map(|chart_id| JsValue::from_str(chart_id.as_str()))
+            .collect()
+    }
+
+    pub fn export_image(&self, chart_id: JsValue, scale: f64) -> Result<String, ChartError> {
+        let index = self.get_chart_index(&chart_id)?;
+        let mut charts = self.charts.borrow_mut();
+        let chart = unsafe { Pin::into_inner_unchecked(charts[index].as_mut()) };
+        Ok(chart.export_image(scale, MainChart::<LinearScale>::get_time_us())?)
+    }
+
+    pub fn export_svg(&self, chart_id: JsValue) -> Result<String, ChartError> {
+        let index = self.get_chart_index(&chart_id)?;
+        let mut charts = self.charts.borrow_mut();
+        let chart = unsafe { Pin::into_inner_unchecked(charts[index].as_mut()) };
+        Ok(chart.export_svg(MainChart::<LinearScale>::get_time_us()))
+    }
+
+    pub fn nearest_point_at(
+        &self,
+        chart_id: JsValue,
+        css_x: f64,
+        css_y: f64,
+    ) -> Result<JsValue, ChartError> {
+        let index = self.get_chart_index(&chart_id)?;
+        let mut charts = self.charts.borrow_mut();
+        let chart = unsafe { Pin::into_inner_unchecked(charts[index].as_mut()) };
+        Ok(chart.nearest_point_at(
+            &ScreenPos(css_x, css_y),
+            MainChart::<LinearScale>::get_time_us(),
+        ))
+    }
+
+    pub fn set_connect_gaps(
+        &self,
+        chart_id: JsValue,
+        connect_gaps: bool,
+    ) -> Result<(), ChartError> {
+        let index = self.get_chart_index(&chart_id)?;
+        let mut charts = self.charts.borrow_mut();
+        let chart = unsafe { Pin::into_inner_unchecked(charts[index].as_mut()) };
+        chart.set_connect_gaps(connect_gaps);
+        Ok(())
+    }
+
+    pub fn set_series_visibility(
+        &self,
+        chart_id: JsValue,
+        name: String,
+        visible: bool,
+    ) -> Result<(), ChartError> {
+        let index = self.get_chart_index(&chart_id)?;
+        let mut charts = self.charts.borrow_mut();
+        let chart = unsafe { Pin::into_inner_unchecked(charts[index].as_mut()) };
+        chart
+            .set_series_visibility(
+                name.as_str(),
+                visible,
+                MainChart::<LinearScale>::get_time_us(),
+            )
+            .map_err(|e| ChartError::at("name".to_string(), e.as_str()))
+    }
+
+    pub fn get_visible_series(&self, chart_id: JsValue) -> Result<js_sys::Array, ChartError> {
+        let index = self.get_chart_index(&chart_id)?;
+        let charts = self.charts.borrow();
+        Ok(charts[index].get_visible_series())
+    }
+
+    pub fn update_data(&self, chart_id: JsValue, raw_params: JsValue) -> Result<(), ChartError> {
+        let index = self.get_chart_index(&chart_id)?;
+        let mut charts = self.charts.borrow_mut();
+        let chart = unsafe { Pin::into_inner_unchecked(charts[index].as_mut()) };
+        chart.update_data(&raw_params, MainChart::<LinearScale>::get_time_us())
+    }
+
+    pub fn refresh(&self, chart_id: JsValue) -> Result<(), ChartError> {
+        let index = self.get_chart_index(&chart_id)?;
+        let mut charts = self.charts.borrow_mut();
+        let chart = unsafe { Pin::into_inner_unchecked(charts[index].as_mut()) };
+        chart.refresh(MainChart::<LinearScale>::get_time_us());
+        Ok(())
+    }
+
+    #[cfg(feature = "debug")]
+    pub fn debug_snapshot(&self, chart_id: JsValue) -> Result<String, ChartError> {
+        let index = self.get_chart_index(&chart_id)?;
+        let mut charts = self.charts.borrow_mut();
+        let chart = unsafe { Pin::into_inner_unchecked(charts[index].as_mut()) };
+        Ok(chart.debug_snapshot(MainChart::<LinearScale>::get_time_us()))
+    }
+
+    #[cfg(feature = "debug")]
+    pub fn debug_snapshot_diff(&self, chart_id: JsValue) -> Result<js_sys::Array, ChartError> {
+        let index = self.get_chart_index(&chart_id)?;
+        let mut charts = self.charts.borrow_mut();
+        let chart = unsafe { Pin::into_inner_unchecked(charts[index].as_mut()) };
+        let changes = chart.debug_snapshot_diff(MainChart::<LinearScale>::get_time_us())?;
+        Ok(changes
+            .iter()
+            .map(|change| JsValue::from_str(change.as_str()))
+            .collect())
+    }
+
     fn uninstall_listeners(&mut self) {
         self.global_window_resize = None;
         self.global_orintation_change = None;
+        self.global_reduced_motion_change = None;
+        self.global_device_pixel_ratio_change = None;
     }
 
     fn ensure_global_listeners_are_set_up(&mut self) {
         if self.global_window_resize.is_some() {
             return;
         }
+        if let Some(reduced_motion_query) = ClientCaps::reduced_motion_query() {
+            let client_caps = Rc::clone(&self.client_caps);
+            self.global_reduced_motion_change = Some(JsEventListener::new(
+                reduced_motion_query.into(),
+                "change",
+                Box::new(move |_: JsValue| {
+                    *client_caps.borrow_mut() = ClientCaps::detect();
+                }),
+            ));
+        }
+        // e.g. the window is moved to a display of another density; the query
+        // reports leaving the initial ratio, resizes catch the rest
+        if let Some(device_pixel_ratio_query) =
+            ClientCaps::device_pixel_ratio_query(self.client_caps.borrow().device_pixel_ratio)
+        {
+            let client_caps = Rc::clone(&self.client_caps);
+            let charts = Rc::clone(&self.charts);
+            self.global_device_pixel_ratio_change = Some(JsEventListener::new(
+                device_pixel_ratio_query.into(),
+                "change",
+                Box::new(move |_: JsValue| {
+                    *client_caps.borrow_mut() = ClientCaps::detect();
+                    for chart in charts.borrow_mut().iter_mut() {
+                        unsafe { Pin::into_inner_unchecked(chart.as_mut()) }.on_resize();
+                    }
+                }),
+            ));
+        }
         let client_caps = Rc::clone(&self.client_caps);
         let charts = Rc::clone(&self.charts);
+        let resize_client_caps = Rc::clone(&self.client_caps);
         self.global_window_resize = Some(JsEventListener::new(
             web_sys::window().unwrap().into(),
             "resize",
             Box::new(move |_: JsValue| {
+                if resize_client_caps.borrow().device_pixel_ratio_changed() {
+                    *resize_client_caps.borrow_mut() = ClientCaps::detect();
+                }
                 for chart in charts.borrow_mut().iter_mut() {
                     unsafe { Pin::into_inner_unchecked(chart.as_mut()) }.on_resize();
                 }
@@ -177,13 +395,47 @@ impl ChartManager {
             ));
         }
     }
-    fn inject_content_wrapper(selector: &str) -> Result<String, String> {
+    fn query_container(selector: &str) -> Result<web_sys::Element, ChartError> {
+        let document = web_sys::window().unwrap().document().unwrap();
+        Self::resolve_container(selector, |selector| {
+            document.query_selector(selector).map_err(|_| ())
+        })
+    }
+    // errors name the selector, so a typo is easy to spot
+    fn resolve_container<E, Q: FnOnce(&str) -> Result<Option<E>, ()>>(
+        selector: &str,
+        query: Q,
+    ) -> Result<E, ChartError> {
+        if selector.trim().is_empty() {
+            return Err(ChartError::at("selector".to_string(), "is empty"));
+        }
+        query(selector)
+            .map_err(|_| {
+                ChartError::at(
+                    "selector".to_string(),
+                    format!("invalid selector: '{}'", selector).as_str(),
+                )
+            })?
+            .ok_or_else(|| {
+                ChartError::at(
+                    "selector".to_string(),
+                    format!("container not found: '{}'", selector).as_str(),
+                )
+            })
+    }
+    fn find_mounted_chart(&self, container: &web_sys::Element) -> Option<usize> {
+        let document = web_sys::window().unwrap().document().unwrap();
+        self.chart_ids.iter().position(|chart_id| {
+            document
+                .query_selector(chart_id.as_str())
+                .ok()
+                .flatten()
+                .and_then(|wrapper| wrapper.parent_element())
+                .is_some_and(|parent| parent.is_same_node(Some(container)))
+        })
+    }
+    fn inject_content_wrapper(container: &web_sys::Element) -> String {
         let document = web_sys::window().unwrap().document().unwrap();
-        let container = document
-            .query_selector(selector)
-            .unwrap()
-            .ok_or_else(|| "container not found".to_string())?;
-
         let wrapper = document.create_element("div").unwrap();
         let content_wrapper_selector = format!(
             "ac-{}",
@@ -196,7 +448,7 @@ impl ChartManager {
         wrapper
             .set_attribute("style", "width: 100%; height: 100%; position: relative")
             .unwrap();
-        Ok(format!("#{}", content_wrapper_selector.as_str()))
+        format!("#{}", content_wrapper_selector.as_str())
     }
     fn is_touch_device() -> bool {
         let window = web_sys::window().unwrap();
@@ -222,3 +474,125 @@ pub fn get_or_create_manager_addr() -> u32 {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::error::{ChartError, ErrorCode};
+    use crate::manager::ChartManager;
+    use crate::params::{ClientCaps, Content, ValueScale};
+    use std::cell::RefCell;
+    use std::marker::PhantomPinned;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_resolve_log_scale() {
+        let mut exponential = Content::default();
+        exponential
+            .parse_and_add_data_set(
+                "a",
+                (0..7).map(|x| x as f64).collect(),
+                (0..7).map(|x| 10f64.powi(x)).collect(),
+                (0, 0, 0),
+                0.0,
+            )
+            .unwrap();
+        exponential
+            .parse_and_add_data_set("b", vec![0.0, 6.0], vec![1.0, 2.0], (0, 0, 0), 0.0)
+            .unwrap();
+        let mut flat = Content::default();
+        flat.parse_and_add_data_set("a", vec![0.0, 1.0], vec![5.0, 6.0], (0, 0, 0), 0.0)
+            .unwrap();
+        let mut negative = Content::default();
+        negative
+            .parse_and_add_data_set("a", vec![0.0, 1.0], vec![-1.0, 6.0], (0, 0, 0), 0.0)
+            .unwrap();
+
+        let resolve = |value_scale: ValueScale, content: &Content| {
+            ChartManager::resolve_log_scale(&value_scale, content, 15.0)
+        };
+        assert_eq!(resolve(ValueScale::Auto, &exponential), Some(true));
+        assert_eq!(resolve(ValueScale::Auto, &flat), Some(false));
+        assert_eq!(resolve(ValueScale::Auto, &negative), Some(false));
+        // forced scales skip the heuristic
+        assert_eq!(resolve(ValueScale::Linear, &exponential), Some(false));
+        assert_eq!(resolve(ValueScale::Log, &flat), Some(true));
+        assert_eq!(resolve(ValueScale::Log, &negative), None);
+    }
+
+    #[test]
+    fn test_resolve_container() {
+        let query = |selector: &str| -> Result<Option<&str>, ()> {
+            match selector {
+                "#chart" => Ok(Some("container")),
+                "#missing" => Ok(None),
+                _ => Err(()),
+            }
+        };
+        assert_eq!(
+            ChartManager::resolve_container("#chart", query),
+            Ok("container")
+        );
+        let error = |message: &str| ChartError {
+            code: ErrorCode::Params,
+            path: Some("selector".to_string()),
+            message: message.to_string(),
+        };
+        assert_eq!(
+            ChartManager::resolve_container("#missing", query),
+            Err(error("container not found: '#missing'"))
+        );
+        assert_eq!(
+            ChartManager::resolve_container("#a[", query),
+            Err(error("invalid selector: '#a['"))
+        );
+        // never reaches the dom
+        assert_eq!(
+            ChartManager::resolve_container("  ", |_| -> Result<Option<&str>, ()> {
+                unreachable!()
+            }),
+            Err(error("is empty"))
+        );
+    }
+
+    fn manager_with_ids(chart_ids: &[&str]) -> ChartManager {
+        ChartManager {
+            global_window_resize: None,
+            global_orintation_change: None,
+            global_reduced_motion_change: None,
+            global_device_pixel_ratio_change: None,
+            charts: Rc::new(RefCell::new(Vec::new())),
+            chart_ids: chart_ids.iter().map(|id| id.to_string()).collect(),
+            touch_device: false,
+            client_caps: Rc::new(RefCell::new(ClientCaps {
+                touch_device: false,
+                device_pixel_ratio: 1.0,
+                css_to_physical_scale: 1.0,
+                screen_orientation: false,
+                prefers_reduced_motion: false,
+            })),
+            _pin: PhantomPinned,
+        }
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut manager = manager_with_ids(&["#ac-1", "#ac-2"]);
+        assert_eq!(manager.clear(), vec!["#ac-1", "#ac-2"]);
+        // left empty, so the next createMain starts from scratch
+        assert!(manager.chart_ids.is_empty());
+        assert!(manager.clear().is_empty());
+    }
+
+    #[test]
+    fn test_find_chart_index() {
+        let mut manager = manager_with_ids(&["#ac-1", "#ac-2"]);
+        // the listed ids are the ones the other calls accept
+        assert_eq!(manager.find_chart_index("#ac-2"), Ok(1));
+        assert_eq!(
+            manager.find_chart_index("#ac-3"),
+            Err("chart not found by id".to_string())
+        );
+        manager.clear();
+        assert!(manager.find_chart_index("#ac-1").is_err());
+    }
+}
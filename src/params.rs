@@ -5,14 +5,45 @@
  *
  * Copyright (C) 2023, Nikita Almakov
  */
+use crate::animate::{AnimatedNumber, Animation, Easing, DEFAULT_ANIMATION_DURATION_US};
+#[cfg(feature = "wasm")]
+use crate::data_set::{Aggregation, Interpolation, MovingAverage};
 use crate::data_set::{DataPoint, DataSet};
-use crate::screen::Size;
+use crate::decimate::{sample_evenly, DecimationMode};
+use crate::error::{ChartError, ErrorCode};
+use crate::grid::{calendar_unit, CalendarUnit, MINUTE_MS};
+use crate::pos::{ScreenPos, Size};
+use chrono::format::{Item, StrftimeItems};
 use chrono::prelude::*;
+#[cfg(feature = "wasm")]
 use js_sys::Reflect;
 use std::str::{from_utf8_unchecked, FromStr};
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
+#[cfg(feature = "wasm")]
 use wasm_bindgen::JsCast;
 
+#[cfg(feature = "wasm")]
+pub type IntlOptions = JsValue;
+#[cfg(feature = "wasm")]
+pub type IntlDateTimeFormat = js_sys::Intl::DateTimeFormat;
+// there is no Intl outside of the browser, so these can't be constructed
+#[cfg(not(feature = "wasm"))]
+#[derive(Clone)]
+pub enum IntlOptions {}
+#[cfg(not(feature = "wasm"))]
+#[derive(Clone)]
+pub enum IntlDateTimeFormat {}
+
+#[cfg(feature = "wasm")]
+fn get_local_tz_offset() -> Option<FixedOffset> {
+    FixedOffset::west_opt(js_sys::Date::new_0().get_timezone_offset() as i32 * 60)
+}
+#[cfg(not(feature = "wasm"))]
+fn get_local_tz_offset() -> Option<FixedOffset> {
+    Some(Local::now().offset().fix())
+}
+
 #[derive(Copy, Clone)]
 pub enum DataType {
     Number,
@@ -25,13 +56,10 @@ impl FromStr for DataType {
         match s.to_lowercase().as_str() {
             "number" => Ok(DataType::Number),
             "date" => Ok(DataType::Date),
-            "datetime" => {
-                let tz_offset_ms = js_sys::Date::new_0().get_timezone_offset() as i32 * 60;
-                Ok(DataType::DateTime {
-                    tz_offset: FixedOffset::west_opt(tz_offset_ms)
-                        .ok_or_else(|| "invalid timezone offset".to_string())?,
-                })
-            }
+            "datetime" => Ok(DataType::DateTime {
+                tz_offset: get_local_tz_offset()
+                    .ok_or_else(|| "invalid timezone offset".to_string())?,
+            }),
             v => Err(format!(
                 "invalid data type: '{}'; use 'number' or 'date'",
                 v
@@ -40,58 +68,191 @@ impl FromStr for DataType {
     }
 }
 impl DataType {
+    // explicit offset in minutes east of UTC instead of the browser's one
+    pub fn with_tz_offset_minutes(self, tz_offset_minutes: Option<f64>) -> Result<Self, String> {
+        match (self, tz_offset_minutes) {
+            (Self::DateTime { .. }, Some(minutes)) => Ok(Self::DateTime {
+                tz_offset: FixedOffset::east_opt((minutes * 60.0).round() as i32)
+                    .ok_or_else(|| format!("invalid timezone offset: {}", minutes))?,
+            }),
+            (data_type, _) => Ok(data_type),
+        }
+    }
     pub fn get_min_period(&self) -> Option<f64> {
         match self {
             Self::Number => None,
-            Self::DateTime { .. } => None,
+            Self::DateTime { .. } => Some(1.0),
             Self::Date => Some(86400000.0),
         }
     }
 }
 
-const SUFFIXES: [&'static str; 4] = ["", "K", "M", "B"];
+const SHORT_SCALE_SUFFIXES: [&str; 6] = ["", "K", "M", "B", "T", "Q"];
+const SI_SUFFIXES: [&str; 6] = ["", "k", "M", "G", "T", "P"];
+
+pub enum NumberAbbreviations {
+    ShortScale,
+    Si,
+}
+impl FromStr for NumberAbbreviations {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "short" => Ok(Self::ShortScale),
+            "si" => Ok(Self::Si),
+            v => Err(format!(
+                "invalid number abbreviations: '{}'; use 'short' or 'si'",
+                v
+            )),
+        }
+    }
+}
+impl NumberAbbreviations {
+    pub fn get_suffixes(&self) -> &'static [&'static str] {
+        match self {
+            Self::ShortScale => &SHORT_SCALE_SUFFIXES,
+            Self::Si => &SI_SUFFIXES,
+        }
+    }
+}
 
 #[derive(Clone)]
 pub enum VerboseFormat {
     Number {
         precision: usize,
         scale: usize,
+        group_separator: String,
+        decimal_separator: String,
+    },
+    NumberConcise {
+        decimal_separator: String,
+        suffixes: &'static [&'static str],
     },
-    NumberConcise,
     Date {
         fmt_str: String,
+        intl: Option<IntlDateTimeFormat>,
     },
     DateTime {
         fmt_str: String,
         tz_offset: FixedOffset,
+        intl: Option<IntlDateTimeFormat>,
     },
+    // milliseconds as e.g. "2h 15m"
+    Duration,
 }
 impl VerboseFormat {
     pub fn from_data_type(
         data_type: &DataType,
         chart_config: &ChartConfig,
+        fmt_override: Option<&str>,
         concise: bool,
-    ) -> VerboseFormat {
+    ) -> Result<VerboseFormat, String> {
         match data_type {
-            DataType::Date => VerboseFormat::Date {
-                fmt_str: "%b %d, %Y".to_string(),
-            },
-            DataType::DateTime { tz_offset } => VerboseFormat::DateTime {
-                fmt_str: "%b %d, %Y %H:%M:%S".to_string(),
+            DataType::Date => Ok(VerboseFormat::Date {
+                fmt_str: Self::validate_fmt_str(fmt_override.unwrap_or("%b %d, %Y"))?,
+                intl: Self::new_intl_date_time_format(chart_config)?,
+            }),
+            DataType::DateTime { tz_offset } => Ok(VerboseFormat::DateTime {
+                fmt_str: Self::validate_fmt_str(fmt_override.unwrap_or("%b %d, %Y %H:%M:%S"))?,
                 tz_offset: *tz_offset,
-            },
+                intl: Self::new_intl_date_time_format(chart_config)?,
+            }),
             DataType::Number => {
                 if concise {
-                    Self::NumberConcise
+                    Ok(Self::NumberConcise {
+                        decimal_separator: chart_config.decimal_separator.clone(),
+                        suffixes: chart_config.number_abbreviations.get_suffixes(),
+                    })
                 } else {
-                    Self::Number {
-                        precision: chart_config.exp_fmt_significant_digits,
-                        scale: chart_config.exp_fmt_significant_digits - 1,
+                    let mut precision = chart_config.exp_fmt_significant_digits;
+                    let mut scale = chart_config.exp_fmt_significant_digits - 1;
+                    if let Some(fmt) = fmt_override {
+                        let (raw_precision, raw_scale) = fmt.split_once('.').unwrap_or((fmt, ""));
+                        if !raw_precision.is_empty() {
+                            precision = usize::from_str(raw_precision)
+                                .map_err(|_| format!("invalid number format: '{}'", fmt))?;
+                        }
+                        if !raw_scale.is_empty() {
+                            scale = usize::from_str(raw_scale)
+                                .map_err(|_| format!("invalid number format: '{}'", fmt))?;
+                        }
                     }
+                    Ok(Self::Number {
+                        precision,
+                        scale,
+                        group_separator: chart_config.group_separator.clone(),
+                        decimal_separator: chart_config.decimal_separator.clone(),
+                    })
                 }
             }
         }
     }
+    fn localize_decimal_separator(formatted_value: String, decimal_separator: &str) -> String {
+        if decimal_separator == "." {
+            formatted_value
+        } else {
+            formatted_value.replacen('.', decimal_separator, 1)
+        }
+    }
+    // values are shifted to the desired offset beforehand, so Intl always formats in UTC
+    #[cfg(feature = "wasm")]
+    fn new_intl_date_time_format(
+        chart_config: &ChartConfig,
+    ) -> Result<Option<IntlDateTimeFormat>, String> {
+        let options = match chart_config.intl_date_format.as_ref() {
+            Some(options) => options,
+            None => return Ok(None),
+        };
+        let locales = js_sys::Array::new();
+        if let Some(locale) = chart_config.intl_locale.as_ref() {
+            locales.push(&JsValue::from_str(locale.as_str()));
+        }
+        let utc_options = js_sys::Object::assign(&js_sys::Object::new(), options.unchecked_ref());
+        Reflect::set(&utc_options, &"timeZone".into(), &"UTC".into()).unwrap();
+
+        let constructor = Reflect::get(&js_sys::global(), &"Intl".into())
+            .and_then(|intl| Reflect::get(&intl, &"DateTimeFormat".into()))
+            .map_err(|_| "Intl.DateTimeFormat is not supported".to_string())?;
+        Reflect::construct(
+            constructor.unchecked_ref::<js_sys::Function>(),
+            &js_sys::Array::of2(&locales, &utc_options),
+        )
+        .map(|intl| Some(intl.unchecked_into()))
+        .map_err(|e| {
+            format!(
+                "intlLocale/intlDateFormat: {}",
+                js_sys::Error::from(e).message()
+            )
+        })
+    }
+    #[cfg(not(feature = "wasm"))]
+    fn new_intl_date_time_format(
+        _chart_config: &ChartConfig,
+    ) -> Result<Option<IntlDateTimeFormat>, String> {
+        Ok(None)
+    }
+    #[cfg(feature = "wasm")]
+    fn format_intl(intl: &IntlDateTimeFormat, timestamp_ms: f64) -> String {
+        intl.format()
+            .call1(
+                &JsValue::NULL,
+                &js_sys::Date::new(&JsValue::from_f64(timestamp_ms)),
+            )
+            .unwrap()
+            .as_string()
+            .unwrap()
+    }
+    #[cfg(not(feature = "wasm"))]
+    fn format_intl(intl: &IntlDateTimeFormat, _timestamp_ms: f64) -> String {
+        match *intl {}
+    }
+    fn validate_fmt_str(fmt_str: &str) -> Result<String, String> {
+        if StrftimeItems::new(fmt_str).any(|item| item == Item::Error) {
+            Err(format!("invalid date format: '{}'", fmt_str))
+        } else {
+            Ok(fmt_str.to_string())
+        }
+    }
     pub fn format_values<T, U, F>(
         &self,
         values: T,
@@ -104,7 +265,11 @@ impl VerboseFormat {
         F: Fn(U) -> f64,
     {
         match self {
-            Self::Date { fmt_str } => values
+            Self::Date { intl: Some(intl), .. } => values
+                .map(getter)
+                .map(|value| Self::format_intl(intl, value))
+                .collect(),
+            Self::Date { fmt_str, .. } => values
                 .map(getter)
                 .map(|value| {
                     DateTime::from_timestamp_millis(value as i64)
@@ -113,7 +278,13 @@ impl VerboseFormat {
                         .to_string()
                 })
                 .collect(),
-            Self::DateTime { fmt_str, tz_offset } => values
+            Self::DateTime { tz_offset, intl: Some(intl), .. } => values
+                .map(getter)
+                .map(|value| {
+                    Self::format_intl(intl, (value + tz_offset.local_minus_utc() as f64) * 1000.0)
+                })
+                .collect(),
+            Self::DateTime { fmt_str, tz_offset, .. } => values
                 .map(getter)
                 .map(|value| {
                     DateTime::from_timestamp(value as i64, 0).unwrap().with_timezone(tz_offset)
@@ -121,12 +292,15 @@ impl VerboseFormat {
                         .to_string()
                 })
                 .collect(),
-            Self::Number {precision, scale} => values
+            Self::Number {precision, scale, group_separator, decimal_separator} => values
                 .map(getter)
                 .map(|value| {
                     let value_abs = value.abs();
                     if value_abs > 1e12 || value_abs < 1e-3 {
-                        format!("{:precision$.scale$e}", value, precision = precision, scale = scale)
+                        Self::localize_decimal_separator(
+                            format!("{:precision$.scale$e}", value, precision = precision, scale = scale),
+                            decimal_separator,
+                        )
                     } else {
                         let integral = (value.abs() as i64).to_string();
                         let parts: Vec<&str> = integral.as_bytes().rchunks(3).map(|b| unsafe {from_utf8_unchecked(b)}).rev().collect();
@@ -136,24 +310,28 @@ impl VerboseFormat {
                         match formatted_value.trim_end_matches("0").split_once(".") {
                             Some((_, right)) => {
                                 if right.len() > 0 {
-                                    format!{"{}{}.{}", if value < 0.0 {"-"} else {""}, parts.join(","), right}
+                                    format!{"{}{}{}{}", if value < 0.0 {"-"} else {""}, parts.join(group_separator), decimal_separator, right}
                                 } else {
-                                    format!{"{}{}", if value < 0.0 {"-"} else {""}, parts.join(",")}
+                                    format!{"{}{}", if value < 0.0 {"-"} else {""}, parts.join(group_separator)}
                                 }
                             }
                             None => {
-                                format!{"{}{}", if value < 0.0 {"-"} else {""}, parts.join(",")}
+                                format!{"{}{}", if value < 0.0 {"-"} else {""}, parts.join(group_separator)}
                             }
                         }
                     }
 
                 })
                 .collect(),
-            Self::NumberConcise => {
-                if min_value < -1e12 || max_value > 1e12 {
+            Self::NumberConcise {
+                decimal_separator,
+                suffixes,
+            } => {
+                if min_value < -1e18 || max_value > 1e18 {
                     values
                         .map(getter)
                         .map(|value| format!("{:3.2e}", value))
+                        .map(|value| Self::localize_decimal_separator(value, decimal_separator))
                         .collect()
                 } else {
                     values
@@ -164,7 +342,7 @@ impl VerboseFormat {
                             if value_abs < 1e-12 {
                                 format!("{:3.2e}", value)
                             } else {
-                                while value_abs >= 1000.0 {
+                                while value_abs >= 1000.0 && index + 1 < suffixes.len() {
                                     index += 1;
                                     value_abs *= 0.001
                                 }
@@ -173,7 +351,7 @@ impl VerboseFormat {
                                         format!(
                                             "{:.2}{}",
                                             value_abs * value.signum(),
-                                            SUFFIXES.get_unchecked(index)
+                                            suffixes.get_unchecked(index)
                                         )
                                     }
                                 } else if value_abs < 100.0 {
@@ -181,7 +359,7 @@ impl VerboseFormat {
                                         format!(
                                             "{:.1}{}",
                                             value_abs * value.signum(),
-                                            SUFFIXES.get_unchecked(index)
+                                            suffixes.get_unchecked(index)
                                         )
                                     }
                                 } else {
@@ -189,41 +367,146 @@ impl VerboseFormat {
                                         format!(
                                             "{:.0}{}",
                                             value_abs * value.signum(),
-                                            SUFFIXES.get_unchecked(index)
+                                            suffixes.get_unchecked(index)
                                         )
                                     }
                                 }
                             }
                         })
+                        .map(|value| Self::localize_decimal_separator(value, decimal_separator))
                         .collect()
                 }
             }
+            Self::Duration => values.map(getter).map(format_duration).collect(),
+        }
+    }
+    // tick labels following the distance between ticks (in coord units),
+    // None when it's not a date format or it's delegated to Intl
+    pub fn format_date_ticks<T, U, F>(
+        &self,
+        values: T,
+        getter: F,
+        period: f64,
+    ) -> Option<Vec<String>>
+    where
+        T: Iterator<Item = U>,
+        F: Fn(U) -> f64,
+    {
+        let utc = FixedOffset::east_opt(0).unwrap();
+        match self {
+            Self::Date { intl: None, .. } => Some(
+                values
+                    .map(getter)
+                    .map(|value| {
+                        format_date_tick(
+                            &DateTime::from_timestamp_millis(value as i64)
+                                .unwrap()
+                                .with_timezone(&utc),
+                            period,
+                        )
+                    })
+                    .collect(),
+            ),
+            Self::DateTime {
+                tz_offset,
+                intl: None,
+                ..
+            } => Some(
+                values
+                    .map(getter)
+                    .map(|value| {
+                        format_date_tick(
+                            &DateTime::from_timestamp(value as i64, 0)
+                                .unwrap()
+                                .with_timezone(tz_offset),
+                            period * 1000.0,
+                        )
+                    })
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+}
+
+const DURATION_UNITS: [(&str, f64); 6] = [
+    ("y", 365.0 * 86400000.0),
+    ("d", 86400000.0),
+    ("h", 3600000.0),
+    ("m", 60000.0),
+    ("s", 1000.0),
+    ("ms", 1.0),
+];
+
+// the two most significant units, e.g. "1y 20d", "2h 15m", "45s"
+pub fn format_duration(ms: f64) -> String {
+    let mut rest = ms.abs().round();
+    let mut parts: Vec<String> = Vec::with_capacity(2);
+    for (suffix, unit_ms) in DURATION_UNITS.iter() {
+        let number = (rest / unit_ms).floor();
+        rest -= number * unit_ms;
+        if number > 0.0 {
+            parts.push(format!("{}{}", number, suffix));
+        } else if !parts.is_empty() {
+            break;
+        }
+        if parts.len() == 2 {
+            break;
+        }
+    }
+    if parts.is_empty() {
+        parts.push("0s".to_string());
+    }
+    format!("{}{}", if ms < 0.0 { "-" } else { "" }, parts.join(" "))
+}
+
+// the coarsest format which still tells apart ticks this far from each other
+pub fn format_date_tick(datetime: &DateTime<FixedOffset>, period_ms: f64) -> String {
+    match calendar_unit(period_ms) {
+        Some(CalendarUnit::Year) => datetime.format("%Y").to_string(),
+        Some(CalendarUnit::Quarter) => {
+            format!("Q{} {}", datetime.month0() / 3 + 1, datetime.year())
         }
+        Some(CalendarUnit::Month) => datetime.format("%b %Y").to_string(),
+        Some(CalendarUnit::Week) => datetime.format("%b %d").to_string(),
+        Some(CalendarUnit::Day) => datetime.format("%a %d").to_string(),
+        None if period_ms >= MINUTE_MS => datetime.format("%H:%M").to_string(),
+        None => datetime.format("%H:%M:%S").to_string(),
     }
 }
 
-pub fn js_value_to_f64<O: Fn() -> String>(value: &JsValue, path: &O) -> Result<f64, String> {
+#[cfg(feature = "wasm")]
+pub fn js_value_to_f64<O: Fn() -> String>(value: &JsValue, path: &O) -> Result<f64, ChartError> {
     if let Some(v) = value.as_f64() {
         return Ok(v);
     }
     let string_value = value
         .as_string()
-        .ok_or_else(|| format!("neither a number nor a string: {}", path()))?;
+        .ok_or_else(|| ChartError::at(path(), "neither a number nor a string"))?;
 
     f64::from_str(string_value.as_str())
-        .map_err(|_| format!("failed to parse as f64: {}", path()))
+        .map_err(|_| ChartError::at(path(), "failed to parse as f64"))
         .and_then(|v| {
             if v.is_finite() {
                 Ok(v)
             } else {
-                Err(format!(
-                    "inf values are not supported: {}",
-                    string_value.as_str()
-                ))
+                Err(ChartError::at(path(), "inf values are not supported"))
             }
         })
 }
-fn js_value_to_date_as_f64<O: Fn() -> String>(value: &JsValue, path: &O) -> Result<f64, String> {
+// null, undefined & NaN mean "no data here" and break the line
+#[cfg(feature = "wasm")]
+pub fn is_missing_value(value: &JsValue) -> bool {
+    value.is_null()
+        || value.is_undefined()
+        || value.as_f64().is_some_and(|v| v.is_nan())
+        || value.as_string().is_some_and(|v| v == "NaN")
+}
+#[cfg(feature = "wasm")]
+fn js_value_to_date_as_f64<O: Fn() -> String>(
+    value: &JsValue,
+    path: &O,
+) -> Result<f64, ChartError> {
     let value = match value.clone().dyn_into::<js_sys::Date>() {
         Ok(dt) => dt.value_of(),
         Err(item) => js_sys::Date::new(&item).value_of(),
@@ -231,34 +514,39 @@ fn js_value_to_date_as_f64<O: Fn() -> String>(value: &JsValue, path: &O) -> Resu
     if value.is_finite() {
         Ok(value)
     } else {
-        Err(format!("{} not a date", path()))
+        Err(ChartError::at(path(), "not a date"))
     }
 }
-fn js_value_to_u8<O: Fn() -> String>(value: &JsValue, path: &O) -> Result<u8, String> {
+#[cfg(feature = "wasm")]
+fn js_value_to_u8<O: Fn() -> String>(value: &JsValue, path: &O) -> Result<u8, ChartError> {
     if let Some(v) = value.as_f64() {
         if v < 0.0 || v > 255.0 {
-            Err(format!("should be 0-255: {}", path()))
+            Err(ChartError::at(path(), "should be 0-255"))
         } else {
             Ok(v as u8)
         }
     } else {
         let string_value = value
             .as_string()
-            .ok_or_else(|| format!("neither a number nor a string: {}", path()))?;
+            .ok_or_else(|| ChartError::at(path(), "neither a number nor a string"))?;
         u8::from_str(string_value.as_str())
-            .map_err(|_| format!("failed to parse as u8: {}", path()))
+            .map_err(|_| ChartError::at(path(), "failed to parse as u8"))
     }
 }
-fn js_value_to_rgb<O: Fn() -> String>(value: &JsValue, path: &O) -> Result<(u8, u8, u8), String> {
+#[cfg(feature = "wasm")]
+fn js_value_to_rgb<O: Fn() -> String>(
+    value: &JsValue,
+    path: &O,
+) -> Result<(u8, u8, u8), ChartError> {
     let items: Vec<JsValue> = value
         .clone()
         .dyn_into::<js_sys::Array>()
-        .map_err(|_| format!("not an array: {}", path()))?
+        .map_err(|_| ChartError::at(path(), "not an array"))?
         .iter()
         .collect();
 
     if items.len() != 3 {
-        return Err(format!("color is an array of length 3: {}", path()));
+        return Err(ChartError::at(path(), "color is an array of length 3"));
     }
 
     Ok((
@@ -267,73 +555,139 @@ fn js_value_to_rgb<O: Fn() -> String>(value: &JsValue, path: &O) -> Result<(u8,
         js_value_to_u8(&items[2], &|| format!("{}.{}", path(), 2))?,
     ))
 }
+#[cfg(feature = "wasm")]
 fn get_by_str_key<O: Fn() -> String>(
     obj: &JsValue,
     key: &str,
     path: &O,
-) -> Result<JsValue, String> {
+) -> Result<JsValue, ChartError> {
     Reflect::get(obj, &JsValue::from_str(key))
-        .map_err(|_| format!("not an object to fetch: '{}'", path()))
+        .map_err(|_| ChartError::at(path(), "not an object to fetch"))
+}
+
+#[cfg(feature = "wasm")]
+fn get_optional_by_str_key<O: Fn() -> String>(
+    obj: &JsValue,
+    key: &str,
+    path: &O,
+) -> Result<Option<JsValue>, ChartError> {
+    let value = get_by_str_key(obj, key, path)?;
+    if value.is_undefined() || value.is_null() {
+        Ok(None)
+    } else {
+        Ok(Some(value))
+    }
+}
+
+#[cfg(feature = "wasm")]
+fn get_optional_string_by_str_key<O: Fn() -> String>(
+    obj: &JsValue,
+    key: &str,
+    path: &O,
+) -> Result<Option<String>, ChartError> {
+    get_optional_by_str_key(obj, key, path)?
+        .map(|value| {
+            value
+                .as_string()
+                .ok_or_else(|| ChartError::at(path(), "not a string"))
+        })
+        .transpose()
+}
+
+#[cfg(feature = "wasm")]
+fn get_optional_bool_by_str_key<O: Fn() -> String>(
+    obj: &JsValue,
+    key: &str,
+    path: &O,
+) -> Result<Option<bool>, ChartError> {
+    get_optional_by_str_key(obj, key, path)?
+        .map(|value| {
+            value
+                .as_bool()
+                .ok_or_else(|| ChartError::at(path(), "not a bool"))
+        })
+        .transpose()
 }
 
+#[cfg(feature = "wasm")]
 fn get_string_by_str_key<O: Fn() -> String>(
     obj: &JsValue,
     key: &str,
     path: &O,
-) -> Result<String, String> {
+) -> Result<String, ChartError> {
     get_by_str_key(obj, key, path)?
         .as_string()
-        .ok_or_else(|| format!("{} is missing", path()))
+        .ok_or_else(|| ChartError::at(path(), "is missing"))
 }
 
 // fn get_bool_by_str_key<O: Fn() -> String>(
 //     obj: &JsValue,
 //     key: &str,
 //     path: &O,
-// ) -> Result<bool, String> {
+// ) -> Result<bool, ChartError> {
 //     get_by_str_key(obj, key, path)?
 //         .as_bool()
-//         .ok_or_else(|| format!("not a bool: {}", path().as_str()))
+//         .ok_or_else(|| ChartError::at(path(), "not a bool"))
 // }
 
+#[cfg(feature = "wasm")]
 fn get_f64_by_str_key<O: Fn() -> String>(
     obj: &JsValue,
     key: &str,
     path: &O,
-) -> Result<f64, String> {
+) -> Result<f64, ChartError> {
     js_value_to_f64(&get_by_str_key(obj, key, path)?, path)
 }
 
-fn get_u8_by_str_key<O: Fn() -> String>(obj: &JsValue, key: &str, path: &O) -> Result<u8, String> {
+#[cfg(feature = "wasm")]
+fn get_optional_f64_by_str_key<O: Fn() -> String>(
+    obj: &JsValue,
+    key: &str,
+    path: &O,
+) -> Result<Option<f64>, ChartError> {
+    get_optional_by_str_key(obj, key, path)?
+        .map(|value| js_value_to_f64(&value, path))
+        .transpose()
+}
+
+#[cfg(feature = "wasm")]
+fn get_u8_by_str_key<O: Fn() -> String>(
+    obj: &JsValue,
+    key: &str,
+    path: &O,
+) -> Result<u8, ChartError> {
     js_value_to_u8(&get_by_str_key(obj, key, path)?, path)
 }
 
+#[cfg(feature = "wasm")]
 fn get_array_by_str_key<O: Fn() -> String>(
     obj: &JsValue,
     key: &str,
     path: &O,
-) -> Result<js_sys::Array, String> {
+) -> Result<js_sys::Array, ChartError> {
     get_by_str_key(obj, key, path)?
         .dyn_into::<js_sys::Array>()
-        .map_err(|_| format!("not an array: {}", path()))
+        .map_err(|_| ChartError::at(path(), "not an array"))
 }
 
+#[cfg(feature = "wasm")]
 fn get_rgb_by_str_key<O: Fn() -> String>(
     obj: &JsValue,
     key: &str,
     path: &O,
-) -> Result<(u8, u8, u8), String> {
+) -> Result<(u8, u8, u8), ChartError> {
     js_value_to_rgb(&get_by_str_key(obj, key, path)?, path)
 }
 
+#[cfg(feature = "wasm")]
 fn get_rgba_by_str_key<O: Fn() -> String>(
     obj: &JsValue,
     key: &str,
     path: &O,
-) -> Result<(u8, u8, u8, f64), String> {
+) -> Result<(u8, u8, u8, f64), ChartError> {
     let items: Vec<JsValue> = get_array_by_str_key(obj, key, path)?.iter().collect();
     if items.len() != 4 {
-        return Err(format!("color is an array of length 4: {}", path()));
+        return Err(ChartError::at(path(), "color is an array of length 4"));
     }
     Ok((
         js_value_to_u8(&items[0], &|| format!("{}.0", path()))?,
@@ -343,35 +697,33 @@ fn get_rgba_by_str_key<O: Fn() -> String>(
     ))
 }
 
+#[cfg(feature = "wasm")]
 pub fn parse_js_values<O: Fn() -> String>(
     value: js_sys::Array,
     data_type: DataType,
+    allow_missing: bool,
     path: &O,
-) -> Result<Vec<f64>, String> {
+) -> Result<Vec<f64>, ChartError> {
     let mut result: Vec<f64> = Vec::with_capacity(value.length() as usize);
-    match data_type {
-        DataType::Number => {
-            for (index, item) in value.iter().enumerate() {
-                result.push(js_value_to_f64(&item, &|| format!("{}.{}", path(), index))?);
-            }
-        }
-        DataType::Date => {
-            for (index, item) in value.iter().enumerate() {
-                result.push(js_value_to_date_as_f64(&item, &|| {
-                    format!("{}.{}", path(), index)
-                })?);
-            }
+    for (index, item) in value.iter().enumerate() {
+        if allow_missing && is_missing_value(&item) {
+            result.push(f64::NAN);
+            continue;
         }
-        DataType::DateTime { .. } => {
-            for (index, item) in value.iter().enumerate() {
-                result.push(js_value_to_date_as_f64(&item, &|| {
-                    format!("{}.{}", path(), index)
-                })?);
+        result.push(match data_type {
+            DataType::Number => js_value_to_f64(&item, &|| format!("{}.{}", path(), index))?,
+            DataType::Date | DataType::DateTime { .. } => {
+                js_value_to_date_as_f64(&item, &|| format!("{}.{}", path(), index))?
             }
-        }
+        });
     }
     Ok(result)
 }
+// attaches a top-level key to errors of FromStr impls
+#[cfg(feature = "wasm")]
+fn at_key(key: &'static str) -> impl Fn(String) -> ChartError {
+    move |message| ChartError::at(key.to_string(), message.as_str())
+}
 
 pub struct Content {
     pub name: Option<String>,
@@ -388,38 +740,95 @@ pub struct Content {
     pub global_coord_max: f64,
     pub global_value_min: f64,
     pub global_value_max: f64,
+    pub hovered_data_set: Option<usize>,
+    pub hover_preview_mix: AnimatedNumber,
+    pub connect_gaps: bool,
+    pub max_points_per_series: Option<usize>,
+    pub on_exceed: OnExceed,
+    pub value_transform: ValueTransform,
+    pub begin_at_zero: bool,
+    pub value_padding: f64,
+    pub log_base: f64,
+    pub coord_axis_title: Option<String>,
+    pub value_axis_title: Option<String>,
+    pub annotations: Vec<Annotation>,
+    pub markers: Vec<Marker>,
 }
-// TODO: panic on empty or zero height data
 impl Content {
     pub fn new(
         name: Option<String>,
         coord_type: DataType,
         value_type: DataType,
         chart_config: &ChartConfig,
-    ) -> Content {
-        Content {
+    ) -> Result<Content, ChartError> {
+        let coord_format = chart_config.coord_format.as_deref();
+        let value_format = chart_config.value_format.as_deref();
+        // the formats come from the config, though only the params tell how
+        // to apply them
+        let at_config_key = |key: &'static str| {
+            move |message: String| {
+                ChartError::at(key.to_string(), message.as_str()).with_code(ErrorCode::Config)
+            }
+        };
+        Ok(Content {
             name,
             coord_type,
-            coord_verbose_format: VerboseFormat::from_data_type(&coord_type, chart_config, false),
+            coord_verbose_format: VerboseFormat::from_data_type(
+                &coord_type,
+                chart_config,
+                coord_format,
+                false,
+            )
+            .map_err(at_config_key("coordFormat"))?,
             coord_verbose_format_short: VerboseFormat::from_data_type(
                 &coord_type,
                 chart_config,
+                coord_format,
                 true,
-            ),
+            )
+            .map_err(at_config_key("coordFormat"))?,
             coord_short_verbose_len: 0,
             value_type,
-            value_verbose_format: VerboseFormat::from_data_type(&value_type, chart_config, false),
+            value_verbose_format: VerboseFormat::from_data_type(
+                &value_type,
+                chart_config,
+                value_format,
+                false,
+            )
+            .map_err(at_config_key("valueFormat"))?,
             value_verbose_format_short: VerboseFormat::from_data_type(
                 &value_type,
                 chart_config,
+                value_format,
                 true,
-            ),
+            )
+            .map_err(at_config_key("valueFormat"))?,
             value_short_verbose_len: 0,
             data_sets: Vec::new(),
             global_coord_min: f64::MAX,
             global_coord_max: f64::MIN,
             global_value_min: f64::MAX,
             global_value_max: f64::MIN,
+            hovered_data_set: None,
+            hover_preview_mix: AnimatedNumber::new(0.0),
+            connect_gaps: chart_config.connect_gaps,
+            max_points_per_series: chart_config.max_points_per_series,
+            on_exceed: chart_config.on_exceed,
+            value_transform: chart_config.value_transform,
+            begin_at_zero: chart_config.begin_at_zero,
+            value_padding: chart_config.value_padding,
+            log_base: chart_config.log_base,
+            coord_axis_title: None,
+            value_axis_title: None,
+            annotations: Vec::new(),
+            markers: Vec::new(),
+        })
+    }
+    pub fn get_hover_preview(&mut self, time_us: f64) -> Option<(usize, f64)> {
+        let mix = self.hover_preview_mix.get_value(time_us);
+        match self.hovered_data_set {
+            Some(index) if mix > 0.0 => Some((index, mix)),
+            _ => None,
         }
     }
     pub fn parse_and_add_data_set(
@@ -428,15 +837,41 @@ impl Content {
         coords: Vec<f64>,
         values: Vec<f64>,
         rgb: (u8, u8, u8),
-    ) -> Result<(), String> {
-        if coords.len() != values.len() {
-            return Err(format!(
-                "coords and values have different lengths: {}",
-                name
-            ));
+        coord_offset: f64,
+    ) -> Result<&mut DataSet, String> {
+        match (coords.len(), values.len()) {
+            (0, 0) => {
+                return Err(format!("data set '{}' - coords and values are empty", name));
+            }
+            (0, values_len) => {
+                return Err(format!(
+                    "data set '{}' - coords are empty, values: {}",
+                    name, values_len
+                ));
+            }
+            (coords_len, 0) => {
+                return Err(format!(
+                    "data set '{}' - values are empty, coords: {}",
+                    name, coords_len
+                ));
+            }
+            (coords_len, values_len) if coords_len != values_len => {
+                return Err(format!(
+                    "data set '{}' - coords and values have different lengths: coords: {}, values: {}",
+                    name, coords_len, values_len
+                ));
+            }
+            _ => {}
         }
-        if coords.is_empty() {
-            return Err(format!("data set is empty: {}", name));
+        if let (Some(max_points), OnExceed::Error) = (self.max_points_per_series, self.on_exceed) {
+            if coords.len() > max_points {
+                return Err(format!(
+                    "data set '{}' - too many points: {}, maxPointsPerSeries: {}",
+                    name,
+                    coords.len(),
+                    max_points
+                ));
+            }
         }
         if self.data_sets.iter().any(|item| item.name == name) {
             return Err(format!("duplicate data set name: {}", name));
@@ -444,7 +879,10 @@ impl Content {
         let mut data_points: Vec<DataPoint> = coords
             .into_iter()
             .zip(values)
-            .map(|(coord, value)| DataPoint { coord, value })
+            .map(|(coord, value)| DataPoint {
+                coord: coord + coord_offset,
+                value,
+            })
             .collect();
 
         data_points.sort_by(|p1, p2| p1.coord.partial_cmp(&p2.coord).unwrap());
@@ -461,7 +899,34 @@ impl Content {
                 ));
             }
         }
-        let data_set = DataSet::new(name, rgb, data_points);
+        if let Some(max_points) = self.max_points_per_series {
+            if data_points.len() > max_points {
+                data_points = sample_evenly(data_points.as_slice(), max_points);
+            }
+        }
+        let mut value_factor = 1.0;
+        if let ValueTransform::Percent = self.value_transform {
+            let base = data_points
+                .iter()
+                .map(|p| p.value)
+                .find(|value| !value.is_nan())
+                .unwrap_or(f64::NAN);
+            if base == 0.0 {
+                return Err(format!(
+                    "data set '{}' - the first value is 0, cannot be taken as 100%",
+                    name
+                ));
+            }
+            for data_point in data_points.iter_mut() {
+                data_point.value = data_point.value / base * 100.0;
+            }
+            value_factor = 100.0 / base;
+        }
+        let mut data_set = DataSet::new(name, rgb, data_points);
+        data_set.value_factor = value_factor;
+        if data_set.meta.min.is_nan() {
+            return Err(format!("data set '{}' - all values are missing", name));
+        }
         self.coord_short_verbose_len = self.coord_short_verbose_len.max(
             self.coord_verbose_format_short
                 .format_values(
@@ -482,7 +947,11 @@ impl Content {
         self.value_short_verbose_len = self.value_short_verbose_len.max(
             self.value_verbose_format_short
                 .format_values(
-                    data_set.data_points.iter().take(30),
+                    data_set
+                        .data_points
+                        .iter()
+                        .filter(|p| !p.value.is_nan())
+                        .take(30),
                     |p| p.value,
                     data_set.meta.min,
                     data_set.meta.max,
@@ -499,8 +968,75 @@ impl Content {
         self.global_value_min = self.global_value_min.min(data_set.meta.min);
         self.global_value_max = self.global_value_max.max(data_set.meta.max);
         self.data_sets.push(data_set);
+        Ok(self.data_sets.last_mut().unwrap())
+    }
+
+    // unlike legend clicks, this may hide every data set
+    pub fn set_data_set_visibility(
+        &mut self,
+        name: &str,
+        visible: bool,
+        time_us: f64,
+        animation: Animation,
+    ) -> Result<(), String> {
+        let data_set = self
+            .data_sets
+            .iter_mut()
+            .find(|data_set| data_set.name == name)
+            .ok_or_else(|| format!("data set not found by name: {}", name))?;
+        let alpha = if visible { 1.0 } else { 0.0 };
+        if data_set.alpha.get_end_value() != alpha {
+            data_set.alpha.set_value(alpha, Some(time_us), animation);
+        }
         Ok(())
     }
+    pub fn get_visible_data_set_names(&self) -> Vec<&str> {
+        self.data_sets
+            .iter()
+            .filter(|data_set| data_set.alpha.get_end_value() > 0.0)
+            .map(|data_set| data_set.name.as_str())
+            .collect()
+    }
+
+    // no data counts as not positive, there is nothing to prefer log for
+    pub fn is_strictly_positive(&self) -> bool {
+        !self.data_sets.is_empty() && self.global_value_min > 0.0
+    }
+
+    // bounds are extended as data sets are added, after other mutations they
+    // are to be recomputed from scratch
+    pub fn recompute_global_bounds(&mut self) {
+        self.global_coord_min = f64::MAX;
+        self.global_coord_max = f64::MIN;
+        self.global_value_min = f64::MAX;
+        self.global_value_max = f64::MIN;
+        for data_set in self.data_sets.iter() {
+            if let (Some(first), Some(last)) =
+                (data_set.data_points.first(), data_set.data_points.last())
+            {
+                self.global_coord_min = self.global_coord_min.min(first.coord);
+                self.global_coord_max = self.global_coord_max.max(last.coord);
+            }
+            for data_point in data_set
+                .data_points
+                .iter()
+                .chain(data_set.band_low.iter())
+                .chain(data_set.band_high.iter())
+            {
+                if !data_point.value.is_nan() {
+                    self.global_value_min = self.global_value_min.min(data_point.value);
+                    self.global_value_max = self.global_value_max.max(data_point.value);
+                }
+            }
+        }
+        // no data yet, a placeholder is drawn within a unit frame
+        if self.data_sets.is_empty() {
+            self.global_coord_min = 0.0;
+            self.global_coord_max = 1.0;
+            self.global_value_min = 0.0;
+            self.global_value_max = 1.0;
+        }
+    }
 
     pub fn sort_data_sets(&mut self, strategy: &DataSetSorting) {
         match strategy {
@@ -540,15 +1076,72 @@ impl Content {
             if data_set.alpha.get_end_value() > 0.0 {
                 coord_min = coord_min.min(data_set.data_points[0].coord);
                 coord_max =
-                    coord_min.max(data_set.data_points[data_set.data_points.len() - 1].coord);
-                for data_point in data_set.data_points.iter() {
+                    coord_max.max(data_set.data_points[data_set.data_points.len() - 1].coord);
+                for data_point in data_set
+                    .data_points
+                    .iter()
+                    .chain(data_set.band_low.iter())
+                    .chain(data_set.band_high.iter())
+                {
                     value_min = value_min.min(data_point.value);
                     value_max = value_max.max(data_point.value);
                 }
             }
         }
+        // everything is hidden, so keep the whole data in view
+        if coord_min > coord_max || value_min > value_max {
+            let (value_min, value_max) =
+                self.frame_value_range(self.global_value_min, self.global_value_max);
+            return [
+                self.global_coord_min,
+                self.global_coord_max,
+                value_min,
+                value_max,
+            ];
+        }
+        let (value_min, value_max) = self.frame_value_range(value_min, value_max);
         [coord_min, coord_max, value_min, value_max]
     }
+    // with begin_at_zero the value axis always reaches zero, from either side;
+    // the padding is added then, except beyond zero
+    pub fn frame_value_range(&self, value_min: f64, value_max: f64) -> (f64, f64) {
+        if value_min > value_max {
+            return (value_min, value_max);
+        }
+        let (value_min, value_max) = if self.begin_at_zero {
+            (value_min.min(0.0), value_max.max(0.0))
+        } else {
+            (value_min, value_max)
+        };
+        let padding = (value_max - value_min) * self.value_padding;
+        let keeps_zero = |value: f64| self.begin_at_zero && value == 0.0;
+        (
+            if keeps_zero(value_min) {
+                value_min
+            } else {
+                value_min - padding
+            },
+            if keeps_zero(value_max) {
+                value_max
+            } else {
+                value_max + padding
+            },
+        )
+    }
+}
+// numbers formatted as by the default config
+#[cfg(test)]
+impl Default for Content {
+    fn default() -> Self {
+        // the default config has no formats to reject
+        Content::new(
+            None,
+            DataType::Number,
+            DataType::Number,
+            &ChartConfig::default(),
+        )
+        .unwrap()
+    }
 }
 
 pub enum DataSetSorting {
@@ -576,77 +1169,612 @@ impl FromStr for DataSetSorting {
     }
 }
 
-pub struct ChartConfig {
-    pub font_standard: String,
-    pub font_monospace: String,
-    pub font_size_small: Size,
-    pub font_size_normal: Size,
-    pub font_size_large: Size,
-    pub font_width_coeff: f64,
-    pub line_width: Size,
-    pub circle_diameter: Size,
-    pub color_grid: (u8, u8, u8),
-    pub color_tick: (u8, u8, u8),
-    pub color_camera_grip: (u8, u8, u8, f64),
-    pub color_preview_overlay: (u8, u8, u8, f64),
-    pub color_preview_hint: (u8, u8, u8, f64),
-    pub color_tooltip: (u8, u8, u8, f64),
-    pub color_tooltip_font: (u8, u8, u8, f64),
-    pub sort_data_sets_by: DataSetSorting,
-    pub layout_content_height: f64,
-    pub layout_preview_height: f64,
-    pub layout_legend_height: f64,
-    pub color_palette: Vec<(u8, u8, u8)>,
-    pub us_long_press: f64,
-    pub auto_log_scale_threshold: f64,
-    pub exp_fmt_significant_digits: usize,
+pub enum UpdateZoomPolicy {
+    Reset,
+    Preserve,
+    ClampToData,
+}
+impl FromStr for UpdateZoomPolicy {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "reset" => Ok(Self::Reset),
+            "preserve" => Ok(Self::Preserve),
+            "clampToData" => Ok(Self::ClampToData),
+            v => Err(format!("unsupported UpdateZoomPolicy: {}", v)),
+        }
+    }
+}
+impl UpdateZoomPolicy {
+    // returns the coord window to zoom into after the data is replaced, None
+    // means the full range
+    pub fn get_window(
+        &self,
+        window: Option<(f64, f64)>,
+        coord_min: f64,
+        coord_max: f64,
+    ) -> Option<(f64, f64)> {
+        let (coord_start, coord_end) = window?;
+        match self {
+            Self::Reset => None,
+            Self::Preserve => Some((coord_start, coord_end)),
+            Self::ClampToData => {
+                let coord_range = coord_end - coord_start;
+                if coord_range >= coord_max - coord_min {
+                    None
+                } else if coord_start < coord_min {
+                    Some((coord_min, coord_min + coord_range))
+                } else if coord_end > coord_max {
+                    Some((coord_max - coord_range, coord_max))
+                } else {
+                    Some((coord_start, coord_end))
+                }
+            }
+        }
+    }
 }
-impl ChartConfig {
-    pub fn from_raw(raw_config: &JsValue) -> Result<Self, String> {
-        let layout_content_height = get_f64_by_str_key(raw_config, "layoutContentHeight", &|| {
-            "layoutContentHeight".to_string()
-        })?;
-        let layout_preview_height = get_f64_by_str_key(raw_config, "layoutPreviewHeight", &|| {
-            "layoutPreviewHeight".to_string()
-        })?;
-        let layout_legend_height = get_f64_by_str_key(raw_config, "layoutLegendHeight", &|| {
-            "layoutLegendHeight".to_string()
-        })?;
-        let total_height_norm =
-            (layout_content_height + layout_preview_height + layout_legend_height).recip();
 
-        let color_palette: Result<Vec<(u8, u8, u8)>, String> =
-            get_array_by_str_key(raw_config, "colorPalette", &|| "colorPalette".to_string())?
-                .iter()
-                .enumerate()
-                .map(|(index, item)| js_value_to_rgb(&item, &|| format!("colorPalette.{}", index)))
-                .collect();
+pub enum TooltipMode {
+    Full,
+    Compact,
+}
+impl FromStr for TooltipMode {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "full" => Ok(Self::Full),
+            "compact" => Ok(Self::Compact),
+            v => Err(format!("unsupported TooltipMode: {}", v)),
+        }
+    }
+}
 
-        Ok(Self {
-            font_standard: get_string_by_str_key(raw_config, "fontStandard", &|| {
-                "fontStandard".to_string()
-            })?,
-            font_monospace: get_string_by_str_key(raw_config, "fontMonospace", &|| {
-                "fontMonospace".to_string()
-            })?,
-            font_size_small: Size::TextLine {
-                font_size: get_f64_by_str_key(raw_config, "fontSizeSmall", &|| {
-                    "fontSizeSmall".to_string()
-                })?,
-                columns: 1.0,
-            },
-            font_size_normal: Size::TextLine {
-                font_size: get_f64_by_str_key(raw_config, "fontSizeNormal", &|| {
-                    "fontSizeNormal".to_string()
-                })?,
-                columns: 1.0,
+// "follow" keeps the tooltip next to the pointer, corners keep it off the
+// hovered data, "anchored" puts it above the highlighted point
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TooltipPosition {
+    Follow,
+    TopRight,
+    TopLeft,
+    Anchored,
+}
+impl FromStr for TooltipPosition {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "follow" => Ok(Self::Follow),
+            "topRight" => Ok(Self::TopRight),
+            "topLeft" => Ok(Self::TopLeft),
+            "anchored" => Ok(Self::Anchored),
+            v => Err(format!("unsupported TooltipPosition: {}", v)),
+        }
+    }
+}
+
+// "custom" leaves drawing the tooltip box to the onTooltip callback
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TooltipRenderer {
+    Canvas,
+    Custom,
+}
+impl FromStr for TooltipRenderer {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "canvas" => Ok(Self::Canvas),
+            "custom" => Ok(Self::Custom),
+            v => Err(format!("unsupported TooltipRenderer: {}", v)),
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+pub enum OnExceed {
+    Error,
+    Sample,
+}
+impl FromStr for OnExceed {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "error" => Ok(Self::Error),
+            "sample" => Ok(Self::Sample),
+            v => Err(format!("unsupported OnExceed: {}", v)),
+        }
+    }
+}
+
+// values are remapped while data sets are parsed, e.g. to compare series of
+// different magnitudes
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ValueTransform {
+    None,
+    // indexed to the first value, which becomes 100
+    Percent,
+}
+impl FromStr for ValueTransform {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "percent" => Ok(Self::Percent),
+            v => Err(format!("unsupported ValueTransform: {}", v)),
+        }
+    }
+}
+impl ValueTransform {
+    pub fn get_suffix(&self) -> &'static str {
+        match self {
+            Self::None => "",
+            Self::Percent => "%",
+        }
+    }
+}
+
+pub enum AnnotationAxis {
+    Coord,
+    Value,
+}
+impl FromStr for AnnotationAxis {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "coord" => Ok(Self::Coord),
+            "value" => Ok(Self::Value),
+            v => Err(format!("unsupported AnnotationAxis: {}", v)),
+        }
+    }
+}
+
+pub enum AnnotationKind {
+    // e.g. a threshold
+    Line(f64),
+    // e.g. a target range or an event window, from & to
+    Band(f64, f64),
+}
+
+pub struct Annotation {
+    pub axis: AnnotationAxis,
+    pub kind: AnnotationKind,
+    pub rgb: (u8, u8, u8),
+    pub label: Option<String>,
+}
+
+// a notable point, e.g. a deploy or a peak
+pub struct Marker {
+    pub coord: f64,
+    pub value: f64,
+    pub rgb: (u8, u8, u8),
+    pub label: Option<String>,
+}
+
+pub enum ValueScale {
+    Auto,
+    Linear,
+    Log,
+}
+impl FromStr for ValueScale {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "linear" => Ok(Self::Linear),
+            "log" => Ok(Self::Log),
+            v => Err(format!("unsupported ValueScale: {}", v)),
+        }
+    }
+}
+
+pub enum TooltipMatch {
+    Coord,
+    Nearest,
+}
+impl FromStr for TooltipMatch {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "coord" => Ok(Self::Coord),
+            "nearest" => Ok(Self::Nearest),
+            v => Err(format!("unsupported TooltipMatch: {}", v)),
+        }
+    }
+}
+
+pub enum TooltipSort {
+    None,
+    ValueDesc,
+    ValueAsc,
+}
+impl FromStr for TooltipSort {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "valueDesc" => Ok(Self::ValueDesc),
+            "valueAsc" => Ok(Self::ValueAsc),
+            v => Err(format!("unsupported TooltipSort: {}", v)),
+        }
+    }
+}
+impl TooltipSort {
+    // stable, so equal values keep the data set order
+    pub fn sort<T, F>(&self, items: &mut [T], get_value: F)
+    where
+        F: Fn(&T) -> f64,
+    {
+        match self {
+            Self::None => {}
+            Self::ValueDesc => {
+                items.sort_by(|a, b| get_value(b).partial_cmp(&get_value(a)).unwrap())
+            }
+            Self::ValueAsc => {
+                items.sort_by(|a, b| get_value(a).partial_cmp(&get_value(b)).unwrap())
+            }
+        }
+    }
+}
+
+pub enum LegendLayout {
+    Flow,
+    Columns,
+}
+impl FromStr for LegendLayout {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "flow" => Ok(Self::Flow),
+            "columns" => Ok(Self::Columns),
+            v => Err(format!("unsupported LegendLayout: {}", v)),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum ChartMode {
+    Normal,
+    // just the lines, filling the whole canvas
+    Sparkline,
+}
+impl FromStr for ChartMode {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "normal" => Ok(Self::Normal),
+            "sparkline" => Ok(Self::Sparkline),
+            v => Err(format!("unsupported ChartMode: {}", v)),
+        }
+    }
+}
+
+pub enum LegendPosition {
+    Top,
+    Bottom,
+    Left,
+    Right,
+    Hidden,
+}
+impl FromStr for LegendPosition {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "top" => Ok(Self::Top),
+            "bottom" => Ok(Self::Bottom),
+            "left" => Ok(Self::Left),
+            "right" => Ok(Self::Right),
+            "hidden" => Ok(Self::Hidden),
+            v => Err(format!("unsupported LegendPosition: {}", v)),
+        }
+    }
+}
+
+pub enum PanAxis {
+    X,
+    Y,
+    Both,
+}
+impl FromStr for PanAxis {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "x" => Ok(Self::X),
+            "y" => Ok(Self::Y),
+            "both" => Ok(Self::Both),
+            v => Err(format!("unsupported PanAxis: {}", v)),
+        }
+    }
+}
+impl PanAxis {
+    // drops the movement along the locked axis of a drag started at down_pos
+    pub fn lock_pos(&self, pos: &ScreenPos, down_pos: &ScreenPos) -> ScreenPos {
+        match self {
+            Self::X => ScreenPos(pos.0, down_pos.1),
+            Self::Y => ScreenPos(down_pos.0, pos.1),
+            Self::Both => pos.clone(),
+        }
+    }
+}
+
+pub struct ChartConfig {
+    pub font_standard: String,
+    pub font_monospace: String,
+    pub font_size_small: Size,
+    pub font_size_normal: Size,
+    pub font_size_large: Size,
+    pub font_width_coeff: f64,
+    pub line_width: Size,
+    pub circle_diameter: Size,
+    pub color_grid: (u8, u8, u8),
+    pub color_tick: (u8, u8, u8),
+    pub color_camera_grip: (u8, u8, u8, f64),
+    pub color_preview_overlay: (u8, u8, u8, f64),
+    pub color_preview_hint: (u8, u8, u8, f64),
+    pub color_tooltip: (u8, u8, u8, f64),
+    pub color_tooltip_font: (u8, u8, u8, f64),
+    pub sort_data_sets_by: DataSetSorting,
+    pub layout_content_height: f64,
+    pub layout_preview_height: f64,
+    pub layout_legend_height: f64,
+    pub color_palette: Vec<(u8, u8, u8)>,
+    pub us_long_press: f64,
+    pub us_double_click: f64,
+    pub auto_log_scale_threshold: f64,
+    pub exp_fmt_significant_digits: usize,
+    pub coord_format: Option<String>,
+    pub value_format: Option<String>,
+    pub animate: Option<bool>,
+    pub group_separator: String,
+    pub decimal_separator: String,
+    pub preview_grip_outline: bool,
+    pub number_abbreviations: NumberAbbreviations,
+    pub hidden_ghost_alpha: f64,
+    pub avoid_tick_label_collisions: bool,
+    pub legend_hover_preview: bool,
+    pub baseline: Option<f64>,
+    pub intl_locale: Option<String>,
+    pub intl_date_format: Option<IntlOptions>,
+    pub drag_threshold: f64,
+    pub decimation_target_points: Option<usize>,
+    pub show_watermark: bool,
+    pub hint_zoom_out: String,
+    pub hint_zoom_in: String,
+    pub hint_hidden: String,
+    pub connect_gaps: bool,
+    pub rtl: bool,
+    pub update_zoom_policy: UpdateZoomPolicy,
+    pub pan_axis: PanAxis,
+    pub context_ghost: bool,
+    pub zero_tick: bool,
+    pub tooltip_delay_ms: f64,
+    pub decimation_mode: DecimationMode,
+    pub tooltip_mode: TooltipMode,
+    pub tooltip_position: TooltipPosition,
+    pub tooltip_renderer: TooltipRenderer,
+    pub selection_duration: bool,
+    pub tooltip_sort: TooltipSort,
+    pub tooltip_match: TooltipMatch,
+    pub value_scale: ValueScale,
+    pub tooltip_max_width: Option<Size>,
+    pub interpolate_tooltip: bool,
+    pub min_tick_period: Option<f64>,
+    pub preview_tooltip: bool,
+    pub adaptive_quality: bool,
+    pub max_points_per_series: Option<usize>,
+    pub on_exceed: OnExceed,
+    pub legend_layout: LegendLayout,
+    pub legend_bulk_toggles: bool,
+    pub hint_show_all: String,
+    pub hint_hide_all: String,
+    pub hint_all_hidden: String,
+    pub hint_no_data: String,
+    pub title_font_size: Size,
+    pub title_color: (u8, u8, u8),
+    pub legend_position: LegendPosition,
+    pub show_preview: bool,
+    pub mode: ChartMode,
+    pub animation_duration_us: f64,
+    pub animation_easing: Easing,
+    // resolved from the three above, see sync_animation
+    pub animation: Animation,
+    pub value_transform: ValueTransform,
+    // tick labels are spaced by this many label sizes, higher is sparser
+    pub coord_ticks_duty_factor: f64,
+    pub value_ticks_duty_factor: f64,
+    pub show_grid_x: bool,
+    pub show_grid_y: bool,
+    pub show_ticks_x: bool,
+    pub show_ticks_y: bool,
+    pub zero_line: bool,
+    pub begin_at_zero: bool,
+    // fraction of the value range added above & below it
+    pub value_padding: f64,
+    pub spike_line: bool,
+    // the log value scale puts its ticks at powers of it
+    pub log_base: f64,
+}
+impl Default for ChartConfig {
+    fn default() -> Self {
+        Self {
+            font_standard: "system-ui".to_string(),
+            font_monospace: "monospace".to_string(),
+            font_size_small: Size::TextLine {
+                font_size: 10.0,
+                columns: 1.0,
+            },
+            font_size_normal: Size::TextLine {
+                font_size: 12.0,
+                columns: 1.0,
             },
             font_size_large: Size::TextLine {
-                font_size: get_f64_by_str_key(raw_config, "fontSizeLarge", &|| {
-                    "fontSizeLarge".to_string()
+                font_size: 14.0,
+                columns: 1.0,
+            },
+            font_width_coeff: 0.65,
+            line_width: Size::Px(1.5),
+            circle_diameter: Size::Px(4.0),
+            color_grid: (237, 237, 237),
+            color_tick: (142, 142, 142),
+            color_camera_grip: (0, 0, 255, 0.15),
+            color_preview_overlay: (0, 0, 0, 0.4),
+            color_preview_hint: (255, 255, 255, 1.0),
+            color_tooltip: (255, 255, 255, 1.0),
+            color_tooltip_font: (0, 0, 0, 1.0),
+            sort_data_sets_by: DataSetSorting::MedianDesc,
+            layout_content_height: 5.0 / 7.5,
+            layout_preview_height: 1.0 / 7.5,
+            layout_legend_height: 1.5 / 7.5,
+            color_palette: vec![(75, 216, 100), (254, 60, 47), (147, 12, 249)],
+            us_long_press: 500000.0,
+            us_double_click: 300000.0,
+            auto_log_scale_threshold: 15.0,
+            exp_fmt_significant_digits: 5,
+            coord_format: None,
+            value_format: None,
+            animate: None,
+            group_separator: ",".to_string(),
+            decimal_separator: ".".to_string(),
+            preview_grip_outline: false,
+            number_abbreviations: NumberAbbreviations::ShortScale,
+            hidden_ghost_alpha: 0.0,
+            avoid_tick_label_collisions: true,
+            legend_hover_preview: false,
+            baseline: None,
+            intl_locale: None,
+            intl_date_format: None,
+            drag_threshold: 3.0,
+            decimation_target_points: None,
+            show_watermark: true,
+            hint_zoom_out: "Click to zoom out".to_string(),
+            hint_zoom_in: "Drag here or above to zoom in".to_string(),
+            hint_hidden: "{} hidden".to_string(),
+            connect_gaps: false,
+            rtl: false,
+            update_zoom_policy: UpdateZoomPolicy::Reset,
+            pan_axis: PanAxis::Both,
+            context_ghost: false,
+            zero_tick: false,
+            tooltip_delay_ms: 0.0,
+            decimation_mode: DecimationMode::Lttb,
+            tooltip_mode: TooltipMode::Full,
+            tooltip_position: TooltipPosition::Follow,
+            tooltip_renderer: TooltipRenderer::Canvas,
+            selection_duration: true,
+            tooltip_sort: TooltipSort::None,
+            tooltip_match: TooltipMatch::Coord,
+            value_scale: ValueScale::Auto,
+            tooltip_max_width: None,
+            interpolate_tooltip: false,
+            min_tick_period: None,
+            preview_tooltip: false,
+            adaptive_quality: false,
+            max_points_per_series: None,
+            on_exceed: OnExceed::Error,
+            legend_layout: LegendLayout::Flow,
+            legend_bulk_toggles: false,
+            hint_show_all: "All".to_string(),
+            hint_hide_all: "None".to_string(),
+            hint_all_hidden: "All series are hidden".to_string(),
+            hint_no_data: "No data".to_string(),
+            title_font_size: Size::TextLine {
+                font_size: 14.0,
+                columns: 1.0,
+            },
+            title_color: (142, 142, 142),
+            legend_position: LegendPosition::Bottom,
+            show_preview: true,
+            mode: ChartMode::Normal,
+            animation_duration_us: DEFAULT_ANIMATION_DURATION_US,
+            animation_easing: Easing::Smooth,
+            animation: Animation::default(),
+            value_transform: ValueTransform::None,
+            coord_ticks_duty_factor: 1.5,
+            value_ticks_duty_factor: 5.0,
+            show_grid_x: true,
+            show_grid_y: true,
+            show_ticks_x: true,
+            show_ticks_y: true,
+            zero_line: false,
+            begin_at_zero: false,
+            value_padding: 0.0,
+            spike_line: false,
+            log_base: 10.0,
+        }
+    }
+}
+impl ChartConfig {
+    // animate left out follows the client's reduced motion preference
+    pub fn sync_animation(&mut self, prefers_reduced_motion: bool) {
+        self.animation = Animation::new(
+            self.animate.unwrap_or(!prefers_reduced_motion),
+            self.animation_duration_us,
+            self.animation_easing,
+        );
+    }
+}
+#[cfg(feature = "wasm")]
+impl ChartConfig {
+    pub fn from_raw(raw_config: &JsValue) -> Result<Self, ChartError> {
+        // options left out fall back to these
+        let defaults = ChartConfig::default();
+        let layout_content_height = get_f64_by_str_key(raw_config, "layoutContentHeight", &|| {
+            "layoutContentHeight".to_string()
+        })?;
+        let layout_preview_height = get_f64_by_str_key(raw_config, "layoutPreviewHeight", &|| {
+            "layoutPreviewHeight".to_string()
+        })?;
+        let layout_legend_height = get_f64_by_str_key(raw_config, "layoutLegendHeight", &|| {
+            "layoutLegendHeight".to_string()
+        })?;
+        let mode = match get_optional_string_by_str_key(raw_config, "mode", &|| "mode".to_string())?
+        {
+            Some(mode) => ChartMode::from_str(mode.as_str()).map_err(at_key("mode"))?,
+            None => defaults.mode,
+        };
+        let show_preview =
+            get_optional_bool_by_str_key(raw_config, "showPreview", &|| "showPreview".to_string())?
+                .unwrap_or(defaults.show_preview)
+                && mode == ChartMode::Normal;
+        // the content takes the space of a hidden preview
+        let layout_preview_height = if show_preview {
+            layout_preview_height
+        } else {
+            0.0
+        };
+        let total_height_norm =
+            (layout_content_height + layout_preview_height + layout_legend_height).recip();
+        let font_size_large =
+            get_f64_by_str_key(raw_config, "fontSizeLarge", &|| "fontSizeLarge".to_string())?;
+        let color_tick = get_rgb_by_str_key(raw_config, "colorTick", &|| "colorTick".to_string())?;
+
+        let color_palette: Result<Vec<(u8, u8, u8)>, ChartError> =
+            get_array_by_str_key(raw_config, "colorPalette", &|| "colorPalette".to_string())?
+                .iter()
+                .enumerate()
+                .map(|(index, item)| js_value_to_rgb(&item, &|| format!("colorPalette.{}", index)))
+                .collect();
+
+        Ok(Self {
+            font_standard: get_string_by_str_key(raw_config, "fontStandard", &|| {
+                "fontStandard".to_string()
+            })?,
+            font_monospace: get_string_by_str_key(raw_config, "fontMonospace", &|| {
+                "fontMonospace".to_string()
+            })?,
+            font_size_small: Size::TextLine {
+                font_size: get_f64_by_str_key(raw_config, "fontSizeSmall", &|| {
+                    "fontSizeSmall".to_string()
                 })?,
                 columns: 1.0,
             },
+            font_size_normal: Size::TextLine {
+                font_size: get_f64_by_str_key(raw_config, "fontSizeNormal", &|| {
+                    "fontSizeNormal".to_string()
+                })?,
+                columns: 1.0,
+            },
+            font_size_large: Size::TextLine {
+                font_size: font_size_large,
+                columns: 1.0,
+            },
             font_width_coeff: get_f64_by_str_key(raw_config, "fontWidthCoeff", &|| {
                 "fontWidthCoeff".to_string()
             })?,
@@ -658,7 +1786,7 @@ impl ChartConfig {
                     * 2.0,
             ),
             color_grid: get_rgb_by_str_key(raw_config, "colorGrid", &|| "colorGrid".to_string())?,
-            color_tick: get_rgb_by_str_key(raw_config, "colorTick", &|| "colorTick".to_string())?,
+            color_tick,
             color_camera_grip: get_rgba_by_str_key(raw_config, "colorCameraGrip", &|| {
                 "colorCameraGrip".to_string()
             })?,
@@ -678,7 +1806,8 @@ impl ChartConfig {
                 raw_config,
                 "sortDataSetsBy",
                 &|| "sortDataSetsBy".to_string(),
-            )?)?,
+            )?)
+            .map_err(at_key("sortDataSetsBy"))?,
             layout_content_height: layout_content_height * total_height_norm,
             layout_preview_height: layout_preview_height * total_height_norm,
             layout_legend_height: layout_legend_height * total_height_norm,
@@ -686,6 +1815,10 @@ impl ChartConfig {
             us_long_press: get_f64_by_str_key(raw_config, "msLongPress", &|| {
                 "msLongPress".to_string()
             })? * 1000.0,
+            us_double_click: get_optional_f64_by_str_key(raw_config, "msDoubleClick", &|| {
+                "msDoubleClick".to_string()
+            })?
+            .map_or(defaults.us_double_click, |ms| ms * 1000.0),
             auto_log_scale_threshold: get_f64_by_str_key(
                 raw_config,
                 "autoLogScaleThreshold",
@@ -696,29 +1829,486 @@ impl ChartConfig {
                 "expFmtSignificantDigits",
                 &|| "expFmtSignificantDigits".to_string(),
             )? as usize,
+            coord_format: get_optional_string_by_str_key(raw_config, "coordFormat", &|| {
+                "coordFormat".to_string()
+            })?,
+            value_format: get_optional_string_by_str_key(raw_config, "valueFormat", &|| {
+                "valueFormat".to_string()
+            })?,
+            animate: get_optional_bool_by_str_key(raw_config, "animate", &|| {
+                "animate".to_string()
+            })?,
+            group_separator: get_optional_string_by_str_key(raw_config, "groupSeparator", &|| {
+                "groupSeparator".to_string()
+            })?
+            .unwrap_or(defaults.group_separator),
+            decimal_separator: get_optional_string_by_str_key(
+                raw_config,
+                "decimalSeparator",
+                &|| "decimalSeparator".to_string(),
+            )?
+            .unwrap_or(defaults.decimal_separator),
+            preview_grip_outline: get_optional_bool_by_str_key(
+                raw_config,
+                "previewGripOutline",
+                &|| "previewGripOutline".to_string(),
+            )?
+            .unwrap_or(defaults.preview_grip_outline),
+            number_abbreviations: match get_optional_string_by_str_key(
+                raw_config,
+                "numberAbbreviations",
+                &|| "numberAbbreviations".to_string(),
+            )? {
+                Some(value) => NumberAbbreviations::from_str(value.as_str())
+                    .map_err(at_key("numberAbbreviations"))?,
+                None => defaults.number_abbreviations,
+            },
+            hidden_ghost_alpha: get_optional_f64_by_str_key(
+                raw_config,
+                "hiddenGhostAlpha",
+                &|| "hiddenGhostAlpha".to_string(),
+            )?
+            .unwrap_or(defaults.hidden_ghost_alpha),
+            avoid_tick_label_collisions: get_optional_bool_by_str_key(
+                raw_config,
+                "avoidTickLabelCollisions",
+                &|| "avoidTickLabelCollisions".to_string(),
+            )?
+            .unwrap_or(defaults.avoid_tick_label_collisions),
+            legend_hover_preview: get_optional_bool_by_str_key(
+                raw_config,
+                "legendHoverPreview",
+                &|| "legendHoverPreview".to_string(),
+            )?
+            .unwrap_or(defaults.legend_hover_preview),
+            baseline: get_optional_f64_by_str_key(raw_config, "baseline", &|| {
+                "baseline".to_string()
+            })?,
+            intl_locale: get_optional_string_by_str_key(raw_config, "intlLocale", &|| {
+                "intlLocale".to_string()
+            })?,
+            intl_date_format: match get_optional_by_str_key(raw_config, "intlDateFormat", &|| {
+                "intlDateFormat".to_string()
+            })? {
+                Some(value) if !value.is_object() => {
+                    return Err(ChartError::at(
+                        "intlDateFormat".to_string(),
+                        "expected an object",
+                    ));
+                }
+                value => value,
+            },
+            drag_threshold: get_optional_f64_by_str_key(raw_config, "dragThreshold", &|| {
+                "dragThreshold".to_string()
+            })?
+            .unwrap_or(defaults.drag_threshold),
+            decimation_target_points: get_optional_f64_by_str_key(
+                raw_config,
+                "decimationTargetPoints",
+                &|| "decimationTargetPoints".to_string(),
+            )?
+            .map(|v| v as usize),
+            show_watermark: get_optional_bool_by_str_key(raw_config, "showWatermark", &|| {
+                "showWatermark".to_string()
+            })?
+            .unwrap_or(defaults.show_watermark),
+            hint_zoom_out: get_optional_string_by_str_key(raw_config, "hintZoomOut", &|| {
+                "hintZoomOut".to_string()
+            })?
+            .unwrap_or(defaults.hint_zoom_out),
+            hint_zoom_in: get_optional_string_by_str_key(raw_config, "hintZoomIn", &|| {
+                "hintZoomIn".to_string()
+            })?
+            .unwrap_or(defaults.hint_zoom_in),
+            hint_hidden: get_optional_string_by_str_key(raw_config, "hintHidden", &|| {
+                "hintHidden".to_string()
+            })?
+            .unwrap_or(defaults.hint_hidden),
+            connect_gaps: get_optional_bool_by_str_key(raw_config, "connectGaps", &|| {
+                "connectGaps".to_string()
+            })?
+            .unwrap_or(defaults.connect_gaps),
+            rtl: get_optional_bool_by_str_key(raw_config, "rtl", &|| "rtl".to_string())?
+                .unwrap_or(defaults.rtl),
+            update_zoom_policy: match get_optional_string_by_str_key(
+                raw_config,
+                "updateZoomPolicy",
+                &|| "updateZoomPolicy".to_string(),
+            )? {
+                Some(policy) => UpdateZoomPolicy::from_str(policy.as_str())
+                    .map_err(at_key("updateZoomPolicy"))?,
+                None => defaults.update_zoom_policy,
+            },
+            pan_axis: match get_optional_string_by_str_key(raw_config, "panAxis", &|| {
+                "panAxis".to_string()
+            })? {
+                Some(pan_axis) => {
+                    PanAxis::from_str(pan_axis.as_str()).map_err(at_key("panAxis"))?
+                }
+                None => defaults.pan_axis,
+            },
+            context_ghost: get_optional_bool_by_str_key(raw_config, "contextGhost", &|| {
+                "contextGhost".to_string()
+            })?
+            .unwrap_or(defaults.context_ghost),
+            zero_tick: get_optional_bool_by_str_key(raw_config, "zeroTick", &|| {
+                "zeroTick".to_string()
+            })?
+            .unwrap_or(defaults.zero_tick),
+            tooltip_delay_ms: get_optional_f64_by_str_key(raw_config, "tooltipDelayMs", &|| {
+                "tooltipDelayMs".to_string()
+            })?
+            .unwrap_or(defaults.tooltip_delay_ms),
+            decimation_mode: match get_optional_string_by_str_key(
+                raw_config,
+                "decimationMode",
+                &|| "decimationMode".to_string(),
+            )? {
+                Some(mode) => {
+                    DecimationMode::from_str(mode.as_str()).map_err(at_key("decimationMode"))?
+                }
+                None => defaults.decimation_mode,
+            },
+            tooltip_mode: match get_optional_string_by_str_key(raw_config, "tooltipMode", &|| {
+                "tooltipMode".to_string()
+            })? {
+                Some(mode) => {
+                    TooltipMode::from_str(mode.as_str()).map_err(at_key("tooltipMode"))?
+                }
+                None => defaults.tooltip_mode,
+            },
+            tooltip_position: match get_optional_string_by_str_key(
+                raw_config,
+                "tooltipPosition",
+                &|| "tooltipPosition".to_string(),
+            )? {
+                Some(position) => TooltipPosition::from_str(position.as_str())
+                    .map_err(at_key("tooltipPosition"))?,
+                None => defaults.tooltip_position,
+            },
+            tooltip_renderer: match get_optional_string_by_str_key(
+                raw_config,
+                "tooltipRenderer",
+                &|| "tooltipRenderer".to_string(),
+            )? {
+                Some(renderer) => TooltipRenderer::from_str(renderer.as_str())
+                    .map_err(at_key("tooltipRenderer"))?,
+                None => defaults.tooltip_renderer,
+            },
+            selection_duration: get_optional_bool_by_str_key(
+                raw_config,
+                "selectionDuration",
+                &|| "selectionDuration".to_string(),
+            )?
+            .unwrap_or(defaults.selection_duration),
+            tooltip_sort: match get_optional_string_by_str_key(raw_config, "tooltipSort", &|| {
+                "tooltipSort".to_string()
+            })? {
+                Some(sort) => {
+                    TooltipSort::from_str(sort.as_str()).map_err(at_key("tooltipSort"))?
+                }
+                None => defaults.tooltip_sort,
+            },
+            tooltip_match: match get_optional_string_by_str_key(
+                raw_config,
+                "tooltipMatch",
+                &|| "tooltipMatch".to_string(),
+            )? {
+                Some(tooltip_match) => TooltipMatch::from_str(tooltip_match.as_str())
+                    .map_err(at_key("tooltipMatch"))?,
+                None => defaults.tooltip_match,
+            },
+            value_scale: match get_optional_string_by_str_key(raw_config, "valueScale", &|| {
+                "valueScale".to_string()
+            })? {
+                Some(value_scale) => {
+                    ValueScale::from_str(value_scale.as_str()).map_err(at_key("valueScale"))?
+                }
+                None => defaults.value_scale,
+            },
+            tooltip_max_width: get_optional_f64_by_str_key(raw_config, "tooltipMaxWidth", &|| {
+                "tooltipMaxWidth".to_string()
+            })?
+            .map(Size::Px),
+            interpolate_tooltip: get_optional_bool_by_str_key(
+                raw_config,
+                "interpolateTooltip",
+                &|| "interpolateTooltip".to_string(),
+            )?
+            .unwrap_or(defaults.interpolate_tooltip),
+            min_tick_period: get_optional_f64_by_str_key(raw_config, "minTickPeriod", &|| {
+                "minTickPeriod".to_string()
+            })?,
+            preview_tooltip: get_optional_bool_by_str_key(raw_config, "previewTooltip", &|| {
+                "previewTooltip".to_string()
+            })?
+            .unwrap_or(defaults.preview_tooltip),
+            adaptive_quality: get_optional_bool_by_str_key(raw_config, "adaptiveQuality", &|| {
+                "adaptiveQuality".to_string()
+            })?
+            .unwrap_or(defaults.adaptive_quality),
+            max_points_per_series: get_optional_f64_by_str_key(
+                raw_config,
+                "maxPointsPerSeries",
+                &|| "maxPointsPerSeries".to_string(),
+            )?
+            .map(|v| v.max(2.0) as usize),
+            on_exceed: match get_optional_string_by_str_key(raw_config, "onExceed", &|| {
+                "onExceed".to_string()
+            })? {
+                Some(on_exceed) => {
+                    OnExceed::from_str(on_exceed.as_str()).map_err(at_key("onExceed"))?
+                }
+                None => defaults.on_exceed,
+            },
+            legend_layout: match get_optional_string_by_str_key(
+                raw_config,
+                "legendLayout",
+                &|| "legendLayout".to_string(),
+            )? {
+                Some(legend_layout) => LegendLayout::from_str(legend_layout.as_str())
+                    .map_err(at_key("legendLayout"))?,
+                None => defaults.legend_layout,
+            },
+            legend_bulk_toggles: get_optional_bool_by_str_key(
+                raw_config,
+                "legendBulkToggles",
+                &|| "legendBulkToggles".to_string(),
+            )?
+            .unwrap_or(defaults.legend_bulk_toggles),
+            hint_show_all: get_optional_string_by_str_key(raw_config, "hintShowAll", &|| {
+                "hintShowAll".to_string()
+            })?
+            .unwrap_or(defaults.hint_show_all),
+            hint_hide_all: get_optional_string_by_str_key(raw_config, "hintHideAll", &|| {
+                "hintHideAll".to_string()
+            })?
+            .unwrap_or(defaults.hint_hide_all),
+            hint_all_hidden: get_optional_string_by_str_key(raw_config, "hintAllHidden", &|| {
+                "hintAllHidden".to_string()
+            })?
+            .unwrap_or(defaults.hint_all_hidden),
+            hint_no_data: get_optional_string_by_str_key(raw_config, "hintNoData", &|| {
+                "hintNoData".to_string()
+            })?
+            .unwrap_or(defaults.hint_no_data),
+            title_font_size: Size::TextLine {
+                font_size: get_optional_f64_by_str_key(raw_config, "titleFontSize", &|| {
+                    "titleFontSize".to_string()
+                })?
+                .unwrap_or(font_size_large),
+                columns: 1.0,
+            },
+            title_color: get_optional_by_str_key(raw_config, "titleColor", &|| {
+                "titleColor".to_string()
+            })?
+            .map(|value| js_value_to_rgb(&value, &|| "titleColor".to_string()))
+            .transpose()?
+            .unwrap_or(color_tick),
+            legend_position: match get_optional_string_by_str_key(
+                raw_config,
+                "legendPosition",
+                &|| "legendPosition".to_string(),
+            )? {
+                _ if mode == ChartMode::Sparkline => LegendPosition::Hidden,
+                Some(legend_position) => LegendPosition::from_str(legend_position.as_str())
+                    .map_err(at_key("legendPosition"))?,
+                None => defaults.legend_position,
+            },
+            show_preview,
+            mode,
+            animation_duration_us: get_optional_f64_by_str_key(
+                raw_config,
+                "animationDurationMs",
+                &|| "animationDurationMs".to_string(),
+            )?
+            .map(|ms| ms * 1000.0)
+            .unwrap_or(defaults.animation_duration_us),
+            animation_easing: match get_optional_string_by_str_key(
+                raw_config,
+                "animationEasing",
+                &|| "animationEasing".to_string(),
+            )? {
+                Some(easing) => {
+                    Easing::from_str(easing.as_str()).map_err(at_key("animationEasing"))?
+                }
+                None => defaults.animation_easing,
+            },
+            animation: defaults.animation,
+            value_transform: match get_optional_string_by_str_key(
+                raw_config,
+                "valueTransform",
+                &|| "valueTransform".to_string(),
+            )? {
+                Some(value_transform) => ValueTransform::from_str(value_transform.as_str())
+                    .map_err(at_key("valueTransform"))?,
+                None => defaults.value_transform,
+            },
+            coord_ticks_duty_factor: match get_optional_f64_by_str_key(
+                raw_config,
+                "coordTicksDutyFactor",
+                &|| "coordTicksDutyFactor".to_string(),
+            )? {
+                Some(factor) if factor <= 0.0 => {
+                    return Err(ChartError::at(
+                        "coordTicksDutyFactor".to_string(),
+                        "should be positive",
+                    ));
+                }
+                Some(factor) => factor,
+                None => defaults.coord_ticks_duty_factor,
+            },
+            value_ticks_duty_factor: match get_optional_f64_by_str_key(
+                raw_config,
+                "valueTicksDutyFactor",
+                &|| "valueTicksDutyFactor".to_string(),
+            )? {
+                Some(factor) if factor <= 0.0 => {
+                    return Err(ChartError::at(
+                        "valueTicksDutyFactor".to_string(),
+                        "should be positive",
+                    ));
+                }
+                Some(factor) => factor,
+                None => defaults.value_ticks_duty_factor,
+            },
+            show_grid_x: get_optional_bool_by_str_key(raw_config, "showGridX", &|| {
+                "showGridX".to_string()
+            })?
+            .unwrap_or(defaults.show_grid_x),
+            show_grid_y: get_optional_bool_by_str_key(raw_config, "showGridY", &|| {
+                "showGridY".to_string()
+            })?
+            .unwrap_or(defaults.show_grid_y),
+            show_ticks_x: get_optional_bool_by_str_key(raw_config, "showTicksX", &|| {
+                "showTicksX".to_string()
+            })?
+            .unwrap_or(defaults.show_ticks_x),
+            show_ticks_y: get_optional_bool_by_str_key(raw_config, "showTicksY", &|| {
+                "showTicksY".to_string()
+            })?
+            .unwrap_or(defaults.show_ticks_y),
+            zero_line: get_optional_bool_by_str_key(raw_config, "zeroLine", &|| {
+                "zeroLine".to_string()
+            })?
+            .unwrap_or(defaults.zero_line),
+            begin_at_zero: get_optional_bool_by_str_key(raw_config, "beginAtZero", &|| {
+                "beginAtZero".to_string()
+            })?
+            .unwrap_or(defaults.begin_at_zero),
+            value_padding: match get_optional_f64_by_str_key(raw_config, "valuePadding", &|| {
+                "valuePadding".to_string()
+            })? {
+                Some(padding) if padding < 0.0 => {
+                    return Err(ChartError::at(
+                        "valuePadding".to_string(),
+                        "should not be negative",
+                    ));
+                }
+                Some(padding) => padding,
+                None => defaults.value_padding,
+            },
+            spike_line: get_optional_bool_by_str_key(raw_config, "spikeLine", &|| {
+                "spikeLine".to_string()
+            })?
+            .unwrap_or(defaults.spike_line),
+            log_base: match get_optional_f64_by_str_key(raw_config, "logBase", &|| {
+                "logBase".to_string()
+            })? {
+                Some(base) if base <= 1.0 || !base.is_finite() => {
+                    return Err(ChartError::at(
+                        "logBase".to_string(),
+                        "should be greater than 1",
+                    ));
+                }
+                Some(base) => base,
+                None => defaults.log_base,
+            },
         })
     }
 }
 
+#[cfg(feature = "wasm")]
 pub struct ChartParams {
     pub selector: String,
     pub content: Content,
+    pub on_ready: Option<js_sys::Function>,
+    pub on_tooltip: Option<js_sys::Function>,
 }
 
+#[cfg(feature = "wasm")]
 impl ChartParams {
-    pub fn from(raw_params: &JsValue, chart_config: &ChartConfig) -> Result<Self, String> {
-        let content_name =
-            get_string_by_str_key(raw_params, "contentName", &|| "contentName".to_string()).ok();
+    pub fn from(raw_params: &JsValue, chart_config: &ChartConfig) -> Result<Self, ChartError> {
         let selector = get_string_by_str_key(raw_params, "selector", &|| "selector".to_string())?;
 
+        let tz_offset_minutes =
+            get_optional_f64_by_str_key(raw_params, "tzOffsetMinutes", &|| {
+                "tzOffsetMinutes".to_string()
+            })?;
         let coord_type = DataType::from_str(
             get_string_by_str_key(raw_params, "coordType", &|| "coordType".to_string())?.as_str(),
-        )?;
+        )
+        .map_err(at_key("coordType"))?
+        .with_tz_offset_minutes(tz_offset_minutes)
+        .map_err(at_key("tzOffsetMinutes"))?;
         let value_type = DataType::from_str(
             get_string_by_str_key(raw_params, "valueType", &|| "valueType".to_string())?.as_str(),
-        )?;
+        )
+        .map_err(at_key("valueType"))?
+        .with_tz_offset_minutes(tz_offset_minutes)
+        .map_err(at_key("tzOffsetMinutes"))?;
+
+        let on_ready = get_optional_by_str_key(raw_params, "onReady", &|| "onReady".to_string())?
+            .map(|value| {
+                value
+                    .dyn_into::<js_sys::Function>()
+                    .map_err(|_| ChartError::at("onReady".to_string(), "not a function"))
+            })
+            .transpose()?;
+        let on_tooltip =
+            get_optional_by_str_key(raw_params, "onTooltip", &|| "onTooltip".to_string())?
+                .map(|value| {
+                    value
+                        .dyn_into::<js_sys::Function>()
+                        .map_err(|_| ChartError::at("onTooltip".to_string(), "not a function"))
+                })
+                .transpose()?;
+        // nothing would show the tooltips otherwise
+        if chart_config.tooltip_renderer == TooltipRenderer::Custom && on_tooltip.is_none() {
+            return Err(ChartError::at(
+                "tooltipRenderer".to_string(),
+                "\"custom\" requires an onTooltip callback",
+            ));
+        }
 
-        let mut content = Content::new(content_name, coord_type, value_type, chart_config);
+        let content = Content::from_raw(raw_params, coord_type, value_type, chart_config)?;
+        Ok(ChartParams {
+            selector,
+            content,
+            on_ready,
+            on_tooltip,
+        })
+    }
+}
+#[cfg(feature = "wasm")]
+impl Content {
+    pub fn from_raw(
+        raw_params: &JsValue,
+        coord_type: DataType,
+        value_type: DataType,
+        chart_config: &ChartConfig,
+    ) -> Result<Self, ChartError> {
+        let content_name =
+            get_string_by_str_key(raw_params, "contentName", &|| "contentName".to_string()).ok();
+        let mut content = Content::new(content_name, coord_type, value_type, chart_config)?;
+        content.coord_axis_title =
+            get_optional_string_by_str_key(raw_params, "coordAxisTitle", &|| {
+                "coordAxisTitle".to_string()
+            })?;
+        content.value_axis_title =
+            get_optional_string_by_str_key(raw_params, "valueAxisTitle", &|| {
+                "valueAxisTitle".to_string()
+            })?;
 
         let color_palette = &chart_config.color_palette;
         let colors_number = color_palette.len();
@@ -726,7 +2316,7 @@ impl ChartParams {
         for (index, raw_data_set) in
             get_by_str_key(&raw_params, "dataSets", &|| "dataSets".to_string())?
                 .dyn_into::<js_sys::Array>()
-                .map_err(|_| "dataSets is not an array".to_string())?
+                .map_err(|_| ChartError::at("dataSets".to_string(), "not an array"))?
                 .iter()
                 .enumerate()
         {
@@ -737,21 +2327,318 @@ impl ChartParams {
             let coords = get_array_by_str_key(&raw_data_set, "coords", &|| {
                 format!("dataSets[{}].coords", index)
             })?;
-            let coords = parse_js_values(coords, coord_type, &|| {
+            let coords = parse_js_values(coords, coord_type, false, &|| {
                 format!("dataSets[{}].coords", index)
             })?;
             let values = get_array_by_str_key(&raw_data_set, "values", &|| {
                 format!("dataSets[{}].values", index)
             })?;
-            let values = parse_js_values(values, value_type, &|| {
+            let values = parse_js_values(values, value_type, true, &|| {
                 format!("dataSets[{}].values", index)
             })?;
+            // data bound asynchronously may be missing yet
+            if coords.is_empty() && values.is_empty() {
+                continue;
+            }
+
+            let lows = get_optional_by_str_key(&raw_data_set, "valuesLow", &|| {
+                format!("dataSets[{}].valuesLow", index)
+            })?;
+            let highs = get_optional_by_str_key(&raw_data_set, "valuesHigh", &|| {
+                format!("dataSets[{}].valuesHigh", index)
+            })?;
+            let parse_bounds = |raw: JsValue, key: &str| -> Result<Vec<f64>, ChartError> {
+                let path = || format!("dataSets[{}].{}", index, key);
+                let bounds = parse_js_values(
+                    raw.dyn_into::<js_sys::Array>()
+                        .map_err(|_| ChartError::at(path(), "not an array"))?,
+                    value_type,
+                    true,
+                    &path,
+                )?;
+                if bounds.len() != coords.len() {
+                    return Err(ChartError::at(
+                        path(),
+                        format!("should have as many items as coords: {}", coords.len()).as_str(),
+                    ));
+                }
+                Ok(bounds)
+            };
+            let mut band = match (lows, highs) {
+                (Some(lows), Some(highs)) => Some((
+                    parse_bounds(lows, "valuesLow")?,
+                    parse_bounds(highs, "valuesHigh")?,
+                )),
+                (Some(_), None) => {
+                    return Err(ChartError::at(
+                        format!("dataSets[{}].valuesHigh", index),
+                        "is required along with valuesLow",
+                    ));
+                }
+                (None, Some(_)) => {
+                    return Err(ChartError::at(
+                        format!("dataSets[{}].valuesLow", index),
+                        "is required along with valuesHigh",
+                    ));
+                }
+                (None, None) => None,
+            };
+
+            let coord_offset = get_optional_f64_by_str_key(&raw_data_set, "coordOffset", &|| {
+                format!("dataSets[{}].coordOffset", index)
+            })?
+            .unwrap_or(0.0);
+
+            // buckets are aligned to shifted coords
+            let bucket = get_optional_f64_by_str_key(&raw_data_set, "bucket", &|| {
+                format!("dataSets[{}].bucket", index)
+            })?;
+            let (coords, values, coord_offset) = match bucket {
+                Some(bucket) if bucket <= 0.0 => {
+                    return Err(ChartError::at(
+                        format!("dataSets[{}].bucket", index),
+                        "should be positive",
+                    ));
+                }
+                Some(bucket) if coords.len() == values.len() => {
+                    let aggregation =
+                        match get_optional_string_by_str_key(&raw_data_set, "agg", &|| {
+                            format!("dataSets[{}].agg", index)
+                        })? {
+                            Some(value) => Aggregation::from_str(value.as_str()).map_err(|e| {
+                                ChartError::at(format!("dataSets[{}].agg", index), e.as_str())
+                            })?,
+                            None => Aggregation::Mean,
+                        };
+                    let shifted_coords: Vec<f64> =
+                        coords.iter().map(|coord| coord + coord_offset).collect();
+                    band = band.map(|(lows, highs)| {
+                        (
+                            aggregation.apply(&shifted_coords, &lows, bucket).1,
+                            aggregation.apply(&shifted_coords, &highs, bucket).1,
+                        )
+                    });
+                    let (coords, values) = aggregation.apply(&shifted_coords, &values, bucket);
+                    (coords, values, 0.0)
+                }
+                // mismatching lengths are reported while adding the data set
+                _ => (coords, values, coord_offset),
+            };
+
+            let color = match get_optional_by_str_key(&raw_data_set, "color", &|| {
+                format!("dataSets[{}].color", index)
+            })? {
+                Some(raw_color) => {
+                    js_value_to_rgb(&raw_color, &|| format!("dataSets[{}].color", index))?
+                }
+                None => color_palette[index % colors_number],
+            };
+
+            let max_gap = get_optional_f64_by_str_key(&raw_data_set, "maxGap", &|| {
+                format!("dataSets[{}].maxGap", index)
+            })?;
+
+            let area = get_optional_bool_by_str_key(&raw_data_set, "area", &|| {
+                format!("dataSets[{}].area", index)
+            })?
+            .unwrap_or(false);
+
+            let interpolation =
+                match get_optional_string_by_str_key(&raw_data_set, "interpolation", &|| {
+                    format!("dataSets[{}].interpolation", index)
+                })? {
+                    Some(value) => Interpolation::from_str(value.as_str()).map_err(|e| {
+                        ChartError::at(format!("dataSets[{}].interpolation", index), e.as_str())
+                    })?,
+                    None => Interpolation::Linear,
+                };
 
-            let color = color_palette[index % colors_number];
+            let band_coords: Vec<f64> = match band {
+                Some(_) => coords.iter().map(|coord| coord + coord_offset).collect(),
+                None => Vec::new(),
+            };
+            let data_set = content
+                .parse_and_add_data_set(data_set_name.as_str(), coords, values, color, coord_offset)
+                .map_err(|e| ChartError::at(format!("dataSets[{}]", index), e.as_str()))?;
+            data_set.max_gap = max_gap;
+            data_set.area = area;
+            data_set.interpolation = interpolation;
+            data_set.decimation_mode = chart_config.decimation_mode;
+            if let Some(target_points) = chart_config.decimation_target_points {
+                data_set.precompute_decimation(target_points);
+            }
+            if let Some((lows, highs)) = band {
+                data_set.set_band(&band_coords, &lows, &highs);
+                let (value_min, value_max) = (data_set.meta.min, data_set.meta.max);
+                content.global_value_min = content.global_value_min.min(value_min);
+                content.global_value_max = content.global_value_max.max(value_max);
+            }
+        }
+        if let Some(raw_derived_data_sets) =
+            get_optional_by_str_key(raw_params, "derivedDataSets", &|| {
+                "derivedDataSets".to_string()
+            })?
+        {
+            let colors_offset = content.data_sets.len();
+            for (index, raw_derived_data_set) in raw_derived_data_sets
+                .dyn_into::<js_sys::Array>()
+                .map_err(|_| ChartError::at("derivedDataSets".to_string(), "not an array"))?
+                .iter()
+                .enumerate()
+            {
+                let data_set_name = get_string_by_str_key(&raw_derived_data_set, "name", &|| {
+                    format!("derivedDataSets[{}].name", index)
+                })?;
+                let from = get_string_by_str_key(&raw_derived_data_set, "from", &|| {
+                    format!("derivedDataSets[{}].from", index)
+                })?;
+                let window = get_f64_by_str_key(&raw_derived_data_set, "window", &|| {
+                    format!("derivedDataSets[{}].window", index)
+                })?;
+                if window < 1.0 {
+                    return Err(ChartError::at(
+                        format!("derivedDataSets[{}].window", index),
+                        "should be at least 1",
+                    ));
+                }
+                let moving_average =
+                    match get_optional_string_by_str_key(&raw_derived_data_set, "type", &|| {
+                        format!("derivedDataSets[{}].type", index)
+                    })? {
+                        Some(value) => MovingAverage::from_str(value.as_str()).map_err(|e| {
+                            ChartError::at(format!("derivedDataSets[{}].type", index), e.as_str())
+                        })?,
+                        None => MovingAverage::Sma,
+                    };
+                let source = content
+                    .data_sets
+                    .iter()
+                    .find(|data_set| data_set.name == from)
+                    .ok_or_else(|| {
+                        ChartError::at(
+                            format!("derivedDataSets[{}].from", index),
+                            format!("data set not found: {}", from).as_str(),
+                        )
+                    })?;
+                let coords: Vec<f64> = source.data_points.iter().map(|p| p.coord).collect();
+                let values = moving_average.compute(&source.data_points, window as usize);
 
-            content.parse_and_add_data_set(data_set_name.as_str(), coords, values, color)?;
+                let color = match get_optional_by_str_key(&raw_derived_data_set, "color", &|| {
+                    format!("derivedDataSets[{}].color", index)
+                })? {
+                    Some(raw_color) => js_value_to_rgb(&raw_color, &|| {
+                        format!("derivedDataSets[{}].color", index)
+                    })?,
+                    None => color_palette[(colors_offset + index) % colors_number],
+                };
+                let data_set = content
+                    .parse_and_add_data_set(data_set_name.as_str(), coords, values, color, 0.0)
+                    .map_err(|e| {
+                        ChartError::at(format!("derivedDataSets[{}]", index), e.as_str())
+                    })?;
+                data_set.decimation_mode = chart_config.decimation_mode;
+                if let Some(target_points) = chart_config.decimation_target_points {
+                    data_set.precompute_decimation(target_points);
+                }
+            }
+        }
+        if content.data_sets.is_empty() {
+            content.recompute_global_bounds();
+        }
+        if let Some(raw_annotations) =
+            get_optional_by_str_key(raw_params, "annotations", &|| "annotations".to_string())?
+        {
+            for (index, raw_annotation) in raw_annotations
+                .dyn_into::<js_sys::Array>()
+                .map_err(|_| ChartError::at("annotations".to_string(), "not an array"))?
+                .iter()
+                .enumerate()
+            {
+                let axis = AnnotationAxis::from_str(
+                    get_string_by_str_key(&raw_annotation, "axis", &|| {
+                        format!("annotations[{}].axis", index)
+                    })?
+                    .as_str(),
+                )
+                .map_err(|e| ChartError::at(format!("annotations[{}].axis", index), e.as_str()))?;
+                let data_type = match axis {
+                    AnnotationAxis::Coord => coord_type,
+                    AnnotationAxis::Value => value_type,
+                };
+                let parse_at = |key: &str| -> Result<f64, ChartError> {
+                    let path = || format!("annotations[{}].{}", index, key);
+                    let raw_at = get_by_str_key(&raw_annotation, key, &path)?;
+                    Ok(parse_js_values(js_sys::Array::of1(&raw_at), data_type, false, &path)?[0])
+                };
+                let kind = match get_optional_string_by_str_key(&raw_annotation, "type", &|| {
+                    format!("annotations[{}].type", index)
+                })?
+                .as_deref()
+                {
+                    None | Some("line") => AnnotationKind::Line(parse_at("at")?),
+                    Some("band") => AnnotationKind::Band(parse_at("from")?, parse_at("to")?),
+                    Some(v) => {
+                        return Err(ChartError::at(
+                            format!("annotations[{}].type", index),
+                            format!("unsupported: {}", v).as_str(),
+                        ))
+                    }
+                };
+                let rgb = match get_optional_by_str_key(&raw_annotation, "color", &|| {
+                    format!("annotations[{}].color", index)
+                })? {
+                    Some(raw_color) => {
+                        js_value_to_rgb(&raw_color, &|| format!("annotations[{}].color", index))?
+                    }
+                    None => chart_config.color_tick,
+                };
+                let label = get_optional_string_by_str_key(&raw_annotation, "label", &|| {
+                    format!("annotations[{}].label", index)
+                })?;
+                content.annotations.push(Annotation {
+                    axis,
+                    kind,
+                    rgb,
+                    label,
+                });
+            }
+        }
+        if let Some(raw_markers) =
+            get_optional_by_str_key(raw_params, "markers", &|| "markers".to_string())?
+        {
+            for (index, raw_marker) in raw_markers
+                .dyn_into::<js_sys::Array>()
+                .map_err(|_| ChartError::at("markers".to_string(), "not an array"))?
+                .iter()
+                .enumerate()
+            {
+                let parse = |key: &str, data_type: DataType| -> Result<f64, ChartError> {
+                    let path = || format!("markers[{}].{}", index, key);
+                    let raw = get_by_str_key(&raw_marker, key, &path)?;
+                    Ok(parse_js_values(js_sys::Array::of1(&raw), data_type, false, &path)?[0])
+                };
+                let rgb = match get_optional_by_str_key(&raw_marker, "color", &|| {
+                    format!("markers[{}].color", index)
+                })? {
+                    Some(raw_color) => {
+                        js_value_to_rgb(&raw_color, &|| format!("markers[{}].color", index))?
+                    }
+                    None => chart_config.color_tick,
+                };
+                let label = get_optional_string_by_str_key(&raw_marker, "label", &|| {
+                    format!("markers[{}].label", index)
+                })?;
+                let coord = parse("coord", coord_type)?;
+                let value = parse("value", value_type)?;
+                content.markers.push(Marker {
+                    coord,
+                    value,
+                    rgb,
+                    label,
+                });
+            }
         }
-        Ok(ChartParams { selector, content })
+        Ok(content)
     }
 }
 #[derive(Debug, Clone)]
@@ -760,7 +2647,9 @@ pub struct ClientCaps {
     pub device_pixel_ratio: f64,
     pub css_to_physical_scale: f64,
     pub screen_orientation: bool,
+    pub prefers_reduced_motion: bool,
 }
+#[cfg(feature = "wasm")]
 impl ClientCaps {
     pub fn detect() -> Self {
         let window = web_sys::window().unwrap();
@@ -780,11 +2669,601 @@ impl ClientCaps {
             .and_then(|screen| Reflect::get(&screen, &JsValue::from_str("orientation")))
             .unwrap()
             .is_undefined();
+        let prefers_reduced_motion = Self::reduced_motion_query()
+            .map(|query| query.matches())
+            .unwrap_or(false);
         Self {
             touch_device,
             device_pixel_ratio,
             css_to_physical_scale,
             screen_orientation,
+            prefers_reduced_motion,
+        }
+    }
+    pub fn device_pixel_ratio_query(device_pixel_ratio: f64) -> Option<web_sys::MediaQueryList> {
+        web_sys::window()
+            .unwrap()
+            .match_media(format!("(resolution: {}dppx)", device_pixel_ratio).as_str())
+            .ok()
+            .flatten()
+    }
+    pub fn device_pixel_ratio_changed(&self) -> bool {
+        web_sys::window().unwrap().device_pixel_ratio() != self.device_pixel_ratio
+    }
+    pub fn reduced_motion_query() -> Option<web_sys::MediaQueryList> {
+        web_sys::window()
+            .unwrap()
+            .match_media("(prefers-reduced-motion: reduce)")
+            .ok()
+            .flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::animate::{Animation, Easing};
+    use crate::data_set::DataPoint;
+    use crate::error::ErrorCode;
+    use crate::grid::DAY_MS;
+    use crate::params::{
+        format_date_tick, format_duration, AnnotationAxis, ChartConfig, ChartMode, Content,
+        DataSetSorting, DataType, LegendPosition, NumberAbbreviations, PanAxis, TooltipSort,
+        UpdateZoomPolicy, ValueTransform, VerboseFormat,
+    };
+    use crate::pos::ScreenPos;
+    use crate::scale::{LinearScale, Scale};
+    use chrono::{DateTime, FixedOffset};
+    use std::str::FromStr;
+
+    fn format_number(verbose_format: &VerboseFormat, value: f64) -> String {
+        verbose_format
+            .format_values(Some(value).into_iter(), |x| x, value, value)
+            .pop()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_number_separators() {
+        let default_format = VerboseFormat::Number {
+            precision: 5,
+            scale: 4,
+            group_separator: ",".to_string(),
+            decimal_separator: ".".to_string(),
+        };
+        assert_eq!(format_number(&default_format, 1234567.25), "1,234,567.25");
+        assert_eq!(format_number(&default_format, -1234.0), "-1,234");
+
+        let european_format = VerboseFormat::Number {
+            precision: 5,
+            scale: 4,
+            group_separator: ".".to_string(),
+            decimal_separator: ",".to_string(),
+        };
+        assert_eq!(format_number(&european_format, 1234567.25), "1.234.567,25");
+        assert_eq!(format_number(&european_format, -1234.0), "-1.234");
+        assert_eq!(format_number(&european_format, 0.0001), "1,0000e-4");
+    }
+
+    #[test]
+    fn test_number_concise_decimal_separator() {
+        let concise_format = VerboseFormat::NumberConcise {
+            decimal_separator: ",".to_string(),
+            suffixes: NumberAbbreviations::ShortScale.get_suffixes(),
+        };
+        assert_eq!(format_number(&concise_format, 1500.0), "1,50K");
+        assert_eq!(format_number(&concise_format, 25.5), "25,5");
+        assert_eq!(format_number(&concise_format, 250.0), "250");
+    }
+
+    #[test]
+    fn test_number_concise_suffixes() {
+        let short_scale_format = VerboseFormat::NumberConcise {
+            decimal_separator: ".".to_string(),
+            suffixes: NumberAbbreviations::ShortScale.get_suffixes(),
+        };
+        assert_eq!(format_number(&short_scale_format, 2.5e9), "2.50B");
+        assert_eq!(format_number(&short_scale_format, 2.5e12), "2.50T");
+        assert_eq!(format_number(&short_scale_format, 2.5e15), "2.50Q");
+        assert_eq!(format_number(&short_scale_format, 2.5e19), "2.50e19");
+
+        let si_format = VerboseFormat::NumberConcise {
+            decimal_separator: ".".to_string(),
+            suffixes: NumberAbbreviations::Si.get_suffixes(),
+        };
+        assert_eq!(format_number(&si_format, 1500.0), "1.50k");
+        assert_eq!(format_number(&si_format, 2.5e9), "2.50G");
+        assert_eq!(format_number(&si_format, 2.5e15), "2.50P");
+    }
+
+    #[test]
+    fn test_malformed_data_sets() {
+        let mut content = Content::default();
+        let mut add = |coords: Vec<f64>, values: Vec<f64>| {
+            content
+                .parse_and_add_data_set("a", coords, values, (0, 0, 0), 0.0)
+                .err()
+                .unwrap()
+        };
+        assert_eq!(
+            add(vec![], vec![]),
+            "data set 'a' - coords and values are empty"
+        );
+        assert_eq!(
+            add(vec![], vec![1.0, 2.0]),
+            "data set 'a' - coords are empty, values: 2"
+        );
+        assert_eq!(
+            add(vec![1.0, 2.0, 3.0], vec![]),
+            "data set 'a' - values are empty, coords: 3"
+        );
+        assert_eq!(
+            add(vec![1.0, 2.0, 3.0, 4.0, 5.0], vec![1.0, 2.0, 3.0]),
+            "data set 'a' - coords and values have different lengths: coords: 5, values: 3"
+        );
+    }
+
+    #[test]
+    fn test_nan_values() {
+        let mut content = Content::default();
+        content
+            .parse_and_add_data_set(
+                "a",
+                vec![1.0, 2.0, 3.0],
+                vec![5.0, f64::NAN, -5.0],
+                (0, 0, 0),
+                0.0,
+            )
+            .unwrap();
+        assert_eq!(content.global_value_min, -5.0);
+        assert_eq!(content.global_value_max, 5.0);
+        assert!(content
+            .parse_and_add_data_set("b", vec![1.0], vec![f64::NAN], (0, 0, 0), 0.0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_coord_offset() {
+        let mut content = Content::default();
+        content
+            .parse_and_add_data_set(
+                "a",
+                vec![1.0, 2.0, 3.0],
+                vec![1.0, 2.0, 3.0],
+                (0, 0, 0),
+                0.0,
+            )
+            .unwrap();
+        content
+            .parse_and_add_data_set(
+                "b",
+                vec![3.0, 1.0, 2.0],
+                vec![3.0, 1.0, 2.0],
+                (0, 0, 0),
+                -10.0,
+            )
+            .unwrap();
+        let coords: Vec<f64> = content.data_sets[1]
+            .data_points
+            .iter()
+            .map(|p| p.coord)
+            .collect();
+        assert_eq!(coords, vec![-9.0, -8.0, -7.0]);
+        assert_eq!(content.global_coord_min, -9.0);
+        assert_eq!(content.global_coord_max, 3.0);
+        assert_eq!(content.global_value_min, 1.0);
+        assert_eq!(content.global_value_max, 3.0);
+    }
+
+    #[test]
+    fn test_with_tz_offset_minutes() {
+        let browser_offset = FixedOffset::east_opt(3600).unwrap();
+        let data_type = DataType::DateTime {
+            tz_offset: browser_offset,
+        };
+        match data_type.with_tz_offset_minutes(Some(-300.0)).unwrap() {
+            DataType::DateTime { tz_offset } => assert_eq!(tz_offset.local_minus_utc(), -18000),
+            _ => panic!("datetime expected"),
+        }
+        match data_type.with_tz_offset_minutes(None).unwrap() {
+            DataType::DateTime { tz_offset } => assert_eq!(tz_offset, browser_offset),
+            _ => panic!("datetime expected"),
+        }
+        assert!(matches!(
+            DataType::Date.with_tz_offset_minutes(Some(60.0)).unwrap(),
+            DataType::Date
+        ));
+        assert!(data_type.with_tz_offset_minutes(Some(1500.0)).is_err());
+    }
+
+    #[test]
+    fn test_format_date_tick() {
+        let datetime = DateTime::parse_from_rfc3339("2023-08-17T14:05:09+00:00").unwrap();
+        for (period_ms, expected) in [
+            (3.0 * 365.0 * DAY_MS, "2023"),
+            (120.0 * DAY_MS, "Q3 2023"),
+            (45.0 * DAY_MS, "Aug 2023"),
+            (14.0 * DAY_MS, "Aug 17"),
+            (2.0 * DAY_MS, "Thu 17"),
+            (3600000.0, "14:05"),
+            (5000.0, "14:05:09"),
+        ] {
+            assert_eq!(format_date_tick(&datetime, period_ms), expected);
+        }
+        assert_eq!(
+            VerboseFormat::Duration.format_date_ticks([0.0].into_iter(), |x| x, DAY_MS),
+            None
+        );
+    }
+
+    #[test]
+    fn test_format_duration() {
+        for (ms, expected) in [
+            (0.0, "0s"),
+            (350.0, "350ms"),
+            (45000.0, "45s"),
+            (61500.0, "1m 1s"),
+            ((2.0 * 60.0 + 15.0) * 60000.0, "2h 15m"),
+            // the lower unit is dropped when it's zero
+            (2.0 * 3600000.0 + 30000.0, "2h"),
+            (3.0 * 86400000.0 + 4.0 * 3600000.0 + 59000.0, "3d 4h"),
+            (385.0 * 86400000.0, "1y 20d"),
+            (-90000.0, "-1m 30s"),
+        ] {
+            assert_eq!(format_duration(ms), expected);
+        }
+        assert_eq!(
+            VerboseFormat::Duration.format_values([60000.0].into_iter(), |x| x, 0.0, 0.0),
+            vec!["1m".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_tooltip_sort() {
+        let rows = [("a", 2.0), ("b", 3.0), ("c", 1.0), ("d", 3.0)];
+        for (sort, expected) in [
+            ("none", ["a", "b", "c", "d"]),
+            ("valueDesc", ["b", "d", "a", "c"]),
+            ("valueAsc", ["c", "a", "b", "d"]),
+        ] {
+            let mut sorted = rows;
+            sort.parse::<TooltipSort>()
+                .unwrap()
+                .sort(&mut sorted, |row| row.1);
+            assert_eq!(sorted.map(|row| row.0), expected);
+        }
+    }
+
+    #[test]
+    fn test_recompute_global_bounds() {
+        let mut content = Content::default();
+        for name in ["a", "b"] {
+            content
+                .parse_and_add_data_set(name, vec![1.0, 2.0], vec![1.0, 2.0], (0, 0, 0), 0.0)
+                .unwrap();
+        }
+        content.data_sets[0].data_points.push(DataPoint {
+            coord: 5.0,
+            value: -3.0,
+        });
+        content.data_sets[1].data_points.push(DataPoint {
+            coord: 6.0,
+            value: f64::NAN,
+        });
+        content.recompute_global_bounds();
+        assert_eq!(content.global_coord_min, 1.0);
+        assert_eq!(content.global_coord_max, 6.0);
+        assert_eq!(content.global_value_min, -3.0);
+        assert_eq!(content.global_value_max, 2.0);
+
+        let data_set = &mut content.data_sets[0];
+        data_set.precompute_decimation(2);
+        data_set.reindex();
+        assert_eq!(data_set.meta.min, -3.0);
+        assert_eq!(data_set.value_extents.query(1, 3), Some((-3.0, 2.0)));
+        assert!(data_set.decimated.is_empty());
+
+        content.data_sets.remove(0);
+        content.recompute_global_bounds();
+        assert_eq!(content.global_coord_max, 6.0);
+        assert_eq!(content.global_value_min, 1.0);
+    }
+
+    #[test]
+    fn test_min_max_with_everything_hidden() {
+        let mut content = Content::default();
+        content
+            .parse_and_add_data_set("a", vec![1.0, 4.0], vec![2.0, 3.0], (0, 0, 0), 0.0)
+            .unwrap();
+        content.recompute_global_bounds();
+        assert_eq!(content.get_min_max(), [1.0, 4.0, 2.0, 3.0]);
+        content.data_sets[0]
+            .alpha
+            .set_value(0.0, None, Animation::default());
+        assert_eq!(content.get_min_max(), [1.0, 4.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_min_max_of_series_with_different_coord_ranges() {
+        let mut content = Content::default();
+        content
+            .parse_and_add_data_set("a", vec![1.0, 10.0], vec![2.0, 3.0], (0, 0, 0), 0.0)
+            .unwrap();
+        content
+            .parse_and_add_data_set("b", vec![3.0, 5.0], vec![1.0, 4.0], (0, 0, 0), 0.0)
+            .unwrap();
+        content.recompute_global_bounds();
+        assert_eq!(content.get_min_max(), [1.0, 10.0, 1.0, 4.0]);
+        content.data_sets[0]
+            .alpha
+            .set_value(0.0, None, Animation::default());
+        assert_eq!(content.get_min_max(), [3.0, 5.0, 1.0, 4.0]);
+        content.data_sets[0]
+            .alpha
+            .set_value(1.0, None, Animation::default());
+        content.data_sets[1]
+            .alpha
+            .set_value(0.0, None, Animation::default());
+        assert_eq!(content.get_min_max(), [1.0, 10.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_empty_content_bounds() {
+        let mut content = Content::default();
+        content.recompute_global_bounds();
+        assert_eq!(content.get_min_max(), [0.0, 1.0, 0.0, 1.0]);
+        let scale = LinearScale::new(&content);
+        assert_eq!(scale.normalize_coord(0.5), 0.5);
+        assert_eq!(scale.normalize_value(0.5), 0.5);
+    }
+
+    #[test]
+    fn test_is_strictly_positive() {
+        let mut content = Content::default();
+        assert!(!content.is_strictly_positive());
+        content
+            .parse_and_add_data_set("a", vec![1.0, 2.0], vec![1.0, 100.0], (0, 0, 0), 0.0)
+            .unwrap();
+        assert!(content.is_strictly_positive());
+        content
+            .parse_and_add_data_set("b", vec![1.0, 2.0], vec![0.0, 100.0], (0, 0, 0), 0.0)
+            .unwrap();
+        assert!(!content.is_strictly_positive());
+    }
+
+    #[test]
+    fn test_sync_animation() {
+        let mut config = ChartConfig {
+            animation_duration_us: 400000.0,
+            animation_easing: Easing::Linear,
+            ..ChartConfig::default()
+        };
+        config.sync_animation(false);
+        assert_eq!(
+            config.animation,
+            Animation::new(true, 400000.0, Easing::Linear)
+        );
+        assert_eq!(config.animation.duration_scale, 2.0);
+        // left out, animate follows the client preference
+        config.sync_animation(true);
+        assert!(!config.animation.enabled);
+        config.animate = Some(true);
+        config.sync_animation(true);
+        assert!(config.animation.enabled);
+        config.animation_duration_us = 0.0;
+        config.sync_animation(false);
+        assert!(!config.animation.enabled);
+    }
+
+    #[test]
+    fn test_percent_value_transform() {
+        let config = ChartConfig {
+            value_transform: ValueTransform::Percent,
+            ..ChartConfig::default()
+        };
+        let mut content = Content::new(None, DataType::Number, DataType::Number, &config).unwrap();
+        let data_set = content
+            .parse_and_add_data_set(
+                "a",
+                vec![3.0, 1.0, 2.0],
+                vec![30.0, f64::NAN, 20.0],
+                (0, 0, 0),
+                0.0,
+            )
+            .unwrap();
+        let values: Vec<f64> = data_set.data_points.iter().map(|p| p.value).collect();
+        assert!(values[0].is_nan());
+        assert_eq!(values[1..], [100.0, 150.0]);
+        assert!(content
+            .parse_and_add_data_set("b", vec![1.0, 2.0], vec![0.0, 1.0], (0, 0, 0), 0.0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_begin_at_zero() {
+        let config = ChartConfig {
+            begin_at_zero: true,
+            ..ChartConfig::default()
+        };
+        let mut content = Content::new(None, DataType::Number, DataType::Number, &config).unwrap();
+        content
+            .parse_and_add_data_set("a", vec![1.0, 2.0], vec![50.0, 60.0], (0, 0, 0), 0.0)
+            .unwrap();
+        assert_eq!(content.get_min_max(), [1.0, 2.0, 0.0, 60.0]);
+        assert_eq!(content.frame_value_range(-20.0, -10.0), (-20.0, 0.0));
+        assert_eq!(content.frame_value_range(-20.0, 10.0), (-20.0, 10.0));
+
+        content.value_padding = 0.1;
+        assert_eq!(content.get_min_max(), [1.0, 2.0, 0.0, 66.0]);
+        assert_eq!(content.frame_value_range(-20.0, 20.0), (-24.0, 24.0));
+
+        content.begin_at_zero = false;
+        assert_eq!(content.get_min_max(), [1.0, 2.0, 49.0, 61.0]);
+        content.value_padding = 0.0;
+        assert_eq!(content.get_min_max(), [1.0, 2.0, 50.0, 60.0]);
+    }
+
+    #[test]
+    fn test_data_set_visibility() {
+        let mut content = Content::default();
+        for name in ["a", "b"] {
+            content
+                .parse_and_add_data_set(name, vec![1.0], vec![1.0], (0, 0, 0), 0.0)
+                .unwrap();
+        }
+        assert_eq!(content.get_visible_data_set_names(), vec!["a", "b"]);
+        content
+            .set_data_set_visibility("a", false, 0.0, Animation::default())
+            .unwrap();
+        assert_eq!(content.get_visible_data_set_names(), vec!["b"]);
+        content
+            .set_data_set_visibility("b", false, 0.0, Animation::default())
+            .unwrap();
+        assert!(content.get_visible_data_set_names().is_empty());
+        content
+            .set_data_set_visibility("a", true, 0.0, Animation::default())
+            .unwrap();
+        assert_eq!(content.get_visible_data_set_names(), vec!["a"]);
+        assert!(content
+            .set_data_set_visibility("c", true, 0.0, Animation::default())
+            .is_err());
+    }
+
+    #[test]
+    fn test_chart_mode() {
+        assert!(ChartMode::from_str("sparkline") == Ok(ChartMode::Sparkline));
+        assert!(ChartMode::from_str("normal") == Ok(ChartMode::Normal));
+        assert_eq!(
+            ChartMode::from_str("tiny").err().unwrap(),
+            "unsupported ChartMode: tiny"
+        );
+    }
+
+    #[test]
+    fn test_legend_position() {
+        assert!(matches!(
+            LegendPosition::from_str("top"),
+            Ok(LegendPosition::Top)
+        ));
+        assert!(matches!(
+            LegendPosition::from_str("hidden"),
+            Ok(LegendPosition::Hidden)
+        ));
+        assert_eq!(
+            LegendPosition::from_str("center").err().unwrap(),
+            "unsupported LegendPosition: center"
+        );
+    }
+
+    // the keys of enum options stay on their FromStr errors
+    #[cfg(feature = "wasm")]
+    #[test]
+    fn test_at_key() {
+        let error = LegendPosition::from_str("center")
+            .map_err(crate::params::at_key("legendPosition"))
+            .err()
+            .unwrap();
+        assert_eq!(error.code, ErrorCode::Params);
+        assert_eq!(error.path.as_deref(), Some("legendPosition"));
+        assert_eq!(error.message, "unsupported LegendPosition: center");
+    }
+
+    #[test]
+    fn test_invalid_format() {
+        let conf = ChartConfig {
+            value_format: Some("x.2".to_string()),
+            ..ChartConfig::default()
+        };
+        let error = Content::new(None, DataType::Number, DataType::Number, &conf)
+            .err()
+            .unwrap();
+        assert_eq!(error.code, ErrorCode::Config);
+        assert_eq!(error.path.as_deref(), Some("valueFormat"));
+        assert_eq!(error.message, "invalid number format: 'x.2'");
+    }
+
+    #[test]
+    fn test_annotation_axis() {
+        assert!(matches!(
+            AnnotationAxis::from_str("coord"),
+            Ok(AnnotationAxis::Coord)
+        ));
+        assert!(matches!(
+            AnnotationAxis::from_str("value"),
+            Ok(AnnotationAxis::Value)
+        ));
+        assert_eq!(
+            AnnotationAxis::from_str("x").err().unwrap(),
+            "unsupported AnnotationAxis: x"
+        );
+    }
+
+    #[test]
+    fn test_colors_survive_sorting() {
+        let mut content = Content::default();
+        for (name, max_value, rgb) in [
+            ("errors", 1.0, (255, 0, 0)),
+            ("requests", 100.0, (0, 255, 0)),
+            ("latency", 10.0, (0, 0, 255)),
+        ] {
+            content
+                .parse_and_add_data_set(name, vec![1.0, 2.0], vec![0.0, max_value], rgb, 0.0)
+                .unwrap();
+        }
+        content.sort_data_sets(&DataSetSorting::MaxDesc);
+        let names_n_colors: Vec<(&str, (u8, u8, u8))> = content
+            .data_sets
+            .iter()
+            .map(|data_set| (data_set.name.as_str(), data_set.rgb))
+            .collect();
+        assert_eq!(
+            names_n_colors,
+            vec![
+                ("requests", (0, 255, 0)),
+                ("latency", (0, 0, 255)),
+                ("errors", (255, 0, 0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_update_zoom_policy() {
+        let window = Some((10.0, 20.0));
+        // not zoomed in - always the full range
+        for policy in ["reset", "preserve", "clampToData"] {
+            let policy: UpdateZoomPolicy = policy.parse().unwrap();
+            assert_eq!(policy.get_window(None, 0.0, 30.0), None);
+        }
+
+        let reset = UpdateZoomPolicy::Reset;
+        assert_eq!(reset.get_window(window, 0.0, 30.0), None);
+
+        let preserve = UpdateZoomPolicy::Preserve;
+        assert_eq!(preserve.get_window(window, 0.0, 30.0), window);
+        assert_eq!(preserve.get_window(window, 15.0, 40.0), window);
+
+        let clamp = UpdateZoomPolicy::ClampToData;
+        assert_eq!(clamp.get_window(window, 0.0, 30.0), window);
+        assert_eq!(clamp.get_window(window, 15.0, 40.0), Some((15.0, 25.0)));
+        assert_eq!(clamp.get_window(window, 0.0, 15.0), Some((5.0, 15.0)));
+        assert_eq!(clamp.get_window(window, 12.0, 18.0), None);
+
+        assert!("pinned".parse::<UpdateZoomPolicy>().is_err());
+    }
+
+    #[test]
+    fn test_pan_axis_lock_pos() {
+        let down_pos = ScreenPos(10.0, 20.0);
+        let pos = ScreenPos(50.0, 90.0);
+        for (pan_axis, expected) in [
+            ("x", (50.0, 20.0)),
+            ("y", (10.0, 90.0)),
+            ("both", (50.0, 90.0)),
+        ] {
+            let locked = pan_axis
+                .parse::<PanAxis>()
+                .unwrap()
+                .lock_pos(&pos, &down_pos);
+            assert_eq!((locked.0, locked.1), expected);
         }
     }
 }
@@ -1,4 +1,64 @@
-pub struct Pos {
-    x: f64,
-    y: f64,
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ *
+ * Copyright (C) 2023, Nikita Almakov
+ */
+pub trait DefineSize {
+    fn get_css_to_physical_scale(self) -> f64;
+    fn get_font_width_to_physical_scale(self) -> f64;
+    fn get_font_height_to_physical_scale(self) -> f64;
+    fn get_content_width(self) -> f64;
+    fn get_content_height(self) -> f64;
 }
+
+#[derive(Clone, Debug)]
+pub enum Size {
+    Px(f64),
+    TextLine { font_size: f64, columns: f64 },
+    Pct(f64),
+    Sum(Vec<Size>),
+}
+impl Size {
+    pub fn to_cpx_width<T>(&self, size_def: T) -> f64
+    where
+        T: DefineSize + Copy,
+    {
+        match self {
+            Self::Px(v) => *v * size_def.get_css_to_physical_scale(),
+            Self::TextLine { font_size, columns } => {
+                *font_size * *columns as f64 * size_def.get_font_width_to_physical_scale()
+            }
+            Self::Pct(v) => *v * size_def.get_content_width(),
+            Self::Sum(sizes) => sizes.iter().map(|size| size.to_cpx_width(size_def)).sum(),
+        }
+    }
+    pub fn to_cpx_height<T>(&self, size_def: T) -> f64
+    where
+        T: DefineSize + Copy,
+    {
+        match self {
+            Self::Px(v) => *v * size_def.get_css_to_physical_scale(),
+            Self::TextLine { font_size, .. } => {
+                *font_size * size_def.get_font_height_to_physical_scale()
+            }
+            Self::Pct(v) => *v * size_def.get_content_height(),
+            Self::Sum(sizes) => sizes.iter().map(|size| size.to_cpx_height(size_def)).sum(),
+        }
+    }
+    pub fn mul(&self, x: f64) -> Self {
+        match self {
+            Self::Px(v) => Self::Px(v * x),
+            Self::TextLine { font_size, columns } => Self::TextLine {
+                font_size: *font_size,
+                columns: columns * x,
+            },
+            Self::Pct(v) => Self::Pct(v * x),
+            Self::Sum(sizes) => Self::Sum(sizes.iter().map(|size| size.mul(x)).collect()),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct ScreenPos(pub f64, pub f64);
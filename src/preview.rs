@@ -5,13 +5,18 @@
  *
  * Copyright (C) 2023, Nikita Almakov
  */
+use crate::camera::ERROR_BAND_ALPHA;
+use crate::decimate::{DecimationConsumer, DECIMATION_POINTS_PER_PX};
+use crate::legend::apply_hover_preview;
 use crate::params::Content;
 use crate::params::{ChartConfig, ClientCaps};
 use crate::scale::Scale;
-use crate::screen::{CoordSpace, ScreenArea, ScreenPos};
+use crate::screen::{CoordSpace, ScreenArea, ScreenPos, Size};
+use crate::tooltip::Tooltip;
 use std::cell::RefCell;
 use std::rc::Rc;
-use wasm_bindgen::JsValue;
+
+const GRIP_OUTLINE_WIDTH: Size = Size::Px(2.0);
 
 pub struct Preview<T>
 where
@@ -63,10 +68,19 @@ where
     }
     pub fn update_by_content(&mut self, content: &mut Content, time_us: Option<f64>) {
         let [coord_min, coord_max, value_min, value_max] = content.get_min_max();
-        self.coord_space
-            .content_updated(coord_min, coord_max, value_min, value_max, time_us);
-        self.control_coord_space
-            .content_updated(coord_min, coord_max, value_min, value_max, time_us);
+        let animation = self.chart_config.borrow().animation;
+        self.coord_space.content_updated(
+            coord_min, coord_max, value_min, value_max, time_us, animation,
+        );
+        self.control_coord_space.content_updated(
+            coord_min, coord_max, value_min, value_max, time_us, animation,
+        );
+    }
+    pub fn content_replaced(&mut self, content: &mut Content, time_us: f64) {
+        let scale = T::new(content);
+        self.coord_space.set_scale(scale.clone());
+        self.control_coord_space.set_scale(scale);
+        self.update_by_content(content, Some(time_us));
     }
     pub fn draw(&mut self, content: &mut Content, time_us: f64) {
         let coord_space_handle = self.coord_space.get_handle(time_us);
@@ -75,36 +89,89 @@ where
 
         let chart_config = self.chart_config.borrow();
 
+        let hover_preview = content.get_hover_preview(time_us);
         let mut alpha: f64;
-        for data_set in content.data_sets.iter_mut() {
-            alpha = data_set.alpha.get_value(time_us);
+        for (index, data_set) in content.data_sets.iter_mut().enumerate() {
+            alpha = apply_hover_preview(data_set.alpha.get_value(time_us), index, hover_preview)
+                .max(chart_config.hidden_ghost_alpha);
             if alpha == 0.0 {
                 continue;
             }
-            let mut it = data_set.data_points.iter();
-            let data_point = it.next().unwrap();
+            if !data_set.band_low.is_empty() {
+                crc.begin_path();
+                crc.set_fill_style(data_set.to_css_color(alpha * ERROR_BAND_ALPHA).as_str());
+                coord_space_handle.trace_band(&data_set.band_low, &data_set.band_high, 1.0);
+                crc.fill();
+            }
             crc.begin_path();
-            crc.set_stroke_style(&JsValue::from_str(data_set.to_css_color(alpha).as_str()));
+            crc.set_stroke_style(data_set.to_css_color(alpha).as_str());
             crc.set_line_width(chart_config.line_width.to_cpx_height(screen_area_handle));
-
-            let mut prev_x = coord_space_handle.get_cx(data_point.coord);
-            let mut prev_y = coord_space_handle.get_cy(data_point.value);
-            crc.move_to(prev_x, prev_y);
-            let mut x: f64;
-            let mut y: f64;
-            for data_point in it {
-                x = coord_space_handle.get_cx(data_point.coord);
-                y = coord_space_handle.get_cy(data_point.value);
-                if x - prev_x >= 1.0 || (y - prev_y).abs() >= 1.0 {
-                    crc.line_to(x, y);
-                    prev_x = x;
-                    prev_y = y;
-                }
-            }
+            let picked = data_set.pick_decimated(
+                (screen_area_handle.canvas_content_width * DECIMATION_POINTS_PER_PX) as usize,
+                DecimationConsumer::Preview,
+            );
+            coord_space_handle.trace_line(
+                data_set.get_decimated(picked),
+                data_set.max_gap,
+                content.connect_gaps,
+                data_set.interpolation,
+                1.0,
+            );
             crc.stroke();
         }
     }
 
+    // values at the hovered coord of the overview, to help aiming a zoom
+    pub fn draw_tooltip(&mut self, content: &Content, time_us: f64) {
+        let chart_config = self.chart_config.borrow();
+        if !chart_config.preview_tooltip || self.pointer_down.is_some() {
+            return;
+        }
+        let pointer = match self.pointer.as_ref() {
+            Some(pointer) => pointer,
+            None => return,
+        };
+        let coord_space_handle = self.control_coord_space.get_handle(time_us);
+        let screen_area_handle = coord_space_handle.screen_area_handle.as_ref();
+        let crc = screen_area_handle.crc.as_ref();
+        let coord = match coord_space_handle.get_coord(pointer) {
+            Some(coord) => coord,
+            None => return,
+        };
+        let (matched_coord, matches) = match Tooltip::match_nearest(content, coord) {
+            Some(nearest) => nearest,
+            None => return,
+        };
+        let text = Tooltip::compact_text(
+            content,
+            coord_space_handle.scale.as_ref(),
+            matched_coord,
+            matches.as_slice(),
+        );
+        let (width, height) =
+            Tooltip::compact_size(&chart_config, screen_area_handle, text.as_str());
+
+        let cx = coord_space_handle.get_cx(matched_coord);
+        let v = chart_config.color_tooltip_font;
+        crc.set_line_width(chart_config.line_width.to_cpx_height(screen_area_handle));
+        crc.set_stroke_style(format!("rgba({}, {}, {}, {})", v.0, v.1, v.2, v.3).as_str());
+        crc.begin_path();
+        crc.move_to(cx, screen_area_handle.top_cy());
+        crc.line_to(cx, screen_area_handle.bottom_cy());
+        crc.stroke();
+
+        let x = (cx - width * 0.5)
+            .min(screen_area_handle.right_cx() - width)
+            .max(screen_area_handle.left_cx());
+        Tooltip::draw_compact(
+            &chart_config,
+            screen_area_handle,
+            (x, screen_area_handle.top_cy()),
+            (width, height),
+            text.as_str(),
+        );
+    }
+
     pub fn draw_grip(&mut self, grip: Option<(f64, f64)>, time_us: f64) {
         let slide_in_progress = self.grip_hold_coord_offset.is_some();
         let zoomed_in = grip.is_some();
@@ -127,17 +194,24 @@ where
             let grip_x_start = coord_space_handle.get_cx(grip_coord - grip_coord_range * 0.5);
             let grip_x_end = coord_space_handle.get_cx(grip_coord + grip_coord_range * 0.5);
             let v = chart_config.color_camera_grip;
-            crc.set_fill_style(&JsValue::from_str(
-                format!("rgba({}, {}, {}, {})", v.0, v.1, v.2, v.3).as_str(),
-            ));
+            crc.set_fill_style(format!("rgba({}, {}, {}, {})", v.0, v.1, v.2, v.3).as_str());
             crc.fill_rect(grip_x_start, top_y, grip_x_end - grip_x_start, height);
+        } else if chart_config.preview_grip_outline {
+            let c_line_width = GRIP_OUTLINE_WIDTH.to_cpx_height(screen_area_handle);
+            let v = chart_config.color_camera_grip;
+            crc.set_line_width(c_line_width);
+            crc.set_stroke_style(format!("rgba({}, {}, {}, {})", v.0, v.1, v.2, v.3).as_str());
+            crc.stroke_rect(
+                left_x + c_line_width * 0.5,
+                top_y + c_line_width * 0.5,
+                width - c_line_width,
+                height - c_line_width,
+            );
         }
 
         if !slide_in_progress {
             let v = chart_config.color_preview_overlay;
-            crc.set_fill_style(&JsValue::from_str(
-                format!("rgba({}, {}, {}, {})", v.0, v.1, v.2, v.3,).as_str(),
-            ));
+            crc.set_fill_style(format!("rgba({}, {}, {}, {})", v.0, v.1, v.2, v.3,).as_str());
             crc.fill_rect(left_x, top_y, width, height);
 
             crc.set_text_align("center");
@@ -153,39 +227,37 @@ where
                 .as_str(),
             );
             let v = chart_config.color_preview_hint;
-            crc.set_fill_style(&JsValue::from_str(
-                format!("rgba({}, {}, {}, {})", v.0, v.1, v.2, v.3,).as_str(),
-            ));
+            crc.set_fill_style(format!("rgba({}, {}, {}, {})", v.0, v.1, v.2, v.3,).as_str());
             crc.fill_text(
                 if zoomed_in {
-                    "Click to zoom out"
+                    chart_config.hint_zoom_out.as_str()
                 } else {
-                    "Drag here or above to zoom in"
+                    chart_config.hint_zoom_in.as_str()
                 },
                 (left_x + right_x) * 0.5,
                 (top_y + bottom_y) * 0.5,
-            )
-            .unwrap();
-
-            let font_cpx_size = chart_config
-                .font_size_small
-                .to_cpx_height(screen_area_handle);
-            crc.set_font(
-                format!(
-                    "{}px {}",
-                    font_cpx_size,
-                    chart_config.font_standard.as_str()
-                )
-                .as_str(),
             );
-            crc.set_text_baseline("bottom");
-            crc.set_text_align("right");
-            crc.fill_text(
-                "© Plotica",
-                right_x - font_cpx_size,
-                bottom_y - font_cpx_size * 0.5,
-            )
-            .unwrap();
+
+            if chart_config.show_watermark {
+                let font_cpx_size = chart_config
+                    .font_size_small
+                    .to_cpx_height(screen_area_handle);
+                crc.set_font(
+                    format!(
+                        "{}px {}",
+                        font_cpx_size,
+                        chart_config.font_standard.as_str()
+                    )
+                    .as_str(),
+                );
+                crc.set_text_baseline("bottom");
+                crc.set_text_align("right");
+                crc.fill_text(
+                    "© Plotica",
+                    right_x - font_cpx_size,
+                    bottom_y - font_cpx_size * 0.5,
+                );
+            }
         }
     }
 }
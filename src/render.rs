@@ -0,0 +1,607 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ *
+ * Copyright (C) 2023, Nikita Almakov
+ */
+use std::cell::RefCell;
+
+// the subset of the 2d canvas api charts draw with
+pub trait Renderer {
+    fn save(&self);
+    fn restore(&self);
+    fn begin_path(&self);
+    fn close_path(&self);
+    fn move_to(&self, x: f64, y: f64);
+    fn line_to(&self, x: f64, y: f64);
+    fn bezier_curve_to(&self, cp1x: f64, cp1y: f64, cp2x: f64, cp2y: f64, x: f64, y: f64);
+    fn arc(&self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64);
+    fn rect(&self, x: f64, y: f64, width: f64, height: f64);
+    fn clip(&self);
+    fn translate(&self, x: f64, y: f64);
+    fn rotate(&self, angle: f64);
+    fn stroke(&self);
+    fn fill(&self);
+    fn fill_rect(&self, x: f64, y: f64, width: f64, height: f64);
+    fn stroke_rect(&self, x: f64, y: f64, width: f64, height: f64);
+    fn clear_rect(&self, x: f64, y: f64, width: f64, height: f64);
+    fn fill_text(&self, text: &str, x: f64, y: f64);
+    fn measure_text_width(&self, text: &str) -> f64;
+    fn set_fill_style(&self, style: &str);
+    fn set_stroke_style(&self, style: &str);
+    fn set_line_width(&self, width: f64);
+    fn set_font(&self, font: &str);
+    fn set_text_align(&self, align: &str);
+    fn set_text_baseline(&self, baseline: &str);
+}
+
+#[cfg(feature = "wasm")]
+impl Renderer for web_sys::CanvasRenderingContext2d {
+    fn save(&self) {
+        web_sys::CanvasRenderingContext2d::save(self);
+    }
+    fn restore(&self) {
+        web_sys::CanvasRenderingContext2d::restore(self);
+    }
+    fn begin_path(&self) {
+        web_sys::CanvasRenderingContext2d::begin_path(self);
+    }
+    fn close_path(&self) {
+        web_sys::CanvasRenderingContext2d::close_path(self);
+    }
+    fn move_to(&self, x: f64, y: f64) {
+        web_sys::CanvasRenderingContext2d::move_to(self, x, y);
+    }
+    fn line_to(&self, x: f64, y: f64) {
+        web_sys::CanvasRenderingContext2d::line_to(self, x, y);
+    }
+    fn bezier_curve_to(&self, cp1x: f64, cp1y: f64, cp2x: f64, cp2y: f64, x: f64, y: f64) {
+        web_sys::CanvasRenderingContext2d::bezier_curve_to(self, cp1x, cp1y, cp2x, cp2y, x, y);
+    }
+    fn arc(&self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64) {
+        web_sys::CanvasRenderingContext2d::arc(self, x, y, radius, start_angle, end_angle).unwrap();
+    }
+    fn rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        web_sys::CanvasRenderingContext2d::rect(self, x, y, width, height);
+    }
+    fn clip(&self) {
+        web_sys::CanvasRenderingContext2d::clip(self);
+    }
+    fn translate(&self, x: f64, y: f64) {
+        web_sys::CanvasRenderingContext2d::translate(self, x, y).unwrap();
+    }
+    fn rotate(&self, angle: f64) {
+        web_sys::CanvasRenderingContext2d::rotate(self, angle).unwrap();
+    }
+    fn stroke(&self) {
+        web_sys::CanvasRenderingContext2d::stroke(self);
+    }
+    fn fill(&self) {
+        web_sys::CanvasRenderingContext2d::fill(self);
+    }
+    fn fill_rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        web_sys::CanvasRenderingContext2d::fill_rect(self, x, y, width, height);
+    }
+    fn stroke_rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        web_sys::CanvasRenderingContext2d::stroke_rect(self, x, y, width, height);
+    }
+    fn clear_rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        web_sys::CanvasRenderingContext2d::clear_rect(self, x, y, width, height);
+    }
+    fn fill_text(&self, text: &str, x: f64, y: f64) {
+        web_sys::CanvasRenderingContext2d::fill_text(self, text, x, y).unwrap();
+    }
+    fn measure_text_width(&self, text: &str) -> f64 {
+        web_sys::CanvasRenderingContext2d::measure_text(self, text)
+            .map(|metrics| metrics.width())
+            .unwrap_or(0.0)
+    }
+    fn set_fill_style(&self, style: &str) {
+        self.set_fill_style_str(style);
+    }
+    fn set_stroke_style(&self, style: &str) {
+        self.set_stroke_style_str(style);
+    }
+    fn set_line_width(&self, width: f64) {
+        web_sys::CanvasRenderingContext2d::set_line_width(self, width);
+    }
+    fn set_font(&self, font: &str) {
+        web_sys::CanvasRenderingContext2d::set_font(self, font);
+    }
+    fn set_text_align(&self, align: &str) {
+        web_sys::CanvasRenderingContext2d::set_text_align(self, align);
+    }
+    fn set_text_baseline(&self, baseline: &str) {
+        web_sys::CanvasRenderingContext2d::set_text_baseline(self, baseline);
+    }
+}
+
+// logs draw calls instead of drawing, e.g. for snapshot tests
+pub struct RecordingRenderer {
+    pub calls: RefCell<Vec<String>>,
+    pub char_width: f64,
+}
+impl RecordingRenderer {
+    pub fn new(char_width: f64) -> Self {
+        Self {
+            calls: RefCell::new(Vec::new()),
+            char_width,
+        }
+    }
+    pub fn take_calls(&self) -> Vec<String> {
+        self.calls.take()
+    }
+    fn record(&self, call: String) {
+        self.calls.borrow_mut().push(call);
+    }
+}
+impl Renderer for RecordingRenderer {
+    fn save(&self) {
+        self.record("save()".to_string());
+    }
+    fn restore(&self) {
+        self.record("restore()".to_string());
+    }
+    fn begin_path(&self) {
+        self.record("begin_path()".to_string());
+    }
+    fn close_path(&self) {
+        self.record("close_path()".to_string());
+    }
+    fn move_to(&self, x: f64, y: f64) {
+        self.record(format!("move_to({}, {})", x, y));
+    }
+    fn line_to(&self, x: f64, y: f64) {
+        self.record(format!("line_to({}, {})", x, y));
+    }
+    fn bezier_curve_to(&self, cp1x: f64, cp1y: f64, cp2x: f64, cp2y: f64, x: f64, y: f64) {
+        self.record(format!(
+            "bezier_curve_to({}, {}, {}, {}, {}, {})",
+            cp1x, cp1y, cp2x, cp2y, x, y
+        ));
+    }
+    fn arc(&self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64) {
+        self.record(format!(
+            "arc({}, {}, {}, {}, {})",
+            x, y, radius, start_angle, end_angle
+        ));
+    }
+    fn rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        self.record(format!("rect({}, {}, {}, {})", x, y, width, height));
+    }
+    fn clip(&self) {
+        self.record("clip()".to_string());
+    }
+    fn translate(&self, x: f64, y: f64) {
+        self.record(format!("translate({}, {})", x, y));
+    }
+    fn rotate(&self, angle: f64) {
+        self.record(format!("rotate({})", angle));
+    }
+    fn stroke(&self) {
+        self.record("stroke()".to_string());
+    }
+    fn fill(&self) {
+        self.record("fill()".to_string());
+    }
+    fn fill_rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        self.record(format!("fill_rect({}, {}, {}, {})", x, y, width, height));
+    }
+    fn stroke_rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        self.record(format!("stroke_rect({}, {}, {}, {})", x, y, width, height));
+    }
+    fn clear_rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        self.record(format!("clear_rect({}, {}, {}, {})", x, y, width, height));
+    }
+    fn fill_text(&self, text: &str, x: f64, y: f64) {
+        self.record(format!("fill_text({:?}, {}, {})", text, x, y));
+    }
+    fn measure_text_width(&self, text: &str) -> f64 {
+        text.chars().count() as f64 * self.char_width
+    }
+    fn set_fill_style(&self, style: &str) {
+        self.record(format!("set_fill_style({:?})", style));
+    }
+    fn set_stroke_style(&self, style: &str) {
+        self.record(format!("set_stroke_style({:?})", style));
+    }
+    fn set_line_width(&self, width: f64) {
+        self.record(format!("set_line_width({})", width));
+    }
+    fn set_font(&self, font: &str) {
+        self.record(format!("set_font({:?})", font));
+    }
+    fn set_text_align(&self, align: &str) {
+        self.record(format!("set_text_align({:?})", align));
+    }
+    fn set_text_baseline(&self, baseline: &str) {
+        self.record(format!("set_text_baseline({:?})", baseline));
+    }
+}
+
+#[derive(Clone)]
+struct SvgStyle {
+    fill: String,
+    stroke: String,
+    line_width: f64,
+    font: String,
+    text_align: String,
+    text_baseline: String,
+    clip_id: Option<usize>,
+    transform: String,
+}
+
+struct SvgState {
+    elements: String,
+    path: String,
+    style: SvgStyle,
+    saved: Vec<SvgStyle>,
+    clips: usize,
+}
+
+// accumulates the drawing as svg elements, e.g. for vector export
+pub struct SvgRenderer {
+    state: RefCell<SvgState>,
+    font_width_coeff: f64,
+}
+impl SvgRenderer {
+    pub fn new(font_width_coeff: f64) -> Self {
+        Self {
+            state: RefCell::new(SvgState {
+                elements: String::new(),
+                path: String::new(),
+                style: SvgStyle {
+                    fill: "black".to_string(),
+                    stroke: "black".to_string(),
+                    line_width: 1.0,
+                    font: "10px sans-serif".to_string(),
+                    text_align: "start".to_string(),
+                    text_baseline: "alphabetic".to_string(),
+                    clip_id: None,
+                    transform: String::new(),
+                },
+                saved: Vec::new(),
+                clips: 0,
+            }),
+            font_width_coeff,
+        }
+    }
+    pub fn to_svg(&self, width: f64, height: f64) -> String {
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">{}</svg>",
+            self.state.borrow().elements,
+            w = width,
+            h = height,
+        )
+    }
+    fn escape(text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    }
+    fn get_font_size(font: &str) -> f64 {
+        font.split_whitespace()
+            .find_map(|part| part.strip_suffix("px")?.parse::<f64>().ok())
+            .unwrap_or(10.0)
+    }
+    fn push_element(&self, tag: &str, attrs: String, text: Option<&str>) {
+        let mut state = self.state.borrow_mut();
+        let clip = match state.style.clip_id {
+            Some(clip_id) => format!(" clip-path=\"url(#clip{})\"", clip_id),
+            None => String::new(),
+        };
+        // clip paths are in the user space of the clipped element, so a
+        // transformed element is clipped by a wrapping group
+        let (attrs, wrap_clip) = if state.style.transform.is_empty() {
+            (format!("{}{}", attrs, clip), None)
+        } else {
+            let attrs = format!("{} transform=\"{}\"", attrs, state.style.transform);
+            (attrs, Some(clip).filter(|clip| !clip.is_empty()))
+        };
+        let element = match text {
+            Some(text) => format!("<{} {}>{}</{}>", tag, attrs, Self::escape(text), tag),
+            None => format!("<{} {}/>", tag, attrs),
+        };
+        let element = match wrap_clip {
+            Some(clip) => format!("<g{}>{}</g>", clip, element),
+            None => element,
+        };
+        state.elements.push_str(element.as_str());
+    }
+    fn push_transform(&self, transform: String) {
+        let mut state = self.state.borrow_mut();
+        if !state.style.transform.is_empty() {
+            state.style.transform.push(' ');
+        }
+        state.style.transform.push_str(transform.as_str());
+    }
+    fn push_path(&self, cmd: String) {
+        let mut state = self.state.borrow_mut();
+        if !state.path.is_empty() {
+            state.path.push(' ');
+        }
+        state.path.push_str(cmd.as_str());
+    }
+}
+impl Renderer for SvgRenderer {
+    fn save(&self) {
+        let mut state = self.state.borrow_mut();
+        let style = state.style.clone();
+        state.saved.push(style);
+    }
+    fn restore(&self) {
+        let mut state = self.state.borrow_mut();
+        if let Some(style) = state.saved.pop() {
+            state.style = style;
+        }
+    }
+    fn begin_path(&self) {
+        self.state.borrow_mut().path.clear();
+    }
+    fn close_path(&self) {
+        self.push_path("Z".to_string());
+    }
+    fn move_to(&self, x: f64, y: f64) {
+        self.push_path(format!("M{} {}", x, y));
+    }
+    fn line_to(&self, x: f64, y: f64) {
+        self.push_path(format!("L{} {}", x, y));
+    }
+    fn bezier_curve_to(&self, cp1x: f64, cp1y: f64, cp2x: f64, cp2y: f64, x: f64, y: f64) {
+        self.push_path(format!("C{} {} {} {} {} {}", cp1x, cp1y, cp2x, cp2y, x, y));
+    }
+    fn arc(&self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64) {
+        let (start_x, start_y) = (
+            x + radius * start_angle.cos(),
+            y + radius * start_angle.sin(),
+        );
+        let connect = if self.state.borrow().path.is_empty() {
+            "M"
+        } else {
+            "L"
+        };
+        self.push_path(format!("{}{} {}", connect, start_x, start_y));
+        let sweep = end_angle - start_angle;
+        if sweep >= std::f64::consts::TAU {
+            // a full circle can't be a single svg arc, so it takes two halves
+            self.push_path(format!(
+                "A{r} {r} 0 1 1 {} {} A{r} {r} 0 1 1 {} {}",
+                2.0 * x - start_x,
+                2.0 * y - start_y,
+                start_x,
+                start_y,
+                r = radius,
+            ));
+        } else {
+            self.push_path(format!(
+                "A{r} {r} 0 {} 1 {} {}",
+                if sweep.rem_euclid(std::f64::consts::TAU) > std::f64::consts::PI {
+                    1
+                } else {
+                    0
+                },
+                x + radius * end_angle.cos(),
+                y + radius * end_angle.sin(),
+                r = radius,
+            ));
+        }
+    }
+    fn rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        self.push_path(format!("M{} {} h{} v{} h{} Z", x, y, width, height, -width));
+    }
+    fn clip(&self) {
+        let mut state = self.state.borrow_mut();
+        let clip_id = state.clips;
+        state.clips += 1;
+        let transform = if state.style.transform.is_empty() {
+            String::new()
+        } else {
+            format!(" transform=\"{}\"", state.style.transform)
+        };
+        let element = format!(
+            "<clipPath id=\"clip{}\"><path d=\"{}\"{}/></clipPath>",
+            clip_id, state.path, transform
+        );
+        state.elements.push_str(element.as_str());
+        state.style.clip_id = Some(clip_id);
+    }
+    fn translate(&self, x: f64, y: f64) {
+        self.push_transform(format!("translate({} {})", x, y));
+    }
+    fn rotate(&self, angle: f64) {
+        self.push_transform(format!("rotate({})", angle.to_degrees()));
+    }
+    fn stroke(&self) {
+        let attrs = {
+            let state = self.state.borrow();
+            format!(
+                "d=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\"",
+                state.path, state.style.stroke, state.style.line_width
+            )
+        };
+        self.push_element("path", attrs, None);
+    }
+    fn fill(&self) {
+        let attrs = {
+            let state = self.state.borrow();
+            format!("d=\"{}\" fill=\"{}\"", state.path, state.style.fill)
+        };
+        self.push_element("path", attrs, None);
+    }
+    fn fill_rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        let fill = self.state.borrow().style.fill.clone();
+        self.push_element(
+            "rect",
+            format!(
+                "x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"",
+                x, y, width, height, fill
+            ),
+            None,
+        );
+    }
+    fn stroke_rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        let (stroke, line_width) = {
+            let state = self.state.borrow();
+            (state.style.stroke.clone(), state.style.line_width)
+        };
+        self.push_element(
+            "rect",
+            format!(
+                "x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\"",
+                x, y, width, height, stroke, line_width
+            ),
+            None,
+        );
+    }
+    // a single draw pass has nothing stale to clear
+    fn clear_rect(&self, _x: f64, _y: f64, _width: f64, _height: f64) {}
+    fn fill_text(&self, text: &str, x: f64, y: f64) {
+        let attrs = {
+            let style = &self.state.borrow().style;
+            format!(
+                "x=\"{}\" y=\"{}\" fill=\"{}\" style=\"font: {}\" text-anchor=\"{}\" dominant-baseline=\"{}\"",
+                x,
+                y,
+                style.fill,
+                Self::escape(style.font.as_str()),
+                match style.text_align.as_str() {
+                    "center" => "middle",
+                    "right" | "end" => "end",
+                    _ => "start",
+                },
+                match style.text_baseline.as_str() {
+                    "top" => "text-before-edge",
+                    "hanging" => "hanging",
+                    "middle" => "middle",
+                    "bottom" => "text-after-edge",
+                    _ => "alphabetic",
+                },
+            )
+        };
+        self.push_element("text", attrs, Some(text));
+    }
+    fn measure_text_width(&self, text: &str) -> f64 {
+        text.chars().count() as f64
+            * Self::get_font_size(self.state.borrow().style.font.as_str())
+            * self.font_width_coeff
+    }
+    fn set_fill_style(&self, style: &str) {
+        self.state.borrow_mut().style.fill = style.to_string();
+    }
+    fn set_stroke_style(&self, style: &str) {
+        self.state.borrow_mut().style.stroke = style.to_string();
+    }
+    fn set_line_width(&self, width: f64) {
+        self.state.borrow_mut().style.line_width = width;
+    }
+    fn set_font(&self, font: &str) {
+        self.state.borrow_mut().style.font = font.to_string();
+    }
+    fn set_text_align(&self, align: &str) {
+        self.state.borrow_mut().style.text_align = align.to_string();
+    }
+    fn set_text_baseline(&self, baseline: &str) {
+        self.state.borrow_mut().style.text_baseline = baseline.to_string();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::render::{RecordingRenderer, Renderer, SvgRenderer};
+
+    #[test]
+    fn test_recording_renderer() {
+        let renderer = RecordingRenderer::new(5.0);
+        let dyn_renderer: &dyn Renderer = &renderer;
+        dyn_renderer.begin_path();
+        dyn_renderer.move_to(0.0, 1.5);
+        dyn_renderer.line_to(10.0, 2.0);
+        dyn_renderer.set_stroke_style("rgba(0, 0, 0, 1)");
+        dyn_renderer.stroke();
+        assert_eq!(dyn_renderer.measure_text_width("abc"), 15.0);
+        assert_eq!(
+            renderer.take_calls(),
+            vec![
+                "begin_path()",
+                "move_to(0, 1.5)",
+                "line_to(10, 2)",
+                "set_stroke_style(\"rgba(0, 0, 0, 1)\")",
+                "stroke()",
+            ]
+        );
+        assert!(renderer.take_calls().is_empty());
+    }
+
+    #[test]
+    fn test_svg_renderer() {
+        let renderer = SvgRenderer::new(0.5);
+        renderer.set_stroke_style("rgba(1, 2, 3, 1)");
+        renderer.set_line_width(2.0);
+        renderer.begin_path();
+        renderer.move_to(0.0, 0.0);
+        renderer.line_to(10.0, 5.0);
+        renderer.bezier_curve_to(12.0, 5.0, 14.0, 6.0, 16.0, 8.0);
+        renderer.stroke();
+
+        renderer.save();
+        renderer.begin_path();
+        renderer.rect(0.0, 0.0, 50.0, 20.0);
+        renderer.clip();
+        renderer.set_fill_style("red");
+        renderer.fill_rect(1.0, 2.0, 3.0, 4.0);
+        renderer.restore();
+
+        renderer.set_font("10px monospace");
+        renderer.set_text_align("center");
+        renderer.fill_text("a < b", 5.0, 6.0);
+        assert_eq!(renderer.measure_text_width("abcd"), 20.0);
+
+        assert_eq!(
+            renderer.to_svg(100.0, 50.0),
+            concat!(
+                "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"100\" height=\"50\" viewBox=\"0 0 100 50\">",
+                "<path d=\"M0 0 L10 5 C12 5 14 6 16 8\" fill=\"none\" stroke=\"rgba(1, 2, 3, 1)\" stroke-width=\"2\"/>",
+                "<clipPath id=\"clip0\"><path d=\"M0 0 h50 v20 h-50 Z\"/></clipPath>",
+                "<rect x=\"1\" y=\"2\" width=\"3\" height=\"4\" fill=\"red\" clip-path=\"url(#clip0)\"/>",
+                "<text x=\"5\" y=\"6\" fill=\"black\" style=\"font: 10px monospace\" text-anchor=\"middle\" dominant-baseline=\"alphabetic\">a &lt; b</text>",
+                "</svg>",
+            )
+        );
+    }
+
+    #[test]
+    fn test_svg_transform() {
+        let renderer = SvgRenderer::new(0.5);
+        renderer.begin_path();
+        renderer.rect(0.0, 0.0, 50.0, 20.0);
+        renderer.clip();
+        renderer.save();
+        renderer.translate(5.0, 10.0);
+        renderer.rotate(-std::f64::consts::FRAC_PI_2);
+        renderer.fill_text("y", 0.0, 0.0);
+        renderer.restore();
+        renderer.fill_rect(1.0, 2.0, 3.0, 4.0);
+        let svg = renderer.to_svg(50.0, 20.0);
+        assert!(svg.contains(concat!(
+            "<g clip-path=\"url(#clip0)\"><text x=\"0\" y=\"0\" fill=\"black\" ",
+            "style=\"font: 10px sans-serif\" text-anchor=\"start\" dominant-baseline=\"alphabetic\" ",
+            "transform=\"translate(5 10) rotate(-90)\">y</text></g>"
+        )));
+        assert!(svg.contains(
+            "<rect x=\"1\" y=\"2\" width=\"3\" height=\"4\" fill=\"black\" clip-path=\"url(#clip0)\"/>"
+        ));
+    }
+
+    #[test]
+    fn test_svg_full_circle() {
+        let renderer = SvgRenderer::new(0.5);
+        renderer.begin_path();
+        renderer.arc(10.0, 10.0, 2.0, 0.0, std::f64::consts::TAU);
+        renderer.fill();
+        assert!(renderer
+            .to_svg(20.0, 20.0)
+            .contains("d=\"M12 10 A2 2 0 1 1 8 10 A2 2 0 1 1 12 10\""));
+    }
+}
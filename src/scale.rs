@@ -8,6 +8,7 @@
 use crate::params::Content;
 
 pub trait Scale: Clone {
+    fn new(content: &Content) -> Self;
     fn reframe(&mut self, coord_min: f64, coord_max: f64, value_min: f64, value_max: f64);
     fn get_coord_min(&self) -> f64;
     fn get_coord_max(&self) -> f64;
@@ -17,6 +18,30 @@ pub trait Scale: Clone {
     fn normalize_value(&self, value: f64) -> f64;
     fn denormalize_coord(&self, normalized_coord: f64) -> f64;
     fn denormalize_value(&self, normalized_value: f64) -> f64;
+    // values of ticks between the two, at least 1/max_ticks of the normalized
+    // range apart; None leaves them to the grid
+    fn get_value_ticks(
+        &self,
+        _value_min: f64,
+        _value_max: f64,
+        _max_ticks: f64,
+    ) -> Option<Vec<f64>> {
+        None
+    }
+}
+
+// inverted or infinite ranges (e.g. everything hidden) keep the last valid frame
+fn is_valid_range(range: f64) -> bool {
+    range > 0.0 && range.is_finite()
+}
+
+// a flat series or a single point still gets a window to be drawn in
+pub fn expand_zero_range(min: f64, max: f64) -> (f64, f64) {
+    if min != max {
+        return (min, max);
+    }
+    let half_width = if min == 0.0 { 1.0 } else { min.abs() * 0.05 };
+    (min - half_width, max + half_width)
 }
 
 #[derive(Clone)]
@@ -30,12 +55,13 @@ pub struct LinearScale {
     pub value_range: f64,
     pub value_range_recip: f64,
 }
-impl LinearScale {
-    pub fn new(content: &Content) -> Self {
-        let global_coord_min = content.global_coord_min;
-        let global_coord_max = content.global_coord_max;
-        let global_value_min = content.global_value_min;
-        let global_value_max = content.global_value_max;
+
+impl Scale for LinearScale {
+    fn new(content: &Content) -> Self {
+        let (global_coord_min, global_coord_max) =
+            expand_zero_range(content.global_coord_min, content.global_coord_max);
+        let (global_value_min, global_value_max) =
+            expand_zero_range(content.global_value_min, content.global_value_max);
         let coord_range = global_coord_max - global_coord_min;
         let value_range = global_value_max - global_value_min;
         Self {
@@ -49,17 +75,13 @@ impl LinearScale {
             value_range_recip: value_range.recip(),
         }
     }
-}
-
-impl Scale for LinearScale {
     fn reframe(&mut self, coord_min: f64, coord_max: f64, value_min: f64, value_max: f64) {
+        let (coord_min, coord_max) = expand_zero_range(coord_min, coord_max);
+        let (value_min, value_max) = expand_zero_range(value_min, value_max);
         let coord_range = coord_max - coord_min;
-        if coord_range == 0.0 {
-            panic!("coord range cannot be zero")
-        }
         let value_range = value_max - value_min;
-        if value_range == 0.0 {
-            panic!("value range cannot be zero")
+        if !is_valid_range(coord_range) || !is_valid_range(value_range) {
+            return;
         }
         self.coord_max = coord_max;
         self.coord_min = coord_min;
@@ -119,13 +141,16 @@ pub struct LogScale {
     pub value_log_base: f64,
     pub value_log_range: f64,
     pub value_log_range_recip: f64,
+    // ticks go to its powers
+    pub base: f64,
 }
-impl LogScale {
-    pub fn new(content: &Content) -> Self {
-        let global_coord_min = content.global_coord_min;
-        let global_coord_max = content.global_coord_max;
-        let global_value_min = content.global_value_min;
-        let global_value_max = content.global_value_max;
+
+impl Scale for LogScale {
+    fn new(content: &Content) -> Self {
+        let (global_coord_min, global_coord_max) =
+            expand_zero_range(content.global_coord_min, content.global_coord_max);
+        let (global_value_min, global_value_max) =
+            expand_zero_range(content.global_value_min, content.global_value_max);
         let coord_range = global_coord_max - global_coord_min;
 
         let value_min_log = MIN_LOG_VALUE;
@@ -141,20 +166,16 @@ impl LogScale {
             value_log_base: value_min_log,
             value_log_range: value_max_log - value_min_log,
             value_log_range_recip: (value_max_log - value_min_log).recip(),
+            base: content.log_base,
         }
     }
-}
-
-impl Scale for LogScale {
     fn reframe(&mut self, coord_min: f64, coord_max: f64, value_min: f64, value_max: f64) {
+        let (coord_min, coord_max) = expand_zero_range(coord_min, coord_max);
+        let (value_min, value_max) = expand_zero_range(value_min, value_max);
         let coord_range = coord_max - coord_min;
-        if coord_range == 0.0 {
-            panic!("coord range cannot be zero")
-        }
-
         let value_range = value_max - value_min;
-        if value_range == 0.0 {
-            panic!("value range cannot be zero")
+        if !is_valid_range(coord_range) || !is_valid_range(value_range) {
+            return;
         }
         let value_min_log = (self.value_min - self.value_global_min + MIN_VALUE_TO_LOG).log10();
         let value_max_log = (value_max - self.value_global_min + MIN_VALUE_TO_LOG).log10();
@@ -203,4 +224,122 @@ impl Scale for LogScale {
     fn get_value_max(&self) -> f64 {
         self.value_max
     }
+    // powers of the base crowd where the scale is close to linear, so the
+    // largest ones are kept first
+    fn get_value_ticks(&self, value_min: f64, value_max: f64, max_ticks: f64) -> Option<Vec<f64>> {
+        let min_gap =
+            (self.normalize_value(value_max) - self.normalize_value(value_min)) / max_ticks;
+        if !min_gap.is_finite() || min_gap <= 0.0 || value_max <= 0.0 {
+            return Some(Vec::new());
+        }
+        let power_min = if value_min > 0.0 {
+            value_min.log(self.base).floor() as i32
+        } else {
+            0
+        };
+        let power_max = value_max.log(self.base).ceil() as i32;
+        let mut kept: Vec<(f64, f64)> = Vec::new();
+        for power in (power_min..=power_max).rev() {
+            let value = self.base.powi(power);
+            if value < value_min || value > value_max {
+                continue;
+            }
+            let normalized_value = self.normalize_value(value);
+            if kept
+                .iter()
+                .all(|(_, kept_normalized)| (kept_normalized - normalized_value).abs() >= min_gap)
+            {
+                kept.push((value, normalized_value));
+            }
+        }
+        Some(kept.into_iter().rev().map(|(value, _)| value).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::animate::Animation;
+    use crate::grid::Grid;
+    use crate::params::{Content, DataType};
+    use crate::scale::{expand_zero_range, LinearScale, LogScale, Scale};
+
+    fn check_keeps_last_frame<T: Scale>() {
+        let mut content = Content::default();
+        content
+            .parse_and_add_data_set("a", vec![1.0, 4.0], vec![2.0, 3.0], (0, 0, 0), 0.0)
+            .unwrap();
+        content.recompute_global_bounds();
+        let mut scale = T::new(&content);
+        scale.reframe(1.0, 3.0, 2.0, 3.0);
+        scale.reframe(3.0, 1.0, 2.0, 3.0);
+        scale.reframe(f64::MAX, f64::MIN, f64::MAX, f64::MIN);
+        scale.reframe(1.0, 3.0, f64::NAN, 3.0);
+        assert_eq!(scale.get_coord_min(), 1.0);
+        assert_eq!(scale.get_coord_max(), 3.0);
+        assert_eq!(scale.get_value_min(), 2.0);
+        assert_eq!(scale.get_value_max(), 3.0);
+        assert!(scale.normalize_coord(2.0).is_finite());
+        assert!(scale.normalize_value(2.5).is_finite());
+    }
+
+    #[test]
+    fn test_reframe_keeps_last_valid_frame() {
+        check_keeps_last_frame::<LinearScale>();
+        check_keeps_last_frame::<LogScale>();
+    }
+
+    fn check_constant_series<T: Scale>() {
+        let mut content = Content::default();
+        content
+            .parse_and_add_data_set("a", vec![1.0, 2.0, 3.0], vec![5.0; 3], (0, 0, 0), 0.0)
+            .unwrap();
+        content.recompute_global_bounds();
+        let mut scale = T::new(&content);
+        assert!(scale.get_value_min() < 5.0 && scale.get_value_max() > 5.0);
+        assert!((scale.normalize_value(5.0) - 0.5).abs() < 0.01);
+        // a single visible point after zooming in
+        scale.reframe(2.0, 2.0, 5.0, 5.0);
+        assert!(scale.get_coord_min() < 2.0 && scale.get_coord_max() > 2.0);
+        assert!(scale.normalize_coord(2.0).is_finite());
+        assert!(scale.normalize_value(5.0).is_finite());
+    }
+
+    #[test]
+    fn test_constant_series() {
+        check_constant_series::<LinearScale>();
+        check_constant_series::<LogScale>();
+        assert_eq!(expand_zero_range(5.0, 5.0), (4.75, 5.25));
+        assert_eq!(expand_zero_range(0.0, 0.0), (-1.0, 1.0));
+        assert_eq!(expand_zero_range(1.0, 2.0), (1.0, 2.0));
+
+        let mut grid = Grid::new(DataType::Number, 5.0, 5.0, None);
+        let ticks = grid.get_ticks(0.0, Animation::default(), 0.0, 1.0, 5.0);
+        assert!(!ticks.is_empty());
+        assert!(ticks.iter().all(|tick| tick.value.is_finite()));
+    }
+
+    #[test]
+    fn test_log_value_ticks() {
+        let mut content = Content::default();
+        content
+            .parse_and_add_data_set("a", vec![1.0, 2.0], vec![1.0, 1e6], (0, 0, 0), 0.0)
+            .unwrap();
+        content.recompute_global_bounds();
+        let scale = LogScale::new(&content);
+        let ticks = scale.get_value_ticks(1.0, 1e6, 10.0).unwrap();
+        // 10 & 100 would overlap 1 & 1000, where the scale is close to linear
+        assert_eq!(ticks, vec![1.0, 1e3, 1e4, 1e5, 1e6]);
+        assert!(LinearScale::new(&content)
+            .get_value_ticks(1.0, 1e6, 10.0)
+            .is_none());
+
+        content.log_base = 2.0;
+        let scale = LogScale::new(&content);
+        let ticks = scale.get_value_ticks(1e3, 1e6, 10.0).unwrap();
+        assert!(ticks.iter().all(|tick| tick.log2().fract() == 0.0));
+        assert!(ticks.contains(&524288.0));
+        assert!(ticks.windows(2).all(|pair| scale.normalize_value(pair[1])
+            - scale.normalize_value(pair[0])
+            >= (scale.normalize_value(1e6) - scale.normalize_value(1e3)) / 10.0));
+    }
 }
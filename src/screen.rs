@@ -5,9 +5,12 @@
  *
  * Copyright (C) 2023, Nikita Almakov
  */
-use crate::animate::AnimatedNumber;
+use crate::animate::{AnimatedNumber, Animation};
+use crate::data_set::{DataPoint, Interpolation};
 use crate::params::ChartConfig;
 use crate::params::ClientCaps;
+pub use crate::pos::{DefineSize, ScreenPos, Size};
+use crate::render::Renderer;
 use crate::scale::Scale;
 use crate::versioned::Versioned;
 use js_sys::Reflect;
@@ -15,60 +18,6 @@ use std::cell::{Ref, RefCell};
 use std::rc::Rc;
 use wasm_bindgen::{JsCast, JsValue};
 
-pub trait DefineSize {
-    fn get_css_to_physical_scale(self) -> f64;
-    fn get_font_width_to_physical_scale(self) -> f64;
-    fn get_font_height_to_physical_scale(self) -> f64;
-    fn get_content_width(self) -> f64;
-    fn get_content_height(self) -> f64;
-}
-
-#[derive(Clone, Debug)]
-pub enum Size {
-    Px(f64),
-    TextLine { font_size: f64, columns: f64 },
-    Pct(f64),
-}
-impl Size {
-    pub fn to_cpx_width<T>(&self, size_def: T) -> f64
-    where
-        T: DefineSize,
-    {
-        match self {
-            Self::Px(v) => *v * size_def.get_css_to_physical_scale(),
-            Self::TextLine { font_size, columns } => {
-                *font_size * *columns as f64 * size_def.get_font_width_to_physical_scale()
-            }
-            Self::Pct(v) => *v * size_def.get_content_width(),
-        }
-    }
-    pub fn to_cpx_height<T>(&self, size_def: T) -> f64
-    where
-        T: DefineSize,
-    {
-        match self {
-            Self::Px(v) => *v * size_def.get_css_to_physical_scale(),
-            Self::TextLine { font_size, .. } => {
-                *font_size * size_def.get_font_height_to_physical_scale()
-            }
-            Self::Pct(v) => *v * size_def.get_content_height(),
-        }
-    }
-    pub fn mul(&self, x: f64) -> Self {
-        match self {
-            Self::Px(v) => Self::Px(v * x),
-            Self::TextLine { font_size, columns } => Self::TextLine {
-                font_size: *font_size,
-                columns: columns * x,
-            },
-            Self::Pct(v) => Self::Pct(v * x),
-        }
-    }
-}
-
-#[derive(Clone, PartialEq)]
-pub struct ScreenPos(pub f64, pub f64);
-
 #[derive(Clone)]
 pub struct ScreenState {
     pub canvas_width: f64,
@@ -103,12 +52,20 @@ impl DefineSize for &ScreenState {
     }
 }
 
+struct Offscreen {
+    canvas: web_sys::HtmlCanvasElement,
+    crc: Rc<web_sys::CanvasRenderingContext2d>,
+    live_state: ScreenState,
+}
+
 pub struct Screen {
     config: Rc<RefCell<ChartConfig>>,
     client_caps: Rc<RefCell<ClientCaps>>,
     pub canvas: web_sys::HtmlCanvasElement,
     pub crc: Rc<web_sys::CanvasRenderingContext2d>,
     state: RefCell<ScreenState>,
+    offscreen: RefCell<Option<Offscreen>>,
+    recording: RefCell<Option<Rc<dyn Renderer>>>,
 }
 impl Screen {
     pub fn new(
@@ -121,7 +78,7 @@ impl Screen {
         let container = document
             .query_selector(container_selector)
             .unwrap()
-            .ok_or_else(|| "container not found".to_string())?;
+            .ok_or_else(|| format!("container not found: '{}'", container_selector))?;
 
         let canvas = document
             .create_element("canvas")
@@ -130,6 +87,9 @@ impl Screen {
             .unwrap();
         container.append_child(&canvas).unwrap();
         canvas.set_attribute("style", style).unwrap();
+        if config.borrow().rtl {
+            canvas.set_attribute("dir", "rtl").unwrap();
+        }
 
         let context_options = js_sys::Object::new();
         let crc = canvas
@@ -152,6 +112,8 @@ impl Screen {
                 sync_requests: 1,
                 syncs: 0,
             }),
+            offscreen: RefCell::new(None),
+            recording: RefCell::new(None),
         };
         result.sync_canvas_size();
         Ok(result)
@@ -206,9 +168,87 @@ impl Screen {
     }
     pub fn clear(&self) {
         let state = self.get_state();
-        self.crc
+        self.get_crc()
             .clear_rect(0.0, 0.0, state.canvas_width, state.canvas_height);
     }
+    pub fn get_crc(&self) -> Rc<dyn Renderer> {
+        if let Some(renderer) = self.recording.borrow().as_ref() {
+            return Rc::clone(renderer);
+        }
+        match self.offscreen.borrow().as_ref() {
+            Some(offscreen) => Rc::clone(&offscreen.crc) as Rc<dyn Renderer>,
+            None => Rc::clone(&self.crc) as Rc<dyn Renderer>,
+        }
+    }
+    // until end_recording, everything is drawn to the given renderer at the
+    // live size; area handles are rebuilt to pick it up
+    pub fn begin_recording(&self, renderer: Rc<dyn Renderer>) {
+        drop(self.get_state());
+        self.recording.replace(Some(renderer));
+        self.invalidate_handles();
+    }
+    pub fn end_recording(&self) {
+        self.recording.replace(None);
+        self.invalidate_handles();
+    }
+    fn invalidate_handles(&self) {
+        let mut state = self.state.borrow_mut();
+        let sync_pending = state.sync_requests != state.syncs;
+        state.syncs += 1;
+        state.sync_requests = if sync_pending {
+            state.syncs + 1
+        } else {
+            state.syncs
+        };
+    }
+    // until end_offscreen, everything is drawn to a hidden canvas of the scaled
+    // size, leaving the live one untouched
+    pub fn begin_offscreen(&self, scale: f64) -> Result<(), String> {
+        let live_state = self.get_state().clone();
+        let mut state = live_state.clone();
+        state.canvas_width *= scale;
+        state.canvas_height *= scale;
+        state.css_to_physical_scale *= scale;
+        state.font_height_to_physical_scale *= scale;
+        state.font_width_to_physical_scale *= scale;
+        state.syncs += 1;
+        state.sync_requests = state.syncs;
+
+        let canvas = web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap()
+            .create_element("canvas")
+            .unwrap()
+            .dyn_into::<web_sys::HtmlCanvasElement>()
+            .unwrap();
+        canvas.set_width(state.canvas_width as u32);
+        canvas.set_height(state.canvas_height as u32);
+        let crc = canvas
+            .get_context("2d")
+            .unwrap()
+            .ok_or_else(|| "failed to get canvas 2d crc".to_string())?
+            .dyn_into::<web_sys::CanvasRenderingContext2d>()
+            .unwrap();
+
+        *self.state.borrow_mut() = state;
+        self.offscreen.replace(Some(Offscreen {
+            canvas,
+            crc: Rc::new(crc),
+            live_state,
+        }));
+        Ok(())
+    }
+    pub fn end_offscreen(&self) -> Option<web_sys::HtmlCanvasElement> {
+        let offscreen = self.offscreen.borrow_mut().take()?;
+        let mut state = self.state.borrow_mut();
+        let syncs = state.syncs + 1;
+        let sync_pending = offscreen.live_state.sync_requests != offscreen.live_state.syncs;
+        *state = offscreen.live_state;
+        state.syncs = syncs;
+        state.sync_requests = if sync_pending { syncs + 1 } else { syncs };
+        Some(offscreen.canvas)
+    }
 }
 
 pub type Padding = Versioned<[Size; 4]>;
@@ -289,7 +329,7 @@ impl ScreenArea {
         ];
         let screen_state = self.screen.get_state();
         let handle = Rc::new(ScreenAreaHandle {
-            crc: Rc::clone(&self.screen.crc),
+            crc: self.screen.get_crc(),
             screen_width: screen_state.canvas_width,
             screen_height: screen_state.canvas_height,
 
@@ -310,7 +350,7 @@ impl ScreenArea {
 }
 
 pub struct ScreenAreaHandle {
-    pub crc: Rc<web_sys::CanvasRenderingContext2d>,
+    pub crc: Rc<dyn Renderer>,
     pub screen_width: f64,
     pub screen_height: f64,
 
@@ -459,11 +499,17 @@ where
         value_min: f64,
         value_max: f64,
         time_us: Option<f64>,
+        animation: Animation,
     ) {
-        self.coord_min.set_value(coord_min, time_us);
-        self.coord_max.set_value(coord_max, time_us);
-        self.value_min.set_value(value_min, time_us);
-        self.value_max.set_value(value_max, time_us);
+        self.coord_min.set_value(coord_min, time_us, animation);
+        self.coord_max.set_value(coord_max, time_us, animation);
+        self.value_min.set_value(value_min, time_us, animation);
+        self.value_max.set_value(value_max, time_us, animation);
+        self.scale_time_us = 0.0;
+    }
+
+    pub fn set_scale(&mut self, scale: T) {
+        self.scale = Some(Rc::new(scale));
         self.scale_time_us = 0.0;
     }
 
@@ -485,6 +531,59 @@ where
         }
     }
 }
+// connects the points of a line as the interpolation says; curves need all
+// the points of a segment, so they are buffered until it ends
+struct PathTracer<'a> {
+    crc: &'a dyn Renderer,
+    interpolation: Interpolation,
+    prev: (f64, f64),
+    segment: Vec<(f64, f64)>,
+}
+impl<'a> PathTracer<'a> {
+    fn new(crc: &'a dyn Renderer, interpolation: Interpolation) -> Self {
+        Self {
+            crc,
+            interpolation,
+            prev: (0.0, 0.0),
+            segment: Vec::new(),
+        }
+    }
+    // a new segment is either moved to or connected to the current point
+    fn start(&mut self, x: f64, y: f64, connect: bool) {
+        self.flush();
+        if connect {
+            self.crc.line_to(x, y);
+        } else {
+            self.crc.move_to(x, y);
+        }
+        if self.interpolation == Interpolation::Monotone {
+            self.segment.push((x, y));
+        }
+        self.prev = (x, y);
+    }
+    fn line_to(&mut self, x: f64, y: f64) {
+        if self.interpolation == Interpolation::Monotone {
+            self.segment.push((x, y));
+        } else {
+            if let Some((corner_x, corner_y)) = self.interpolation.get_corner(self.prev, (x, y)) {
+                self.crc.line_to(corner_x, corner_y);
+            }
+            self.crc.line_to(x, y);
+        }
+        self.prev = (x, y);
+    }
+    fn flush(&mut self) {
+        for (((cp1x, cp1y), (cp2x, cp2y)), (x, y)) in
+            Interpolation::get_monotone_control_points(self.segment.as_slice())
+                .into_iter()
+                .zip(self.segment.iter().skip(1))
+        {
+            self.crc.bezier_curve_to(cp1x, cp1y, cp2x, cp2y, *x, *y);
+        }
+        self.segment.clear();
+    }
+}
+
 pub struct CoordSpaceHandle<T>
 where
     T: Scale,
@@ -518,6 +617,145 @@ where
             None
         }
     }
+    // points closer than min_step (canvas px) to the previous drawn one are
+    // skipped; step corners are not, so the holds stay level & curves are
+    // fitted through the points which are left
+    pub fn trace_line(
+        &self,
+        data_points: &[DataPoint],
+        max_gap: Option<f64>,
+        connect_gaps: bool,
+        interpolation: Interpolation,
+        min_step: f64,
+    ) {
+        let mut tracer = PathTracer::new(self.screen_area_handle.crc.as_ref(), interpolation);
+        let max_gap = max_gap.filter(|_| !connect_gaps);
+        let mut prev: Option<(f64, f64)> = None;
+        let mut prev_coord = f64::NAN;
+        let mut skipped: Option<(f64, f64)> = None;
+        let mut x: f64;
+        let mut y: f64;
+        for data_point in data_points.iter() {
+            // NaN values break the line, the next finite point starts a new one
+            if data_point.value.is_nan() {
+                if connect_gaps {
+                    continue;
+                }
+                if let Some((skipped_x, skipped_y)) = skipped.take() {
+                    tracer.line_to(skipped_x, skipped_y);
+                }
+                prev = None;
+                continue;
+            }
+            x = self.get_cx(data_point.coord);
+            y = self.get_cy(data_point.value);
+            match prev {
+                Some((prev_x, prev_y)) => {
+                    if max_gap.is_some_and(|max_gap| data_point.coord - prev_coord > max_gap) {
+                        if let Some((skipped_x, skipped_y)) = skipped.take() {
+                            tracer.line_to(skipped_x, skipped_y);
+                        }
+                        tracer.start(x, y, false);
+                        prev = Some((x, y));
+                    } else if x - prev_x >= min_step || (y - prev_y).abs() >= min_step {
+                        tracer.line_to(x, y);
+                        prev = Some((x, y));
+                        skipped = None;
+                    } else {
+                        skipped = Some((x, y));
+                    }
+                }
+                None => {
+                    tracer.start(x, y, false);
+                    prev = Some((x, y));
+                }
+            }
+            prev_coord = data_point.coord;
+        }
+        tracer.flush();
+    }
+    pub fn trace_area(
+        &self,
+        data_points: &[DataPoint],
+        max_gap: Option<f64>,
+        connect_gaps: bool,
+        interpolation: Interpolation,
+        base_cy: f64,
+    ) {
+        let crc = self.screen_area_handle.crc.as_ref();
+        let mut tracer = PathTracer::new(crc, interpolation);
+        let mut in_segment = false;
+        let mut last_x: f64 = 0.0;
+        let mut prev_coord = f64::NAN;
+        let mut x: f64;
+        for data_point in data_points.iter() {
+            let breaks = !connect_gaps
+                && (data_point.value.is_nan()
+                    || max_gap.is_some_and(|max_gap| data_point.coord - prev_coord > max_gap));
+            if in_segment && breaks {
+                tracer.flush();
+                crc.line_to(last_x, base_cy);
+                crc.close_path();
+                in_segment = false;
+            }
+            if data_point.value.is_nan() {
+                continue;
+            }
+            x = self.get_cx(data_point.coord);
+            if in_segment {
+                tracer.line_to(x, self.get_cy(data_point.value));
+            } else {
+                crc.move_to(x, base_cy);
+                tracer.start(x, self.get_cy(data_point.value), true);
+                in_segment = true;
+            }
+            last_x = x;
+            prev_coord = data_point.coord;
+        }
+        if in_segment {
+            tracer.flush();
+            crc.line_to(last_x, base_cy);
+            crc.close_path();
+        }
+    }
+    // a polygon along the lower bounds & back along the upper ones; points
+    // closer than min_step are skipped, missing bounds are connected across
+    pub fn trace_band(&self, band_low: &[DataPoint], band_high: &[DataPoint], min_step: f64) {
+        let crc = self.screen_area_handle.crc.as_ref();
+        let mut started = false;
+        let mut last_x = f64::NEG_INFINITY;
+        for (low, high) in band_low.iter().zip(band_high.iter()) {
+            if low.value.is_nan() || high.value.is_nan() {
+                continue;
+            }
+            let x = self.get_cx(low.coord);
+            if started && x - last_x < min_step {
+                continue;
+            }
+            if started {
+                crc.line_to(x, self.get_cy(low.value));
+            } else {
+                crc.move_to(x, self.get_cy(low.value));
+                started = true;
+            }
+            last_x = x;
+        }
+        last_x = f64::INFINITY;
+        for (low, high) in band_low.iter().zip(band_high.iter()).rev() {
+            if low.value.is_nan() || high.value.is_nan() {
+                continue;
+            }
+            let x = self.get_cx(high.coord);
+            if last_x - x < min_step {
+                continue;
+            }
+            crc.line_to(x, self.get_cy(high.value));
+            last_x = x;
+        }
+        if started {
+            crc.close_path();
+        }
+    }
     pub fn get_value(&self, pos: &ScreenPos) -> Option<f64> {
         let normalized_value = 1.0
             - (pos.1 * self.screen_area_handle.css_to_physical_scale
@@ -571,6 +809,9 @@ impl ScreenRect {
             cy2: cy + height,
         }
     }
+    pub fn intersects(&self, other: &ScreenRect) -> bool {
+        self.cx1 < other.cx2 && self.cx2 > other.cx1 && self.cy1 < other.cy2 && self.cy2 > other.cy1
+    }
     pub fn contains(&self, cx: f64, cy: f64) -> bool {
         self.cx1 <= cx && self.cx2 >= cx && self.cy1 <= cy && self.cy2 >= cy
     }
@@ -0,0 +1,217 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ *
+ * Copyright (C) 2023, Nikita Almakov
+ */
+
+// the render state of a chart, deterministic under animate: false, so it can
+// be compared against golden files instead of pixels
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartSnapshot {
+    pub coord_min: f64,
+    pub coord_max: f64,
+    pub value_min: f64,
+    pub value_max: f64,
+    pub zoomed_in: bool,
+    pub coord_ticks: Vec<f64>,
+    pub value_ticks: Vec<f64>,
+    pub visible_series: Vec<String>,
+    pub tooltip_visible: bool,
+    pub tooltip_pinned: bool,
+    pub matched_coord: Option<f64>,
+    pub legend_offset: usize,
+    pub legend_items_shown: usize,
+    pub legend_has_next: bool,
+}
+
+fn number_to_json(value: f64) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "null".to_string()
+    }
+}
+fn string_to_json(value: &str) -> String {
+    let mut result = String::with_capacity(value.len() + 2);
+    result.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            c if (c as u32) < 0x20 => result.push_str(format!("\\u{:04x}", c as u32).as_str()),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+fn array_to_json<I: Iterator<Item = String>>(values: I) -> String {
+    format!("[{}]", values.collect::<Vec<String>>().join(", "))
+}
+
+impl ChartSnapshot {
+    // (group, key, json value) in a fixed order
+    fn entries(&self) -> Vec<(&'static str, &'static str, String)> {
+        vec![
+            ("viewport", "coordMin", number_to_json(self.coord_min)),
+            ("viewport", "coordMax", number_to_json(self.coord_max)),
+            ("viewport", "valueMin", number_to_json(self.value_min)),
+            ("viewport", "valueMax", number_to_json(self.value_max)),
+            ("viewport", "zoomedIn", self.zoomed_in.to_string()),
+            (
+                "ticks",
+                "coord",
+                array_to_json(self.coord_ticks.iter().map(|&v| number_to_json(v))),
+            ),
+            (
+                "ticks",
+                "value",
+                array_to_json(self.value_ticks.iter().map(|&v| number_to_json(v))),
+            ),
+            (
+                "",
+                "visibleSeries",
+                array_to_json(self.visible_series.iter().map(|name| string_to_json(name))),
+            ),
+            ("tooltip", "visible", self.tooltip_visible.to_string()),
+            ("tooltip", "pinned", self.tooltip_pinned.to_string()),
+            (
+                "tooltip",
+                "matchedCoord",
+                self.matched_coord
+                    .map_or_else(|| "null".to_string(), number_to_json),
+            ),
+            ("legend", "offset", self.legend_offset.to_string()),
+            ("legend", "itemsShown", self.legend_items_shown.to_string()),
+            ("legend", "hasNext", self.legend_has_next.to_string()),
+        ]
+    }
+
+    // pretty printed with one entry per line, so golden files diff cleanly
+    pub fn to_json(&self) -> String {
+        let mut lines: Vec<String> = Vec::new();
+        let mut open_group: &str = "";
+        for (group, key, value) in self.entries() {
+            if group != open_group {
+                if !open_group.is_empty() {
+                    lines.push("  },".to_string());
+                }
+                if !group.is_empty() {
+                    lines.push(format!("  \"{group}\": {{"));
+                }
+                open_group = group;
+            }
+            let indent = if group.is_empty() { "  " } else { "    " };
+            lines.push(format!("{indent}\"{key}\": {value},"));
+        }
+        if !open_group.is_empty() {
+            lines.push("  },".to_string());
+        }
+        // no trailing commas before closing braces
+        for index in 0..lines.len() {
+            let closes = lines
+                .get(index + 1)
+                .is_none_or(|next| next.trim_start().starts_with('}'));
+            if closes {
+                if let Some(line) = lines[index].strip_suffix(',') {
+                    lines[index] = line.to_string();
+                }
+            }
+        }
+        format!("{{\n{}\n}}", lines.join("\n"))
+    }
+
+    // one "path: before -> after" line per changed entry
+    pub fn diff(&self, other: &Self) -> Vec<String> {
+        self.entries()
+            .into_iter()
+            .zip(other.entries())
+            .filter(|((_, _, before), (_, _, after))| before != after)
+            .map(|((group, key, before), (_, _, after))| {
+                if group.is_empty() {
+                    format!("{key}: {before} -> {after}")
+                } else {
+                    format!("{group}.{key}: {before} -> {after}")
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::snapshot::ChartSnapshot;
+
+    fn snapshot() -> ChartSnapshot {
+        ChartSnapshot {
+            coord_min: 0.0,
+            coord_max: 10.0,
+            value_min: -1.5,
+            value_max: 3.0,
+            zoomed_in: false,
+            coord_ticks: vec![0.0, 5.0, 10.0],
+            value_ticks: vec![-1.0, 0.0, 1.0, 2.0, 3.0],
+            visible_series: vec!["a".to_string(), "b \"c\"".to_string()],
+            tooltip_visible: false,
+            tooltip_pinned: false,
+            matched_coord: None,
+            legend_offset: 0,
+            legend_items_shown: 2,
+            legend_has_next: false,
+        }
+    }
+
+    #[test]
+    fn test_to_json() {
+        assert_eq!(
+            snapshot().to_json(),
+            r#"{
+  "viewport": {
+    "coordMin": 0,
+    "coordMax": 10,
+    "valueMin": -1.5,
+    "valueMax": 3,
+    "zoomedIn": false
+  },
+  "ticks": {
+    "coord": [0, 5, 10],
+    "value": [-1, 0, 1, 2, 3]
+  },
+  "visibleSeries": ["a", "b \"c\""],
+  "tooltip": {
+    "visible": false,
+    "pinned": false,
+    "matchedCoord": null
+  },
+  "legend": {
+    "offset": 0,
+    "itemsShown": 2,
+    "hasNext": false
+  }
+}"#
+        );
+    }
+    #[test]
+    fn test_diff() {
+        let before = snapshot();
+        assert!(before.diff(&before.clone()).is_empty());
+
+        let mut after = snapshot();
+        after.coord_max = 5.0;
+        after.coord_ticks = vec![0.0, 2.5, 5.0];
+        after.visible_series.pop();
+        after.matched_coord = Some(2.5);
+        assert_eq!(
+            before.diff(&after),
+            vec![
+                "viewport.coordMax: 10 -> 5",
+                "ticks.coord: [0, 5, 10] -> [0, 2.5, 5]",
+                "visibleSeries: [\"a\", \"b \\\"c\\\"\"] -> [\"a\"]",
+                "tooltip.matchedCoord: null -> 2.5",
+            ]
+        );
+    }
+}
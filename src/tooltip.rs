@@ -7,14 +7,16 @@
  */
 use crate::animate::AnimatedNumber;
 use crate::data_set::{DataPoint, DataSet};
-use crate::params::{ChartConfig, Content};
+use crate::params::{
+    ChartConfig, Content, TooltipMatch, TooltipMode, TooltipPosition, TooltipRenderer,
+};
 use crate::scale::Scale;
-use crate::screen::{CoordSpaceHandle, ScreenPos, Size};
+use crate::screen::{CoordSpaceHandle, ScreenAreaHandle, ScreenPos, ScreenRect, Size};
 use crate::utils::place_rect_inside;
 use std::cell::RefCell;
 use std::f64::consts::PI;
 use std::rc::Rc;
-use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsValue;
 
 const LINE_WIDTH: Size = Size::Px(1.0);
 const PADDING: Size = Size::Px(5.0);
@@ -28,6 +30,66 @@ pub struct Tooltip {
     pub chart_config: Rc<RefCell<ChartConfig>>,
     min_width: AnimatedNumber,
     pub visible: bool,
+    pub matched_coord: Option<f64>,
+    // tooltips already drawn this frame, which the next ones are kept off
+    pub placed: Vec<ScreenRect>,
+    pub on_tooltip: Option<js_sys::Function>,
+    // tooltips shown this frame, the callback is only called when their
+    // keys change
+    pub reported: Vec<TooltipReport>,
+    notified_key: String,
+}
+
+// what onTooltip gets for a tooltip, positions are in css pixels relative to
+// the chart
+#[derive(Debug, PartialEq)]
+pub struct TooltipReport {
+    pub key: String,
+    pub coord: f64,
+    pub formatted_coord: String,
+    // (name, value, formatted value, css color) in the tooltip order
+    pub rows: Vec<(String, f64, String, String)>,
+    pub pointer: (f64, f64),
+    pub point: (f64, f64),
+}
+
+impl TooltipReport {
+    fn to_js(&self) -> JsValue {
+        let to_object = |entries: &[(&str, JsValue)]| -> JsValue {
+            let object = js_sys::Object::new();
+            for (key, value) in entries.iter() {
+                js_sys::Reflect::set(&object, &JsValue::from_str(key), value).unwrap();
+            }
+            object.into()
+        };
+        let rows: js_sys::Array = self
+            .rows
+            .iter()
+            .map(|(name, value, formatted_value, color)| {
+                to_object(&[
+                    ("name", JsValue::from_str(name.as_str())),
+                    ("value", JsValue::from_f64(*value)),
+                    (
+                        "formattedValue",
+                        JsValue::from_str(formatted_value.as_str()),
+                    ),
+                    ("color", JsValue::from_str(color.as_str())),
+                ])
+            })
+            .collect();
+        to_object(&[
+            ("coord", JsValue::from_f64(self.coord)),
+            (
+                "formattedCoord",
+                JsValue::from_str(self.formatted_coord.as_str()),
+            ),
+            ("rows", rows.into()),
+            ("x", JsValue::from_f64(self.pointer.0)),
+            ("y", JsValue::from_f64(self.pointer.1)),
+            ("pointX", JsValue::from_f64(self.point.0)),
+            ("pointY", JsValue::from_f64(self.point.1)),
+        ])
+    }
 }
 
 impl Tooltip {
@@ -36,6 +98,11 @@ impl Tooltip {
             chart_config,
             min_width: AnimatedNumber::custom(0.0, 500000.0, 500000.0),
             visible: false,
+            matched_coord: None,
+            placed: Vec::new(),
+            on_tooltip: None,
+            reported: Vec::new(),
+            notified_key: String::new(),
         }
     }
 
@@ -49,6 +116,7 @@ impl Tooltip {
     ) where
         T: Scale,
     {
+        self.matched_coord = None;
         let pointer = match pointer {
             Some(pointer) => pointer,
             None => {
@@ -56,7 +124,8 @@ impl Tooltip {
                 return;
             }
         };
-        let conf = self.chart_config.borrow();
+        let chart_config = Rc::clone(&self.chart_config);
+        let conf = chart_config.borrow();
         let screen_area_handle = coord_space_handle.screen_area_handle.as_ref();
         let crc = screen_area_handle.crc.as_ref();
 
@@ -80,54 +149,81 @@ impl Tooltip {
         let (coord, value, pointer_cx, pointer_cy) = data.unwrap();
         self.visible = true;
 
-        let mut max_coord: f64 = f64::MIN;
-        let mut left_matches: Vec<(&DataSet, &DataPoint)> =
-            Vec::with_capacity(content.data_sets.len());
-        for data_set in content.data_sets.iter() {
-            if data_set.alpha.get_end_value() == 0.0 {
-                continue;
-            }
-            if let Some(index) = data_set.bin_search_right_bound(coord) {
-                let data_point = &data_set.data_points[index];
-                if max_coord < data_point.coord {
-                    max_coord = data_point.coord;
+        let interpolated: Vec<(&DataSet, DataPoint)> = if conf.interpolate_tooltip {
+            Self::interpolate(content, coord)
+        } else {
+            Vec::new()
+        };
+        let (matched_coord, mut matches, cx_step_size) = match conf.tooltip_match {
+            _ if conf.interpolate_tooltip => {
+                if interpolated.is_empty() {
+                    return;
                 }
-                left_matches.push((data_set, data_point));
+                (
+                    coord,
+                    interpolated
+                        .iter()
+                        .map(|(data_set, data_point)| (*data_set, data_point))
+                        .collect(),
+                    0.0,
+                )
             }
-        }
-        left_matches.retain(|m| m.1.coord == max_coord);
+            TooltipMatch::Nearest => match Self::match_nearest(content, coord) {
+                Some((matched_coord, matches)) => (matched_coord, matches, 0.0),
+                None => return,
+            },
+            TooltipMatch::Coord => {
+                let mut max_coord: f64 = f64::MIN;
+                let mut left_matches: Vec<(&DataSet, &DataPoint)> =
+                    Vec::with_capacity(content.data_sets.len());
+                for data_set in content.data_sets.iter() {
+                    if data_set.alpha.get_end_value() == 0.0 {
+                        continue;
+                    }
+                    if let Some(index) = data_set.bin_search_defined_right_bound(coord) {
+                        let data_point = &data_set.data_points[index];
+                        if max_coord < data_point.coord {
+                            max_coord = data_point.coord;
+                        }
+                        left_matches.push((data_set, data_point));
+                    }
+                }
+                left_matches.retain(|m| m.1.coord == max_coord);
 
-        if left_matches.len() == 0 {
-            return;
-        }
+                if left_matches.len() == 0 {
+                    return;
+                }
 
-        let mut min_coord: f64 = f64::MAX;
-        let mut right_matches: Vec<(&DataSet, &DataPoint)> =
-            Vec::with_capacity(content.data_sets.len());
-        for data_set in content.data_sets.iter() {
-            if data_set.alpha.get_end_value() == 0.0 {
-                continue;
-            }
-            if let Some(index) = data_set.bin_search_left_bound(coord) {
-                let data_point = &data_set.data_points[index];
-                if min_coord > data_point.coord {
-                    min_coord = data_point.coord;
+                let mut min_coord: f64 = f64::MAX;
+                let mut right_matches: Vec<(&DataSet, &DataPoint)> =
+                    Vec::with_capacity(content.data_sets.len());
+                for data_set in content.data_sets.iter() {
+                    if data_set.alpha.get_end_value() == 0.0 {
+                        continue;
+                    }
+                    if let Some(index) = data_set.bin_search_defined_left_bound(coord) {
+                        let data_point = &data_set.data_points[index];
+                        if min_coord > data_point.coord {
+                            min_coord = data_point.coord;
+                        }
+                        right_matches.push((data_set, data_point));
+                    }
+                }
+                right_matches.retain(|m| m.1.coord == min_coord);
+
+                let cx_step_size =
+                    coord_space_handle.get_cx(min_coord) - coord_space_handle.get_cx(max_coord);
+                if (coord - min_coord).abs() < (coord - max_coord).abs() {
+                    (min_coord, right_matches, cx_step_size)
+                } else {
+                    (max_coord, left_matches, cx_step_size)
                 }
-                right_matches.push((data_set, data_point));
             }
-        }
-        right_matches.retain(|m| m.1.coord == min_coord);
-
-        let cx_step_size =
-            coord_space_handle.get_cx(min_coord) - coord_space_handle.get_cx(max_coord);
-        let matched_coord: f64;
-        let mut matches = if (coord - min_coord).abs() < (coord - max_coord).abs() {
-            matched_coord = min_coord;
-            right_matches
-        } else {
-            matched_coord = max_coord;
-            left_matches
         };
+        self.matched_coord = Some(matched_coord);
+        // sorted before the highlight is picked, so it follows the moved row
+        conf.tooltip_sort
+            .sort(matches.as_mut_slice(), |(_, data_point)| data_point.value);
 
         let mut min_diff: f64 = f64::MAX;
         let mut index_with_min_diff_by_value: usize = 0;
@@ -140,7 +236,6 @@ impl Tooltip {
         }
 
         let coord_format = &content.coord_verbose_format;
-        let value_format = &content.value_verbose_format;
 
         let formatted_coord = coord_format
             .format_values(
@@ -159,17 +254,80 @@ impl Tooltip {
             .map(|t| t.0.name.len())
             .max()
             .unwrap();
-        let formatted_values = value_format.format_values(
-            matches.iter().cloned(),
-            |t| t.1.value,
-            global_scale.get_value_min(),
-            global_scale.get_value_max(),
-        );
+        let formatted_values = Self::format_match_values(content, global_scale, matches.as_slice());
         let max_formatted_value_length: usize =
             formatted_values.iter().map(|v| v.len()).max().unwrap();
 
         let c_line_width = LINE_WIDTH.to_cpx_height(screen_area_handle);
         let c_padding = PADDING.to_cpx_height(screen_area_handle);
+
+        let anchor = {
+            let data_point = matches[index_with_min_diff_by_value].1;
+            (
+                coord_space_handle.get_cx(data_point.coord),
+                coord_space_handle.get_cy(data_point.value),
+            )
+        };
+        if conf.spike_line {
+            let (data_set, data_point) = matches[index_with_min_diff_by_value];
+            Self::draw_spike_line(
+                &conf,
+                &coord_space_handle,
+                data_point,
+                data_set.to_css_color(1.0).as_str(),
+                formatted_coord.as_str(),
+            );
+        }
+
+        if self.on_tooltip.is_some() {
+            self.report(
+                screen_area_handle,
+                (matched_coord, formatted_coord.as_str()),
+                matches.as_slice(),
+                formatted_values.as_slice(),
+                pointer,
+                anchor,
+            );
+        }
+        if conf.tooltip_renderer == TooltipRenderer::Custom {
+            Self::draw_match_circles(
+                &conf,
+                &coord_space_handle,
+                matches.as_slice(),
+                index_with_min_diff_by_value,
+            );
+            return;
+        }
+
+        if let TooltipMode::Compact = conf.tooltip_mode {
+            let text = format!("{}: {}", formatted_coord, formatted_values.join(", "));
+            let (tooltip_width, tooltip_height) =
+                Self::compact_size(&conf, screen_area_handle, text.as_str());
+            let (tooltip_x, tooltip_y) = self.place(
+                screen_area_handle,
+                conf.tooltip_position,
+                (pointer_cx, pointer_cy),
+                anchor,
+                (tooltip_width, tooltip_height),
+                EXPECTED_SHIFT_X
+                    .to_cpx_height(screen_area_handle)
+                    .max(cx_step_size * 0.125),
+            );
+            Self::draw_match_circles(
+                &conf,
+                &coord_space_handle,
+                matches.as_slice(),
+                index_with_min_diff_by_value,
+            );
+            Self::draw_compact(
+                &conf,
+                screen_area_handle,
+                (tooltip_x, tooltip_y),
+                (tooltip_width, tooltip_height),
+                text.as_str(),
+            );
+            return;
+        }
         let c_additional_gap_after_heading =
             ADDITIONAL_GAP_AFTER_HEADING.to_cpx_height(screen_area_handle);
         let c_gap_between_lines = GAP_BETWEEN_LINES.to_cpx_height(screen_area_handle);
@@ -188,19 +346,46 @@ impl Tooltip {
             .max(cx_step_size * 0.125);
         let c_heading_width = content.coord_short_verbose_len as f64 * c_font_width;
 
+        let c_max_width = conf
+            .tooltip_max_width
+            .as_ref()
+            .map(|max_width| max_width.to_cpx_width(screen_area_handle));
+        // names get whatever is left of the capped width once values are fit
+        let max_name_length = match c_max_width {
+            Some(c_max_width) => max_name_length.min(
+                ((c_max_width
+                    - c_padding * 2.0
+                    - c_color_size
+                    - c_gap_between_colors_n_names
+                    - c_gap_between_names_n_values)
+                    / c_font_width
+                    - max_formatted_value_length as f64)
+                    .max(1.0) as usize,
+            ),
+            None => max_name_length,
+        };
+
         let mut tooltip_width = c_heading_width.max(
             c_color_size
                 + c_gap_between_colors_n_names
                 + (max_name_length + max_formatted_value_length) as f64 * c_font_width
                 + c_gap_between_names_n_values,
         ) + c_padding * 2.0;
-        let tooltip_min_width = self.min_width.get_value(time_us);
+        if let Some(c_max_width) = c_max_width {
+            tooltip_width = tooltip_width.min(c_max_width);
+        }
+        let tooltip_min_width = match c_max_width {
+            Some(c_max_width) => self.min_width.get_value(time_us).min(c_max_width),
+            None => self.min_width.get_value(time_us),
+        };
         if tooltip_width > tooltip_min_width {
-            self.min_width.set_value(tooltip_width, None);
+            self.min_width
+                .set_value(tooltip_width, None, conf.animation);
         }
         if tooltip_width < tooltip_min_width {
             if tooltip_width < self.min_width.get_end_value() {
-                self.min_width.set_value(tooltip_width, Some(time_us));
+                self.min_width
+                    .set_value(tooltip_width, Some(time_us), conf.animation);
             }
             tooltip_width = tooltip_min_width;
         }
@@ -212,20 +397,35 @@ impl Tooltip {
         let tooltip_height = tooltip_height.min(screen_area_handle.canvas_content_height);
 
         let bottom_cy = screen_area_handle.bottom_cy() - c_line_width;
-        let (tooltip_x, tooltip_y) = place_rect_inside(
-            pointer_cx,
-            pointer_cy,
-            tooltip_width,
-            tooltip_height,
-            c_line_width,
-            screen_area_handle.right_cx() - c_line_width,
-            bottom_cy,
+        let (tooltip_x, tooltip_y) = self.place(
+            screen_area_handle,
+            conf.tooltip_position,
+            (pointer_cx, pointer_cy),
+            anchor,
+            (tooltip_width, tooltip_height),
             c_expected_tooltip_shift_x,
         );
 
-        let color_x = tooltip_x + c_padding;
-        let name_x = color_x + c_color_size + c_gap_between_colors_n_names;
-        let value_x = tooltip_x + tooltip_width - c_padding;
+        // names & values swap sides for right-to-left layouts
+        let (color_x, name_x, value_x, name_align, value_align) = if conf.rtl {
+            let color_x = tooltip_x + tooltip_width - c_padding - c_color_size;
+            (
+                color_x,
+                color_x - c_gap_between_colors_n_names,
+                tooltip_x + c_padding,
+                "right",
+                "left",
+            )
+        } else {
+            let color_x = tooltip_x + c_padding;
+            (
+                color_x,
+                color_x + c_color_size + c_gap_between_colors_n_names,
+                tooltip_x + tooltip_width - c_padding,
+                "left",
+                "right",
+            )
+        };
         let heading_y = tooltip_y + c_padding;
         let start_y = heading_y
             + c_heading_lines as f64 * c_font_size
@@ -243,37 +443,18 @@ impl Tooltip {
             hidden_lines = 0;
         }
 
-        let transparent_color = JsValue::from_str("rgba(0, 0, 0, 0)");
-        for (index, (data_set, data_point)) in matches.iter().enumerate() {
-            let color = JsValue::from_str(data_set.to_css_color(1.0).as_str());
-            crc.begin_path();
-            crc.set_line_width(conf.line_width.to_cpx_height(screen_area_handle));
-            if index_with_min_diff_by_value == index {
-                crc.set_fill_style(&color);
-            } else {
-                crc.set_fill_style(&transparent_color);
-            }
-
-            crc.set_stroke_style(&color);
-            crc.arc(
-                coord_space_handle.get_cx(data_point.coord),
-                coord_space_handle.get_cy(data_point.value),
-                conf.circle_diameter.to_cpx_height(screen_area_handle),
-                0.0,
-                PI * 2.0,
-            )
-            .unwrap();
-            crc.fill();
-            crc.stroke();
-        }
+        Self::draw_match_circles(
+            &conf,
+            &coord_space_handle,
+            matches.as_slice(),
+            index_with_min_diff_by_value,
+        );
 
         let v = &conf.color_tooltip_font;
-        let font_color =
-            JsValue::from_str(format!("rgba({}, {}, {}, {})", v.0, v.1, v.2, v.3,).as_str());
+        let font_color = format!("rgba({}, {}, {}, {})", v.0, v.1, v.2, v.3,);
 
         let v = &conf.color_tooltip;
-        let background_color =
-            JsValue::from_str(format!("rgba({}, {}, {}, {})", v.0, v.1, v.2, v.3,).as_str());
+        let background_color = format!("rgba({}, {}, {}, {})", v.0, v.1, v.2, v.3,);
 
         crc.set_line_width(c_line_width);
         crc.set_fill_style(&background_color);
@@ -289,7 +470,7 @@ impl Tooltip {
             )
             .as_str(),
         );
-        crc.set_fill_style(&JsValue::from_str("black"));
+        crc.set_fill_style("black");
 
         crc.set_text_baseline("top");
         crc.set_text_align("center");
@@ -297,8 +478,7 @@ impl Tooltip {
             formatted_coord.as_str(),
             tooltip_x + tooltip_width * 0.5,
             heading_y,
-        )
-        .unwrap();
+        );
 
         crc.set_text_baseline("top");
 
@@ -309,7 +489,7 @@ impl Tooltip {
             .enumerate()
         {
             let y = start_y + delta_y * index as f64;
-            let color = JsValue::from_str(data_set.to_css_color(1.0).as_str());
+            let color = data_set.to_css_color(1.0);
 
             crc.set_fill_style(&color);
             crc.fill_rect(color_x, y, c_color_size, c_color_size);
@@ -329,16 +509,20 @@ impl Tooltip {
                 )
                 .as_str(),
             );
-            crc.set_text_align("left");
-            crc.fill_text(data_set.name.as_str(), name_x, y).unwrap();
+            crc.set_text_align(name_align);
+            crc.fill_text(
+                Self::truncate_name(data_set.name.as_str(), max_name_length).as_str(),
+                name_x,
+                y,
+            );
 
-            crc.set_text_align("right");
-            crc.fill_text(formatted_value.as_str(), value_x, y).unwrap();
+            crc.set_text_align(value_align);
+            crc.fill_text(formatted_value.as_str(), value_x, y);
         }
 
         if hidden_lines > 0 {
             let y = start_y + delta_y * matches.len() as f64;
-            crc.set_text_align("left");
+            crc.set_text_align(name_align);
             crc.set_fill_style(&font_color);
             crc.set_font(
                 format!(
@@ -348,8 +532,651 @@ impl Tooltip {
                 )
                 .as_str(),
             );
-            crc.fill_text(format!("{} hidden", hidden_lines).as_str(), name_x, y)
+            crc.fill_text(
+                conf.hint_hidden
+                    .replacen("{}", hidden_lines.to_string().as_str(), 1)
+                    .as_str(),
+                name_x,
+                y,
+            );
+        }
+    }
+
+    // every visible data set contributes its point closest to the coord, the
+    // heading shows the closest of them
+    pub fn match_nearest(
+        content: &Content,
+        coord: f64,
+    ) -> Option<(f64, Vec<(&DataSet, &DataPoint)>)> {
+        let mut matches: Vec<(&DataSet, &DataPoint)> = Vec::with_capacity(content.data_sets.len());
+        for data_set in content.data_sets.iter() {
+            if data_set.alpha.get_end_value() == 0.0 {
+                continue;
+            }
+            let nearest = [
+                data_set.bin_search_defined_right_bound(coord),
+                data_set.bin_search_defined_left_bound(coord),
+            ]
+            .into_iter()
+            .flatten()
+            .map(|index| &data_set.data_points[index])
+            .min_by(|a, b| {
+                (a.coord - coord)
+                    .abs()
+                    .partial_cmp(&(b.coord - coord).abs())
+                    .unwrap()
+            });
+            if let Some(data_point) = nearest {
+                matches.push((data_set, data_point));
+            }
+        }
+        let matched_coord = matches
+            .iter()
+            .map(|(_, data_point)| data_point.coord)
+            .min_by(|a, b| (a - coord).abs().partial_cmp(&(b - coord).abs()).unwrap())?;
+        Some((matched_coord, matches))
+    }
+
+    pub fn interpolate(content: &Content, coord: f64) -> Vec<(&DataSet, DataPoint)> {
+        content
+            .data_sets
+            .iter()
+            .filter(|data_set| data_set.alpha.get_end_value() != 0.0)
+            .filter_map(|data_set| {
+                data_set
+                    .interpolate_value(coord)
+                    .map(|value| (data_set, DataPoint { coord, value }))
+            })
+            .collect()
+    }
+
+    // a single "coord: value, value" line, also used over the preview
+    pub fn compact_text<T>(
+        content: &Content,
+        scale: &T,
+        matched_coord: f64,
+        matches: &[(&DataSet, &DataPoint)],
+    ) -> String
+    where
+        T: Scale,
+    {
+        let formatted_coord = content
+            .coord_verbose_format
+            .format_values(
+                Some(matched_coord).into_iter(),
+                |x| x,
+                scale.get_coord_min(),
+                scale.get_coord_max(),
+            )
+            .into_iter()
+            .next()
+            .unwrap();
+        let formatted_values = Self::format_match_values(content, scale, matches);
+        format!("{}: {}", formatted_coord, formatted_values.join(", "))
+    }
+
+    // values of data sets with a band are followed by its bounds
+    fn format_match_values<T>(
+        content: &Content,
+        scale: &T,
+        matches: &[(&DataSet, &DataPoint)],
+    ) -> Vec<String>
+    where
+        T: Scale,
+    {
+        let value_format = &content.value_verbose_format;
+        let value_suffix = content.value_transform.get_suffix();
+        let format = |values: &[f64]| -> Vec<String> {
+            value_format
+                .format_values(
+                    values.iter(),
+                    |value| *value,
+                    scale.get_value_min(),
+                    scale.get_value_max(),
+                )
+                .into_iter()
+                .map(|value| value + value_suffix)
+                .collect()
+        };
+        let values: Vec<f64> = matches.iter().map(|t| t.1.value).collect();
+        format(values.as_slice())
+            .into_iter()
+            .zip(matches.iter())
+            .map(|(formatted_value, (data_set, data_point))| {
+                let bounds = data_set
+                    .bin_search_left_bound(data_point.coord)
+                    .filter(|index| data_set.data_points[*index].coord == data_point.coord)
+                    .and_then(|index| {
+                        Some((
+                            data_set.band_low.get(index)?,
+                            data_set.band_high.get(index)?,
+                        ))
+                    })
+                    .filter(|(low, high)| !low.value.is_nan() && !high.value.is_nan());
+                match bounds {
+                    Some((low, high)) => {
+                        let formatted_bounds = format(&[low.value, high.value]);
+                        format!(
+                            "{} [{} – {}]",
+                            formatted_value, formatted_bounds[0], formatted_bounds[1]
+                        )
+                    }
+                    None => formatted_value,
+                }
+            })
+            .collect()
+    }
+
+    pub fn compact_size(
+        conf: &ChartConfig,
+        screen_area_handle: &ScreenAreaHandle,
+        text: &str,
+    ) -> (f64, f64) {
+        let c_padding = PADDING.to_cpx_height(screen_area_handle);
+        (
+            text.chars().count() as f64 * conf.font_size_small.to_cpx_width(screen_area_handle)
+                + c_padding * 2.0,
+            conf.font_size_small.to_cpx_height(screen_area_handle) + c_padding * 2.0,
+        )
+    }
+
+    pub fn draw_compact(
+        conf: &ChartConfig,
+        screen_area_handle: &ScreenAreaHandle,
+        (tooltip_x, tooltip_y): (f64, f64),
+        (tooltip_width, tooltip_height): (f64, f64),
+        text: &str,
+    ) {
+        let crc = screen_area_handle.crc.as_ref();
+        let v = &conf.color_tooltip;
+        crc.set_fill_style(format!("rgba({}, {}, {}, {})", v.0, v.1, v.2, v.3,).as_str());
+        crc.fill_rect(tooltip_x, tooltip_y, tooltip_width, tooltip_height);
+        let v = &conf.color_tooltip_font;
+        crc.set_fill_style(format!("rgba({}, {}, {}, {})", v.0, v.1, v.2, v.3,).as_str());
+        crc.set_font(
+            format!(
+                "{:.0}px {}",
+                conf.font_size_small.to_cpx_height(screen_area_handle),
+                conf.font_monospace.as_str()
+            )
+            .as_str(),
+        );
+        crc.set_text_baseline("middle");
+        crc.set_text_align("center");
+        crc.fill_text(
+            text,
+            tooltip_x + tooltip_width * 0.5,
+            tooltip_y + tooltip_height * 0.5,
+        );
+    }
+
+    fn report(
+        &mut self,
+        screen_area_handle: &ScreenAreaHandle,
+        (matched_coord, formatted_coord): (f64, &str),
+        matches: &[(&DataSet, &DataPoint)],
+        formatted_values: &[String],
+        pointer: &ScreenPos,
+        (anchor_cx, anchor_cy): (f64, f64),
+    ) {
+        let rows = matches
+            .iter()
+            .zip(formatted_values.iter())
+            .map(|((data_set, data_point), formatted_value)| {
+                (
+                    data_set.name.clone(),
+                    data_point.value,
+                    formatted_value.clone(),
+                    data_set.to_css_color(1.0),
+                )
+            })
+            .collect();
+        let scale = screen_area_handle.css_to_physical_scale;
+        self.reported.push(TooltipReport {
+            key: format!(
+                "{}:{}:{}:{}",
+                matched_coord,
+                pointer.0,
+                pointer.1,
+                formatted_values.join(",")
+            ),
+            coord: matched_coord,
+            formatted_coord: formatted_coord.to_string(),
+            rows,
+            pointer: (pointer.0, pointer.1),
+            point: (anchor_cx / scale, anchor_cy / scale),
+        });
+    }
+    // whether the reported tooltips differ from the last notified ones,
+    // remembering them as notified
+    fn take_reported_changes(&mut self) -> bool {
+        let key: Vec<&str> = self
+            .reported
+            .iter()
+            .map(|report| report.key.as_str())
+            .collect();
+        let key = key.join(";");
+        if key == self.notified_key {
+            return false;
+        }
+        self.notified_key = key;
+        true
+    }
+    // deferred like onReady, so the callback is free to call back into the
+    // chart; an empty array means no tooltip is shown
+    pub fn notify(&mut self) {
+        if !self.take_reported_changes() {
+            return;
+        }
+        if let Some(on_tooltip) = self.on_tooltip.as_ref() {
+            let payload: js_sys::Array = self.reported.iter().map(TooltipReport::to_js).collect();
+            web_sys::window()
+                .unwrap()
+                .set_timeout_with_callback_and_timeout_and_arguments_1(on_tooltip, 0, &payload)
                 .unwrap();
         }
     }
+
+    // drops from the highlighted point to the coord axis, which gets a boxed
+    // label of the coord over its ticks
+    fn draw_spike_line<T>(
+        conf: &ChartConfig,
+        coord_space_handle: &CoordSpaceHandle<T>,
+        data_point: &DataPoint,
+        color: &str,
+        formatted_coord: &str,
+    ) where
+        T: Scale,
+    {
+        let screen_area_handle = coord_space_handle.screen_area_handle.as_ref();
+        let crc = screen_area_handle.crc.as_ref();
+        let cx = coord_space_handle.get_cx(data_point.coord);
+        let bottom_cy = screen_area_handle.bottom_cy();
+        crc.begin_path();
+        crc.set_line_width(LINE_WIDTH.to_cpx_height(screen_area_handle));
+        crc.set_stroke_style(color);
+        crc.move_to(cx, coord_space_handle.get_cy(data_point.value));
+        crc.line_to(cx, bottom_cy);
+        crc.stroke();
+
+        let c_padding = PADDING.to_cpx_height(screen_area_handle);
+        let label_width = formatted_coord.chars().count() as f64
+            * conf.font_size_small.to_cpx_width(screen_area_handle)
+            + c_padding * 2.0;
+        let label_height = conf.font_size_small.to_cpx_height(screen_area_handle) + c_padding * 2.0;
+        let label_x = (cx - label_width * 0.5)
+            .min(screen_area_handle.right_cx() - label_width)
+            .max(screen_area_handle.left_cx());
+        let v = &conf.color_tooltip;
+        crc.set_fill_style(format!("rgba({}, {}, {}, {})", v.0, v.1, v.2, v.3,).as_str());
+        crc.fill_rect(label_x, bottom_cy, label_width, label_height);
+        let v = &conf.color_tooltip_font;
+        let font_color = format!("rgba({}, {}, {}, {})", v.0, v.1, v.2, v.3,);
+        crc.set_stroke_style(font_color.as_str());
+        crc.stroke_rect(label_x, bottom_cy, label_width, label_height);
+        crc.set_fill_style(font_color.as_str());
+        crc.set_font(
+            format!(
+                "{:.0}px {}",
+                conf.font_size_small.to_cpx_height(screen_area_handle),
+                conf.font_monospace.as_str()
+            )
+            .as_str(),
+        );
+        crc.set_text_baseline("middle");
+        crc.set_text_align("center");
+        crc.fill_text(
+            formatted_coord,
+            label_x + label_width * 0.5,
+            bottom_cy + label_height * 0.5,
+        );
+    }
+
+    pub fn truncate_name(name: &str, max_length: usize) -> String {
+        if name.chars().count() <= max_length {
+            return name.to_string();
+        }
+        let mut truncated: String = name.chars().take(max_length.saturating_sub(1)).collect();
+        truncated.push('…');
+        truncated
+    }
+
+    // keeps tooltips drawn within the same frame off each other; the anchor
+    // is the highlighted point
+    fn place(
+        &mut self,
+        screen_area_handle: &ScreenAreaHandle,
+        position: TooltipPosition,
+        (pointer_cx, pointer_cy): (f64, f64),
+        (anchor_cx, anchor_cy): (f64, f64),
+        (tooltip_width, tooltip_height): (f64, f64),
+        c_expected_shift_x: f64,
+    ) -> (f64, f64) {
+        let c_line_width = LINE_WIDTH.to_cpx_height(screen_area_handle);
+        let c_padding = PADDING.to_cpx_height(screen_area_handle);
+        let bottom_cy = screen_area_handle.bottom_cy() - c_line_width;
+        let top_cy = screen_area_handle.top_cy() + c_line_width;
+        let (left_cx, right_cx) = (
+            screen_area_handle.left_cx() + c_line_width,
+            screen_area_handle.right_cx() - c_line_width,
+        );
+        let mut desired_cy = match position {
+            TooltipPosition::Follow => pointer_cy,
+            TooltipPosition::TopRight | TooltipPosition::TopLeft => top_cy,
+            // below the point when there is no room above it
+            TooltipPosition::Anchored if anchor_cy - c_padding - tooltip_height < top_cy => {
+                anchor_cy + c_padding
+            }
+            TooltipPosition::Anchored => anchor_cy - c_padding - tooltip_height,
+        };
+        let (mut tooltip_x, mut tooltip_y) = (0.0, 0.0);
+        for _ in 0..=self.placed.len() {
+            (tooltip_x, tooltip_y) = match position {
+                TooltipPosition::Follow => place_rect_inside(
+                    pointer_cx,
+                    desired_cy,
+                    tooltip_width,
+                    tooltip_height,
+                    c_line_width,
+                    right_cx,
+                    bottom_cy,
+                    c_expected_shift_x,
+                ),
+                TooltipPosition::TopRight => (
+                    (right_cx - tooltip_width).max(left_cx),
+                    desired_cy.min(bottom_cy - tooltip_height),
+                ),
+                TooltipPosition::TopLeft => (left_cx, desired_cy.min(bottom_cy - tooltip_height)),
+                TooltipPosition::Anchored => (
+                    (anchor_cx - tooltip_width * 0.5)
+                        .min(right_cx - tooltip_width)
+                        .max(left_cx),
+                    desired_cy.min(bottom_cy - tooltip_height),
+                ),
+            };
+            let rect = ScreenRect::from_width(tooltip_x, tooltip_y, tooltip_width, tooltip_height);
+            match self.placed.iter().find(|placed| placed.intersects(&rect)) {
+                // moving below the overlapped one, unless it's at the bottom already
+                Some(placed) if placed.cy2 + c_padding < bottom_cy - tooltip_height => {
+                    desired_cy = placed.cy2 + c_padding;
+                }
+                _ => break,
+            }
+        }
+        self.placed.push(ScreenRect::from_width(
+            tooltip_x,
+            tooltip_y,
+            tooltip_width,
+            tooltip_height,
+        ));
+        (tooltip_x, tooltip_y)
+    }
+
+    fn draw_match_circles<T>(
+        conf: &ChartConfig,
+        coord_space_handle: &CoordSpaceHandle<T>,
+        matches: &[(&DataSet, &DataPoint)],
+        index_with_min_diff_by_value: usize,
+    ) where
+        T: Scale,
+    {
+        let screen_area_handle = coord_space_handle.screen_area_handle.as_ref();
+        let crc = screen_area_handle.crc.as_ref();
+        let transparent_color = "rgba(0, 0, 0, 0)";
+        for (index, (data_set, data_point)) in matches.iter().enumerate() {
+            let color = data_set.to_css_color(1.0);
+            crc.begin_path();
+            crc.set_line_width(conf.line_width.to_cpx_height(screen_area_handle));
+            if index_with_min_diff_by_value == index {
+                crc.set_fill_style(&color);
+            } else {
+                crc.set_fill_style(transparent_color);
+            }
+
+            crc.set_stroke_style(&color);
+            crc.arc(
+                coord_space_handle.get_cx(data_point.coord),
+                coord_space_handle.get_cy(data_point.value),
+                conf.circle_diameter.to_cpx_height(screen_area_handle),
+                0.0,
+                PI * 2.0,
+            );
+            crc.fill();
+            crc.stroke();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animate::Animation;
+    use crate::params::DataType;
+    use crate::render::RecordingRenderer;
+    use crate::scale::LinearScale;
+    use std::str::FromStr;
+
+    // a 200x100 screen, its canvas is scaled by css_to_physical_scale
+    fn screen_area_handle(
+        renderer: &Rc<RecordingRenderer>,
+        css_to_physical_scale: f64,
+    ) -> ScreenAreaHandle {
+        ScreenAreaHandle {
+            crc: Rc::clone(renderer) as Rc<dyn crate::render::Renderer>,
+            screen_width: 200.0,
+            screen_height: 100.0,
+            css_to_physical_scale,
+            font_height_to_physical_scale: css_to_physical_scale,
+            font_width_to_physical_scale: css_to_physical_scale,
+            outer_padding: [0.0; 4],
+            canvas_content_width: 200.0 * css_to_physical_scale,
+            canvas_content_height: 100.0 * css_to_physical_scale,
+            canvas_padding: [0.0; 4],
+        }
+    }
+
+    #[test]
+    fn test_truncate_name() {
+        assert_eq!(Tooltip::truncate_name("requests", 8), "requests");
+        assert_eq!(Tooltip::truncate_name("requests", 20), "requests");
+        assert_eq!(Tooltip::truncate_name("requests", 5), "requ…");
+        assert_eq!(Tooltip::truncate_name("requests", 1), "…");
+        assert_eq!(Tooltip::truncate_name("запросы", 4), "зап…");
+    }
+
+    #[test]
+    fn test_draw_compact() {
+        let renderer = Rc::new(RecordingRenderer::new(1.0));
+        let screen_area_handle = screen_area_handle(&renderer, 1.0);
+        Tooltip::draw_compact(
+            &ChartConfig::default(),
+            &screen_area_handle,
+            (10.0, 20.0),
+            (100.0, 30.0),
+            "a: 1",
+        );
+        assert_eq!(
+            renderer.take_calls(),
+            vec![
+                "set_fill_style(\"rgba(255, 255, 255, 1)\")",
+                "fill_rect(10, 20, 100, 30)",
+                "set_fill_style(\"rgba(0, 0, 0, 1)\")",
+                "set_font(\"10px monospace\")",
+                "set_text_baseline(\"middle\")",
+                "set_text_align(\"center\")",
+                "fill_text(\"a: 1\", 60, 35)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_place() {
+        let screen_area_handle = screen_area_handle(&Rc::new(RecordingRenderer::new(1.0)), 1.0);
+        let mut tooltip = Tooltip::new(Rc::new(RefCell::new(ChartConfig::default())));
+        let mut place = |position: TooltipPosition, anchor: (f64, f64)| {
+            tooltip.placed.clear();
+            tooltip.place(
+                &screen_area_handle,
+                position,
+                (50.0, 50.0),
+                anchor,
+                (40.0, 20.0),
+                10.0,
+            )
+        };
+        assert_eq!(place(TooltipPosition::Follow, (0.0, 0.0)), (60.0, 50.0));
+        assert_eq!(place(TooltipPosition::TopRight, (0.0, 0.0)), (159.0, 1.0));
+        assert_eq!(place(TooltipPosition::TopLeft, (0.0, 0.0)), (1.0, 1.0));
+        assert_eq!(
+            place(TooltipPosition::Anchored, (100.0, 60.0)),
+            (80.0, 35.0)
+        );
+        // no room above the point
+        assert_eq!(
+            place(TooltipPosition::Anchored, (195.0, 10.0)),
+            (159.0, 15.0)
+        );
+        assert!(TooltipPosition::from_str("bottom").is_err());
+    }
+
+    #[test]
+    fn test_match_nearest() {
+        let mut content = Content::default();
+        content
+            .parse_and_add_data_set(
+                "dense",
+                vec![1.0, 2.0, 3.0, 4.0],
+                vec![1.0; 4],
+                (0, 0, 0),
+                0.0,
+            )
+            .unwrap();
+        content
+            .parse_and_add_data_set("sparse", vec![1.0, 10.0], vec![2.0, 3.0], (0, 0, 0), 0.0)
+            .unwrap();
+        content
+            .parse_and_add_data_set(
+                "gappy",
+                vec![2.0, 3.0, 8.0],
+                vec![4.0, f64::NAN, 5.0],
+                (0, 0, 0),
+                0.0,
+            )
+            .unwrap();
+        content
+            .parse_and_add_data_set("hidden", vec![3.0], vec![6.0], (0, 0, 0), 0.0)
+            .unwrap();
+        content.data_sets[3]
+            .alpha
+            .set_value(0.0, None, Animation::default());
+
+        let (matched_coord, matches) = Tooltip::match_nearest(&content, 3.2).unwrap();
+        assert_eq!(matched_coord, 3.0);
+        // every visible series shows up, undefined values are skipped
+        assert_eq!(
+            matches
+                .iter()
+                .map(|(data_set, data_point)| (data_set.name.as_str(), data_point.coord))
+                .collect::<Vec<_>>(),
+            vec![("dense", 3.0), ("sparse", 1.0), ("gappy", 2.0)]
+        );
+        assert!(TooltipMatch::from_str("nearest").is_ok());
+        assert!(TooltipMatch::from_str("closest").is_err());
+
+        content
+            .data_sets
+            .iter_mut()
+            .for_each(|data_set| data_set.alpha.set_value(0.0, None, Animation::default()));
+        assert!(Tooltip::match_nearest(&content, 3.2).is_none());
+    }
+
+    #[test]
+    fn test_report_n_notify() {
+        let screen_area_handle = screen_area_handle(&Rc::new(RecordingRenderer::new(2.0)), 2.0);
+        let conf = ChartConfig::default();
+        let mut content = Content::new(None, DataType::Number, DataType::Number, &conf).unwrap();
+        content
+            .parse_and_add_data_set("a", vec![1.0, 2.0], vec![3.0, 4.0], (255, 0, 0), 0.0)
+            .unwrap();
+        let data_set = &content.data_sets[0];
+        let data_point = DataPoint {
+            coord: 2.0,
+            value: 4.0,
+        };
+        let mut tooltip = Tooltip::new(Rc::new(RefCell::new(conf)));
+        let report = |tooltip: &mut Tooltip, pointer: ScreenPos| {
+            tooltip.reported.clear();
+            tooltip.report(
+                &screen_area_handle,
+                (2.0, "2"),
+                &[(data_set, &data_point)],
+                &["4".to_string()],
+                &pointer,
+                (100.0, 60.0),
+            );
+        };
+        report(&mut tooltip, ScreenPos(40.0, 20.0));
+        assert_eq!(tooltip.reported.len(), 1);
+        let reported = &tooltip.reported[0];
+        assert_eq!(
+            (reported.coord, reported.formatted_coord.as_str()),
+            (2.0, "2")
+        );
+        assert_eq!(
+            reported.rows,
+            vec![(
+                "a".to_string(),
+                4.0,
+                "4".to_string(),
+                data_set.to_css_color(1.0)
+            )]
+        );
+        // the pointer is in css pixels already, the point gets converted
+        assert_eq!(reported.pointer, (40.0, 20.0));
+        assert_eq!(reported.point, (50.0, 30.0));
+
+        // the callback is only due when what's shown changes
+        assert!(tooltip.take_reported_changes());
+        report(&mut tooltip, ScreenPos(40.0, 20.0));
+        assert!(!tooltip.take_reported_changes());
+        report(&mut tooltip, ScreenPos(41.0, 20.0));
+        assert!(tooltip.take_reported_changes());
+        // and once more when it's hidden
+        tooltip.reported.clear();
+        assert!(tooltip.take_reported_changes());
+        assert!(!tooltip.take_reported_changes());
+    }
+
+    #[test]
+    fn test_draw_spike_line() {
+        let renderer = Rc::new(RecordingRenderer::new(1.0));
+        let screen_area_handle = screen_area_handle(&renderer, 1.0);
+        let conf = ChartConfig::default();
+        let mut content = Content::new(None, DataType::Number, DataType::Number, &conf).unwrap();
+        content
+            .parse_and_add_data_set("a", vec![0.0, 10.0], vec![0.0, 10.0], (0, 0, 0), 0.0)
+            .unwrap();
+        let coord_space_handle = CoordSpaceHandle {
+            screen_area_handle: Rc::new(screen_area_handle),
+            scale: Rc::new(LinearScale::new(&content)),
+        };
+        Tooltip::draw_spike_line(
+            &conf,
+            &coord_space_handle,
+            &DataPoint {
+                coord: 10.0,
+                value: 5.0,
+            },
+            "red",
+            "10",
+        );
+        let calls = renderer.take_calls();
+        assert_eq!(
+            calls[3..6],
+            ["move_to(200, 50)", "line_to(200, 100)", "stroke()"]
+        );
+        // the label is kept within the plot
+        assert!(calls.contains(&"fill_rect(170, 100, 30, 20)".to_string()));
+        assert_eq!(calls.last().unwrap(), "fill_text(\"10\", 185, 110)");
+    }
 }
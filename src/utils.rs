@@ -7,8 +7,15 @@
  */
 use crate::screen::ScreenPos;
 
-pub fn is_click(pos1: &ScreenPos, pos2: &ScreenPos) -> bool {
-    pos1.0 == pos2.0 && pos1.1 == pos2.1
+// fingers jitter during a tap, so exact equality is too strict
+pub fn is_click(pos1: &ScreenPos, pos2: &ScreenPos, threshold: f64) -> bool {
+    !is_drag(pos1, pos2, threshold)
+}
+
+pub fn is_drag(pos1: &ScreenPos, pos2: &ScreenPos, threshold: f64) -> bool {
+    let dx = pos1.0 - pos2.0;
+    let dy = pos1.1 - pos2.1;
+    dx * dx + dy * dy > threshold * threshold
 }
 
 pub fn place_rect_inside(
@@ -30,3 +37,22 @@ pub fn place_rect_inside(
     let y = desired_y.min(y_max - height);
     (x, y)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::screen::ScreenPos;
+    use crate::utils::{is_click, is_drag};
+
+    #[test]
+    fn test_is_click() {
+        let down = ScreenPos(10.0, 10.0);
+        assert!(is_click(&down, &ScreenPos(10.0, 10.0), 0.0));
+        assert!(!is_click(&down, &ScreenPos(10.5, 10.0), 0.0));
+
+        // within the threshold by distance, not per axis
+        assert!(is_click(&down, &ScreenPos(13.0, 14.0), 5.0));
+        assert!(!is_click(&down, &ScreenPos(14.0, 14.0), 5.0));
+        assert!(is_drag(&down, &ScreenPos(14.0, 14.0), 5.0));
+        assert!(is_click(&down, &ScreenPos(6.0, 7.0), 5.0));
+    }
+}
@@ -0,0 +1,61 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ *
+ * Copyright (C) 2023, Nikita Almakov
+ */
+use plotica::animate::Animation;
+use plotica::grid::Grid;
+use plotica::params::{ChartConfig, Content, DataType};
+use plotica::scale::{LinearScale, Scale};
+
+fn make_content() -> Content {
+    let mut content = Content::new(
+        None,
+        DataType::Number,
+        DataType::Number,
+        &ChartConfig::default(),
+    )
+    .unwrap();
+    content
+        .parse_and_add_data_set(
+            "a",
+            (0..101).map(|i| i as f64).collect(),
+            (0..101).map(|i| (i * 2) as f64).collect(),
+            (0, 0, 0),
+            0.0,
+        )
+        .unwrap();
+    content.recompute_global_bounds();
+    content
+}
+
+#[test]
+fn test_scale_round_trip() {
+    let content = make_content();
+    let scale = LinearScale::new(&content);
+    for coord in [0.0, 25.0, 100.0] {
+        let normalized = scale.normalize_coord(coord);
+        assert!((scale.denormalize_coord(normalized) - coord).abs() < 1e-9);
+    }
+    let data_set = &content.data_sets[0];
+    let index = data_set.bin_search_right_bound(42.4).unwrap();
+    assert_eq!(data_set.data_points[index].coord, 42.0);
+}
+
+#[test]
+fn test_grid_ticks() {
+    let content = make_content();
+    let mut grid = Grid::new(
+        DataType::Number,
+        content.global_coord_min,
+        content.global_coord_max,
+        None,
+    );
+    let ticks = grid.get_ticks(0.0, Animation::default(), 0.0, 1.0, 5.0);
+    assert!(!ticks.is_empty());
+    assert!(ticks
+        .windows(2)
+        .all(|pair| pair[0].normalized_value < pair[1].normalized_value));
+}